@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::AdaptiveSettings;
+
+/// Trigger and reset thresholds in force on one channel, as exposed by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveThreshold {
+    pub trigger_name: String,
+    pub channel: String,
+    pub threshold: f64,
+    pub reset: f64,
+    /// Current background noise RMS (trigger units), when adaptive
+    pub noise: Option<f64>,
+    /// Noise level at which the configured thresholds apply, when adaptive
+    pub reference: Option<f64>,
+    /// Factor applied to the configured thresholds
+    pub scale: f64,
+}
+
+/// Rolling background noise of one channel, overall and per hour of day, used to
+/// scale the configured thresholds between `min_scale` and `max_scale`.
+#[derive(Debug, Clone)]
+pub struct NoiseTracker {
+    settings: AdaptiveSettings,
+    /// Mean square over the last `window` seconds (exponential)
+    recent: Option<f64>,
+    /// Long-term mean square, the reference when none is configured
+    long_term: Option<f64>,
+    /// Mean square of each `window` block over the last `learning_days`, per local hour of day
+    hourly: [VecDeque<f64>; 24],
+    /// Sum of squares, sample count and seconds of the block being accumulated
+    block: (f64, usize, f64),
+    /// Seconds of quiet data seen so far
+    observed: f64,
+}
+
+impl NoiseTracker {
+    pub fn new(settings: AdaptiveSettings) -> Self {
+        Self { settings, recent: None, long_term: None, hourly: Default::default(), block: (0.0, 0, 0.0), observed: 0.0 }
+    }
+
+    fn hour(&self, time: DateTime<Utc>) -> usize {
+        let local = time + Duration::milliseconds((self.settings.utc_offset * 3_600_000.0) as i64);
+        local.hour() as usize
+    }
+
+    /// Update with quiet (untriggered) samples in trigger units ending at `time`.
+    pub fn update(&mut self, samples: &[f64], sample_rate: f64, time: DateTime<Utc>) {
+        if samples.is_empty() || sample_rate <= 0.0 {
+            return;
+        }
+        let sum_square = samples.iter().map(|x| x * x).sum::<f64>();
+        let mean_square = sum_square / samples.len() as f64;
+        let dt = samples.len() as f64 / sample_rate;
+        let ema = |state: &mut Option<f64>, tau: f64| {
+            let alpha = (dt / tau.max(dt)).min(1.0);
+            *state = Some(state.map_or(mean_square, |s| s + alpha * (mean_square - s)));
+        };
+        ema(&mut self.recent, self.settings.window);
+        ema(&mut self.long_term, self.settings.learning_days * 86_400.0);
+        self.observed += dt;
+
+        // Completed blocks go to the bin of their hour, which keeps `learning_days` of them
+        self.block = (self.block.0 + sum_square, self.block.1 + samples.len(), self.block.2 + dt);
+        if self.block.2 >= self.settings.window {
+            let capacity = ((self.settings.learning_days * 3_600.0 / self.settings.window).ceil() as usize).max(1);
+            let hour = self.hour(time);
+            let bin = &mut self.hourly[hour];
+            bin.push_back(self.block.0 / self.block.1 as f64);
+            while bin.len() > capacity {
+                bin.pop_front();
+            }
+            self.block = (0.0, 0, 0.0);
+        }
+    }
+
+    /// `percentile` of the block levels learned for this hour of day, `None` before any.
+    fn hourly_noise(&self, time: DateTime<Utc>) -> Option<f64> {
+        let mut levels: Vec<f64> = self.hourly[self.hour(time)].iter().copied().collect();
+        if levels.is_empty() {
+            return None;
+        }
+        levels.sort_by(f64::total_cmp);
+        let rank = (self.settings.percentile / 100.0 * (levels.len() - 1) as f64).round() as usize;
+        Some(levels[rank.min(levels.len() - 1)])
+    }
+
+    /// Current noise RMS: the recent level, or the learned level of this hour of day.
+    pub fn noise(&self, time: DateTime<Utc>) -> Option<f64> {
+        let hourly = self.settings.mode.eq_ignore_ascii_case("hourly").then(|| self.hourly_noise(time)).flatten();
+        hourly.or(self.recent).map(f64::sqrt)
+    }
+
+    /// Noise level at which the configured thresholds apply.
+    pub fn reference(&self) -> Option<f64> {
+        if self.settings.reference_noise > 0.0 {
+            Some(self.settings.reference_noise)
+        } else {
+            self.long_term.map(f64::sqrt)
+        }
+    }
+
+    /// Threshold multiplier; 1 until a full `window` of quiet data has been seen.
+    pub fn scale(&self, time: DateTime<Utc>) -> f64 {
+        if self.observed < self.settings.window {
+            return 1.0;
+        }
+        match (self.noise(time), self.reference()) {
+            (Some(noise), Some(reference)) if reference > 0.0 => {
+                (noise / reference).clamp(self.settings.min_scale, self.settings.max_scale)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: &str) -> AdaptiveSettings {
+        AdaptiveSettings { enabled: true, mode: mode.to_string(), window: 60.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_scale_follows_noise_within_bounds() {
+        let mut nt = NoiseTracker::new(AdaptiveSettings { reference_noise: 1.0, ..settings("rms") });
+        let t0 = Utc::now();
+        assert_eq!(nt.scale(t0), 1.0);
+
+        // 10 minutes at twice the reference noise
+        let packet = vec![2.0; 25];
+        for k in 0..2400 {
+            nt.update(&packet, 100.0, t0 + Duration::milliseconds(k * 250));
+        }
+        assert!((nt.scale(t0) - 2.0).abs() < 1e-6, "scale={}", nt.scale(t0));
+
+        // Very loud noise is capped at max_scale
+        for k in 0..2400 {
+            nt.update(&[100.0; 25], 100.0, t0 + Duration::milliseconds(k * 250));
+        }
+        assert_eq!(nt.scale(t0), nt.settings.max_scale);
+    }
+
+    #[test]
+    fn test_hourly_levels() {
+        let mut nt = NoiseTracker::new(AdaptiveSettings { learning_days: 0.01, ..settings("hourly") });
+        let midnight = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        // Quiet nights, noisy afternoons, for two days
+        for day in 0..2 {
+            for hour in 0..24 {
+                let level = if (12..18).contains(&hour) { 4.0 } else { 1.0 };
+                for k in 0..720 {
+                    let t = midnight + Duration::days(day) + Duration::hours(hour) + Duration::seconds(k * 5);
+                    nt.update(&[level; 25], 5.0, t);
+                }
+            }
+        }
+        let night = nt.noise(midnight + Duration::hours(3)).unwrap();
+        let afternoon = nt.noise(midnight + Duration::hours(14)).unwrap();
+        assert!((night - 1.0).abs() < 0.05, "night={}", night);
+        assert!((afternoon - 4.0).abs() < 0.2, "afternoon={}", afternoon);
+        assert!(nt.scale(midnight + Duration::hours(14)) > nt.scale(midnight + Duration::hours(3)));
+    }
+
+    #[test]
+    fn test_hourly_percentile_ignores_bursts() {
+        let mut nt = NoiseTracker::new(settings("hourly"));
+        let t0 = DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        // One hour of 1-minute blocks, every fifth one loud
+        for block in 0..60 {
+            let level = if block % 5 == 0 { 10.0 } else { 1.0 };
+            for k in 0..12 {
+                nt.update(&[level; 25], 5.0, t0 + Duration::seconds(block * 60 + k * 5));
+            }
+        }
+        let median = nt.noise(t0 + Duration::minutes(30)).unwrap();
+        assert!((median - 1.0).abs() < 1e-9, "median={}", median);
+
+        nt.settings.percentile = 95.0;
+        assert_eq!(nt.noise(t0 + Duration::minutes(30)), Some(10.0));
+        // Other hours fall back to the recent level until they have been learned
+        assert!(nt.noise(t0 + Duration::hours(3)).is_some());
+    }
+}
@@ -0,0 +1,205 @@
+use clap::Parser;
+use rsudp_rust::detector::DetectorKind;
+use rsudp_rust::parser::mseed::parse_mseed_file;
+use rsudp_rust::parser::TraceSegment;
+use rsudp_rust::tuning::{parse_labels, run_config, score, TuningGrid, TuningScore};
+use std::path::PathBuf;
+
+/// Replay miniSEED files through the trigger for a grid of settings and report the
+/// triggers of each combination, with precision and recall against labeled events.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// MiniSEED files to replay
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Channel the triggers run on (substring match, as `channel` in [alert])
+    #[arg(long, default_value = "HZ")]
+    channel: String,
+
+    /// Detectors to compare (recursive, classic, delayed, zdetect, amplitude)
+    #[arg(long, value_delimiter = ',', default_value = "recursive")]
+    detector: Vec<String>,
+
+    /// STA lengths (s)
+    #[arg(long, value_delimiter = ',', default_value = "6")]
+    sta: Vec<f64>,
+
+    /// LTA lengths (s)
+    #[arg(long, value_delimiter = ',', default_value = "30")]
+    lta: Vec<f64>,
+
+    /// Trigger thresholds
+    #[arg(long, value_delimiter = ',', default_value = "1.1")]
+    threshold: Vec<f64>,
+
+    /// Reset thresholds
+    #[arg(long, value_delimiter = ',', default_value = "0.5")]
+    reset: Vec<f64>,
+
+    /// Bandpass corners as HIGHPASS-LOWPASS (Hz)
+    #[arg(long, value_delimiter = ',', default_value = "0.1-2.0")]
+    band: Vec<String>,
+
+    /// Butterworth filter order
+    #[arg(long, default_value_t = 4)]
+    corners: usize,
+
+    /// Minimum trigger duration (s)
+    #[arg(long, default_value_t = 0.0)]
+    duration: f64,
+
+    /// Labeled events: one time per line, or a CSV with a time column
+    #[arg(long)]
+    events: Option<PathBuf>,
+
+    /// Largest difference between a trigger and a labeled event (s)
+    #[arg(long, default_value_t = 10.0)]
+    tolerance: f64,
+
+    /// Print every trigger time of each configuration
+    #[arg(long, default_value_t = false)]
+    verbose: bool,
+
+    /// Write CSV instead of a table
+    #[arg(long, default_value_t = false)]
+    csv: bool,
+}
+
+fn parse_band(band: &str) -> Result<(f64, f64), String> {
+    let (hp, lp) = band.split_once('-').ok_or_else(|| format!("band '{}' must be HIGHPASS-LOWPASS", band))?;
+    let hp = hp.trim().parse::<f64>().map_err(|_| format!("invalid highpass in '{}'", band))?;
+    let lp = lp.trim().parse::<f64>().map_err(|_| format!("invalid lowpass in '{}'", band))?;
+    Ok((hp, lp))
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let bands: Vec<(f64, f64)> = match args.band.iter().map(|b| parse_band(b)).collect() {
+        Ok(bands) => bands,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let detectors: Vec<DetectorKind> = match args.detector.iter().map(|d| DetectorKind::try_parse(d).ok_or_else(|| format!("unknown detector '{}'", d))).collect() {
+        Ok(detectors) => detectors,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let grid = TuningGrid {
+        channel: args.channel.clone(),
+        detectors,
+        sta: args.sta.clone(),
+        lta: args.lta.clone(),
+        threshold: args.threshold.clone(),
+        reset: args.reset.clone(),
+        bands,
+        corners: args.corners,
+        duration: args.duration,
+    };
+
+    let mut segments: Vec<TraceSegment> = Vec::new();
+    for path in &args.files {
+        match parse_mseed_file(&path.to_string_lossy()) {
+            Ok(segs) => {
+                eprintln!("Loaded {} segments from {}", segs.len(), path.display());
+                segments.extend(segs);
+            }
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let labels = match &args.events {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(text) => {
+                let labels = parse_labels(&text);
+                eprintln!("Loaded {} labeled events from {}", labels.len(), path.display());
+                Some(labels)
+            }
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let configs = grid.configs();
+    eprintln!("Evaluating {} configurations", configs.len());
+    // Configurations are independent; spread them over the available cores
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = configs.len().div_ceil(workers).max(1);
+    let triggers: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = configs
+            .chunks(chunk)
+            .map(|part| scope.spawn(|| part.iter().map(|c| run_config(c, &segments)).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().expect("tuning worker panicked")).collect()
+    });
+
+    let mut rows: Vec<_> = configs
+        .iter()
+        .zip(triggers)
+        .map(|(config, times)| {
+            let result: Option<TuningScore> = labels.as_ref().map(|l| score(&times, l, args.tolerance));
+            (config, times, result)
+        })
+        .collect();
+    // Best F1 first when scoring, otherwise grid order
+    if labels.is_some() {
+        rows.sort_by(|a, b| {
+            let f1 = |r: &Option<TuningScore>| r.as_ref().map_or(0.0, |s| s.f1);
+            f1(&b.2).total_cmp(&f1(&a.2))
+        });
+    }
+
+    if args.csv {
+        println!("detector,sta,lta,threshold,reset,highpass,lowpass,triggers,true_positives,false_positives,missed,precision,recall,f1");
+    } else {
+        println!(
+            "{:<10} {:>6} {:>6} {:>7} {:>6} {:>11} {:>8} {:>5} {:>5} {:>6} {:>9} {:>7} {:>6}",
+            "detector", "sta", "lta", "thresh", "reset", "band", "triggers", "TP", "FP", "missed", "precision", "recall", "F1"
+        );
+    }
+    for (config, times, result) in &rows {
+        let stats = match result {
+            Some(s) => [
+                s.true_positives.to_string(),
+                s.false_positives.to_string(),
+                s.missed.to_string(),
+                format!("{:.3}", s.precision),
+                format!("{:.3}", s.recall),
+                format!("{:.3}", s.f1),
+            ],
+            None => Default::default(),
+        };
+        let detector = format!("{:?}", config.detector);
+        if args.csv {
+            println!(
+                "{},{},{},{},{},{},{},{},{}",
+                detector, config.sta_sec, config.lta_sec, config.threshold, config.reset_threshold,
+                config.highpass, config.lowpass, times.len(), stats.join(",")
+            );
+        } else {
+            println!(
+                "{:<10} {:>6} {:>6} {:>7} {:>6} {:>11} {:>8} {:>5} {:>5} {:>6} {:>9} {:>7} {:>6}",
+                detector, config.sta_sec, config.lta_sec, config.threshold, config.reset_threshold,
+                format!("{}-{}", config.highpass, config.lowpass), times.len(),
+                stats[0], stats[1], stats[2], stats[3], stats[4], stats[5]
+            );
+        }
+        if args.verbose && !args.csv {
+            for t in times {
+                println!("    trigger {}", t.to_rfc3339());
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::settings::CatalogSettings;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// An earthquake from the reference catalog.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEvent {
+    pub id: String,
+    pub origin_time: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Hypocentre depth (km)
+    pub depth: f64,
+    pub magnitude: Option<f64>,
+}
+
+/// The catalog event a detection was attributed to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogMatch {
+    pub event: CatalogEvent,
+    /// Epicentral distance to the station (km)
+    pub distance: f64,
+    /// Theoretical P arrival at the station
+    pub predicted_p: DateTime<Utc>,
+    /// Detection time minus predicted P arrival (s)
+    pub residual: f64,
+}
+
+impl std::fmt::Display for CatalogMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let magnitude = self.event.magnitude.map(|m| format!("M{:.1} ", m)).unwrap_or_default();
+        write!(
+            f,
+            "{}{} at {:.0} km (origin {}, P residual {:+.1} s)",
+            magnitude, self.event.id, self.distance, self.event.origin_time.format("%Y-%m-%d %H:%M:%S"), self.residual
+        )
+    }
+}
+
+/// Detection performance against the catalog over a period.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CatalogStats {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub detections: usize,
+    /// Detections attributed to a catalog event
+    pub matched: usize,
+    /// Detections with no catalog event
+    pub false_alarms: usize,
+    /// Catalog events within range whose P arrival fell in the period
+    pub expected: usize,
+    /// Expected events without a detection
+    pub missed: usize,
+}
+
+/// Great-circle distance (km) between two points given in degrees.
+pub fn epicentral_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = phi2 - phi1;
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Parse a time in RFC 3339 or "YYYY-MM-DD HH:MM:SS[.f]" (UTC).
+pub(crate) fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y/%m/%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s.trim_end_matches('Z'), fmt).ok())
+        .map(|t| t.and_utc())
+}
+
+/// An `<origin>` of a QuakeML event, with the text of the values it holds.
+#[derive(Debug, Default)]
+struct QuakemlOrigin {
+    public_id: Option<String>,
+    time: Option<String>,
+    latitude: Option<String>,
+    longitude: Option<String>,
+    depth: Option<String>,
+}
+
+/// A QuakeML `<event>` as read, before choosing its origin and magnitude.
+#[derive(Debug, Default)]
+struct QuakemlEvent {
+    public_id: String,
+    preferred_origin: Option<String>,
+    preferred_magnitude: Option<String>,
+    origins: Vec<QuakemlOrigin>,
+    /// (publicID, mag/value)
+    magnitudes: Vec<(Option<String>, Option<String>)>,
+}
+
+impl QuakemlEvent {
+    /// The preferred origin and magnitude, else the first ones.
+    fn to_event(&self) -> Option<CatalogEvent> {
+        let origin = &self.origins[preferred_index(&self.preferred_origin, self.origins.iter().map(|o| &o.public_id))?];
+        let magnitude = preferred_index(&self.preferred_magnitude, self.magnitudes.iter().map(|m| &m.0))
+            .and_then(|i| self.magnitudes[i].1.as_ref());
+        Some(CatalogEvent {
+            id: self.public_id.clone(),
+            origin_time: parse_time(origin.time.as_ref()?)?,
+            latitude: origin.latitude.as_ref()?.parse().ok()?,
+            longitude: origin.longitude.as_ref()?.parse().ok()?,
+            // QuakeML depths are in metres
+            depth: origin.depth.as_ref().and_then(|d| d.parse::<f64>().ok()).unwrap_or(0.0) / 1000.0,
+            magnitude: magnitude.and_then(|m| m.parse().ok()),
+        })
+    }
+}
+
+/// Index of the element whose publicID is `wanted`, else of the first element.
+fn preferred_index<'a>(wanted: &Option<String>, mut ids: impl ExactSizeIterator<Item = &'a Option<String>>) -> Option<usize> {
+    let len = ids.len();
+    ids.position(|id| wanted.is_some() && id == wanted).or((len > 0).then_some(0))
+}
+
+/// Value of the attribute with local name `name`.
+fn local_attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// Events of a QuakeML 1.2 document, using the preferred (else first) origin and magnitude.
+/// Elements are matched by local name, so namespace prefixes do not matter.
+pub fn parse_quakeml(xml: &str) -> Vec<CatalogEvent> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut events = Vec::new();
+    // Local names of the open elements below <event>
+    let mut path: Vec<String> = Vec::new();
+    let mut current: Option<QuakemlEvent> = None;
+    let mut text = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(XmlEvent::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                text.clear();
+                match current.as_mut() {
+                    None if name == "event" => {
+                        current = Some(QuakemlEvent { public_id: local_attribute(&e, b"publicID").unwrap_or_default(), ..Default::default() });
+                        continue;
+                    }
+                    None => continue,
+                    Some(event) if path.is_empty() && name == "origin" => {
+                        event.origins.push(QuakemlOrigin { public_id: local_attribute(&e, b"publicID"), ..Default::default() });
+                    }
+                    Some(event) if path.is_empty() && name == "magnitude" => {
+                        event.magnitudes.push((local_attribute(&e, b"publicID"), None));
+                    }
+                    Some(_) => {}
+                }
+                path.push(name);
+            }
+            Ok(XmlEvent::Text(t)) => match t.unescape() {
+                Ok(t) => text.push_str(&t),
+                Err(e) => warn!("Invalid QuakeML text: {}", e),
+            },
+            Ok(XmlEvent::CData(c)) => text.push_str(String::from_utf8_lossy(&c).trim()),
+            Ok(XmlEvent::End(_)) => {
+                let Some(event) = current.as_mut() else { continue };
+                if path.is_empty() {
+                    match event.to_event() {
+                        Some(parsed) => events.push(parsed),
+                        None => warn!("Skipping QuakeML event '{}' without a usable origin", event.public_id),
+                    }
+                    current = None;
+                    continue;
+                }
+                let value = Some(std::mem::take(&mut text));
+                let path_ref: Vec<&str> = path.iter().map(String::as_str).collect();
+                match path_ref.as_slice() {
+                    ["preferredOriginID"] => event.preferred_origin = value,
+                    ["preferredMagnitudeID"] => event.preferred_magnitude = value,
+                    ["origin", field, "value"] => {
+                        if let Some(origin) = event.origins.last_mut() {
+                            match *field {
+                                "time" => origin.time = value,
+                                "latitude" => origin.latitude = value,
+                                "longitude" => origin.longitude = value,
+                                "depth" => origin.depth = value,
+                                _ => {}
+                            }
+                        }
+                    }
+                    ["magnitude", "mag", "value"] => {
+                        if let Some(magnitude) = event.magnitudes.last_mut() {
+                            magnitude.1 = value;
+                        }
+                    }
+                    _ => {}
+                }
+                path.pop();
+            }
+            Ok(XmlEvent::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Invalid QuakeML at byte {}: {}", reader.error_position(), e);
+                break;
+            }
+        }
+    }
+    events
+}
+
+/// Split a CSV line, honouring double quotes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().expect("at least one field").push(c),
+        }
+    }
+    fields.iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Events of a CSV catalog with a header row. Recognised columns: time (or
+/// origin_time), latitude (lat), longitude (lon), depth (km), mag (magnitude) and
+/// id; the USGS/FDSN text layouts work as they are.
+pub fn parse_csv(text: &str) -> Result<Vec<CatalogEvent>, String> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+    let header: Vec<String> = csv_fields(lines.next().ok_or("empty catalog")?)
+        .iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let time = column(&["time", "origin_time", "origintime", "datetime"]).ok_or("no time column")?;
+    let lat = column(&["latitude", "lat"]).ok_or("no latitude column")?;
+    let lon = column(&["longitude", "lon", "lng"]).ok_or("no longitude column")?;
+    let depth = column(&["depth", "depth_km"]);
+    let mag = column(&["mag", "magnitude"]);
+    let id = column(&["id", "eventid", "event_id"]);
+
+    let mut events = Vec::new();
+    for (n, line) in lines.enumerate() {
+        let fields = csv_fields(line);
+        let get = |i: Option<usize>| i.and_then(|i| fields.get(i)).filter(|f| !f.is_empty());
+        let parsed = (|| {
+            Some(CatalogEvent {
+                id: get(id).cloned().unwrap_or_else(|| format!("row{}", n + 1)),
+                origin_time: parse_time(get(Some(time))?)?,
+                latitude: get(Some(lat))?.parse().ok()?,
+                longitude: get(Some(lon))?.parse().ok()?,
+                depth: get(depth).and_then(|d| d.parse().ok()).unwrap_or(0.0),
+                magnitude: get(mag).and_then(|m| m.parse().ok()),
+            })
+        })();
+        match parsed {
+            Some(event) => events.push(event),
+            None => warn!("Skipping catalog row {}: {}", n + 1, line),
+        }
+    }
+    Ok(events)
+}
+
+/// Reference catalog loaded from a file that is refreshed externally, used to label
+/// detections by their theoretical P arrival at the station. Clones share the events.
+#[derive(Debug, Default, Clone)]
+pub struct Catalog {
+    settings: CatalogSettings,
+    events: Arc<Vec<CatalogEvent>>,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    /// The file was missing at the last check (reported once)
+    missing: bool,
+}
+
+impl Catalog {
+    pub fn new(settings: CatalogSettings) -> Self {
+        let path = (settings.enabled && !settings.path.is_empty()).then(|| PathBuf::from(&settings.path));
+        let catalog = Self { settings, events: Arc::default(), path, modified: None, missing: false };
+        catalog.reloaded().unwrap_or(catalog)
+    }
+
+    /// Catalog with the given events, for replay and tests.
+    pub fn with_events(settings: CatalogSettings, events: Vec<CatalogEvent>) -> Self {
+        Self { settings, events: Arc::new(events), path: None, modified: None, missing: false }
+    }
+
+    pub fn events(&self) -> &[CatalogEvent] {
+        &self.events
+    }
+
+    fn load(path: &Path) -> Result<Vec<CatalogEvent>, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        if text.trim_start().starts_with('<') {
+            Ok(parse_quakeml(&text))
+        } else {
+            parse_csv(&text)
+        }
+    }
+
+    /// The catalog re-read from its file when the file's modification time has changed,
+    /// `None` when nothing changed. `self` is left untouched, so a shared catalog only
+    /// needs locking to clone it and to swap the result in. A missing file keeps the
+    /// events loaded so far and is only reported once.
+    pub fn reloaded(&self) -> Option<Self> {
+        let path = self.path.as_ref()?;
+        let modified = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(e) => {
+                if self.missing {
+                    return None;
+                }
+                warn!("Catalog {} unavailable: {}", path.display(), e);
+                return Some(Self { missing: true, ..self.clone() });
+            }
+        };
+        if modified.is_some() && modified == self.modified {
+            return self.missing.then(|| Self { missing: false, ..self.clone() });
+        }
+        match Self::load(path) {
+            Ok(events) => {
+                info!("Loaded {} catalog events from {}", events.len(), path.display());
+                Some(Self { events: Arc::new(events), modified, missing: false, ..self.clone() })
+            }
+            Err(e) => {
+                warn!("Failed to load catalog {}: {}", path.display(), e);
+                self.missing.then(|| Self { missing: false, ..self.clone() })
+            }
+        }
+    }
+
+    /// Epicentral distance and theoretical P arrival of `event` at the station.
+    fn predicted_p(&self, event: &CatalogEvent) -> (f64, DateTime<Utc>) {
+        let s = &self.settings;
+        let distance = epicentral_distance(s.latitude, s.longitude, event.latitude, event.longitude);
+        let hypocentral = distance.hypot(event.depth);
+        let travel = Duration::milliseconds((hypocentral / s.vp * 1000.0) as i64);
+        (distance, event.origin_time + travel)
+    }
+
+    /// Whether the station could be expected to record `event` (magnitude and distance limits).
+    fn in_range(&self, event: &CatalogEvent, distance: f64) -> bool {
+        let s = &self.settings;
+        event.magnitude.unwrap_or(f64::INFINITY) >= s.min_magnitude && (s.max_distance <= 0.0 || distance <= s.max_distance)
+    }
+
+    /// The in-range catalog event whose predicted P arrival is closest to `time`,
+    /// within `tolerance` seconds.
+    pub fn match_detection(&self, time: DateTime<Utc>) -> Option<CatalogMatch> {
+        self.events
+            .iter()
+            .filter_map(|event| {
+                let (distance, predicted_p) = self.predicted_p(event);
+                let residual = (time - predicted_p).num_milliseconds() as f64 / 1000.0;
+                (self.in_range(event, distance) && residual.abs() <= self.settings.tolerance)
+                    .then(|| CatalogMatch { event: event.clone(), distance, predicted_p, residual })
+            })
+            .min_by(|a, b| a.residual.abs().total_cmp(&b.residual.abs()))
+    }
+
+    /// Matched/false-alarm/missed counts for the detections made between `start` and `end`.
+    pub fn statistics(&self, detections: &[DateTime<Utc>], start: DateTime<Utc>, end: DateTime<Utc>) -> CatalogStats {
+        let matches: Vec<Option<CatalogMatch>> = detections.iter().map(|&t| self.match_detection(t)).collect();
+        let matched = matches.iter().flatten().count();
+        let expected: Vec<&CatalogEvent> = self
+            .events
+            .iter()
+            .filter(|event| {
+                let (distance, predicted_p) = self.predicted_p(event);
+                self.in_range(event, distance) && predicted_p >= start && predicted_p <= end
+            })
+            .collect();
+        let detected = |event: &CatalogEvent| matches.iter().flatten().any(|m| m.event.id == event.id);
+        CatalogStats {
+            start: Some(start),
+            end: Some(end),
+            detections: detections.len(),
+            matched,
+            false_alarms: detections.len() - matched,
+            expected: expected.len(),
+            missed: expected.iter().filter(|e| !detected(e)).count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAKEML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<q:quakeml xmlns="http://quakeml.org/xmlns/bed/1.2" xmlns:q="http://quakeml.org/xmlns/quakeml/1.2">
+  <eventParameters publicID="smi:local/catalog">
+    <event publicID="smi:local/event/1">
+      <preferredOriginID>smi:local/origin/1b</preferredOriginID>
+      <origin publicID="smi:local/origin/1a">
+        <time><value>2026-01-05T02:29:00Z</value></time>
+        <latitude><value>0.0</value></latitude>
+        <longitude><value>0.0</value></longitude>
+      </origin>
+      <origin publicID="smi:local/origin/1b">
+        <time><value>2026-01-05T02:30:00.000Z</value></time>
+        <latitude><value>35.9</value></latitude>
+        <longitude><value>139.9</value></longitude>
+        <depth><value>10000</value></depth>
+        <originUncertainty><horizontalUncertainty>500</horizontalUncertainty></originUncertainty>
+      </origin>
+      <magnitude publicID="smi:local/magnitude/1"><mag><value>4.6</value></mag></magnitude>
+    </event>
+  </eventParameters>
+</q:quakeml>"#;
+
+    /// Prefixed elements, an event without attributes, comments and CDATA.
+    const QUAKEML_PREFIXED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<q:quakeml xmlns:q="http://quakeml.org/xmlns/quakeml/1.2" xmlns:bed="http://quakeml.org/xmlns/bed/1.2">
+  <bed:eventParameters publicID="smi:local/catalog">
+    <!-- <event publicID="smi:local/commented"> -->
+    <bed:event>
+      <bed:origin publicID="smi:local/origin/2">
+        <bed:time><bed:value><![CDATA[2026-02-01T10:00:00Z]]></bed:value></bed:time>
+        <bed:latitude><bed:value>36.5</bed:value></bed:latitude>
+        <bed:longitude><!-- degrees east --><bed:value>140.5</bed:value></bed:longitude>
+      </bed:origin>
+      <bed:magnitude><bed:mag><bed:value>3.2</bed:value></bed:mag></bed:magnitude>
+    </bed:event>
+    <bed:event publicID="smi:local/event/3"><bed:type>not existing</bed:type></bed:event>
+  </bed:eventParameters>
+</q:quakeml>"#;
+
+    fn settings() -> CatalogSettings {
+        CatalogSettings { enabled: true, latitude: 35.0, longitude: 139.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_parse_quakeml_and_csv() {
+        let events = parse_quakeml(QUAKEML);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "smi:local/event/1");
+        assert_eq!(events[0].latitude, 35.9);
+        assert_eq!(events[0].depth, 10.0);
+        assert_eq!(events[0].magnitude, Some(4.6));
+
+        let events = parse_quakeml(QUAKEML_PREFIXED);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "");
+        assert_eq!(events[0].origin_time, parse_time("2026-02-01T10:00:00Z").unwrap());
+        assert_eq!((events[0].latitude, events[0].longitude), (36.5, 140.5));
+        assert_eq!(events[0].depth, 0.0);
+        assert_eq!(events[0].magnitude, Some(3.2));
+
+        let csv = "time,latitude,longitude,depth,mag,magType,place,id\n\
+                   2026-01-05T02:30:00.000Z,35.9,139.9,10,4.6,mb,\"10 km N of Somewhere, Japan\",us7000abcd\n\
+                   2026-01-05 03:00:00,36.1,140.2,,,ml,,\n";
+        let events = parse_csv(csv).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, "us7000abcd");
+        assert_eq!(events[0].magnitude, Some(4.6));
+        assert_eq!(events[1].depth, 0.0);
+        assert!(parse_csv("latitude,longitude\n1,2\n").is_err());
+    }
+
+    #[test]
+    fn test_matches_detections_by_predicted_p() {
+        let catalog = Catalog::with_events(settings(), parse_quakeml(QUAKEML));
+        let event = &catalog.events()[0];
+        let (distance, predicted_p) = catalog.predicted_p(event);
+        assert!((distance - 128.0).abs() < 2.0, "distance={}", distance);
+        assert!((predicted_p - event.origin_time).num_seconds() >= 20);
+
+        let matched = catalog.match_detection(predicted_p + Duration::seconds(2)).unwrap();
+        assert!((matched.residual - 2.0).abs() < 1e-3);
+        assert!(catalog.match_detection(event.origin_time - Duration::minutes(5)).is_none());
+
+        let start = event.origin_time - Duration::hours(1);
+        let end = event.origin_time + Duration::hours(1);
+        let stats = catalog.statistics(&[predicted_p, start + Duration::minutes(5)], start, end);
+        assert_eq!((stats.matched, stats.false_alarms, stats.expected, stats.missed), (1, 1, 1, 0));
+        let stats = catalog.statistics(&[], start, end);
+        assert_eq!(stats.missed, 1);
+    }
+
+    #[test]
+    fn test_reloads_only_when_the_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catalog.csv");
+        let header = "time,latitude,longitude,depth,mag\n";
+        std::fs::write(&path, format!("{}2026-01-05T02:30:00Z,35.9,139.9,10,4.6\n", header)).unwrap();
+
+        let catalog = Catalog::new(CatalogSettings { path: path.to_string_lossy().into_owned(), ..settings() });
+        assert_eq!(catalog.events().len(), 1);
+        assert!(catalog.reloaded().is_none());
+
+        std::fs::write(&path, format!("{}2026-01-05T02:30:00Z,35.9,139.9,10,4.6\n2026-01-06T00:00:00Z,36.0,140.0,20,3.1\n", header)).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+        let reloaded = catalog.reloaded().unwrap();
+        assert_eq!(reloaded.events().len(), 2);
+        // The original is unchanged until the reload is swapped in
+        assert_eq!(catalog.events().len(), 1);
+
+        // A missing file keeps the loaded events
+        std::fs::remove_file(&path).unwrap();
+        let missing = reloaded.reloaded().unwrap();
+        assert_eq!(missing.events().len(), 2);
+        assert!(missing.reloaded().is_none());
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::info;
+
+use crate::picker::PhasePick;
+use crate::settings::CoincidenceSettings;
+use crate::trigger::{AlertEvent, AlertEventType};
+
+/// Key under which the network-level alert is tracked by the pipeline.
+pub const NETWORK_ID: &str = "NETWORK";
+
+/// Combines per-station `TriggerManager` alerts into a single network-level alert,
+/// in the spirit of ObsPy's `coincidence_trigger`: an event is declared once the
+/// summed weight of stations (or channels) that triggered within `window` seconds
+/// reaches `min_sum`, and reset once every contributor has reset.
+pub struct CoincidenceManager {
+    settings: CoincidenceSettings,
+    /// Trigger-on time of each currently triggered (trigger name, channel id)
+    active: HashMap<(String, String), DateTime<Utc>>,
+    /// P picks of the currently triggered (trigger name, channel id)
+    picks: HashMap<(String, String), PhasePick>,
+    /// Stations/channels that contributed to the declared network event
+    declared: Option<Vec<String>>,
+    max_sum: f64,
+}
+
+impl CoincidenceManager {
+    pub fn new(settings: CoincidenceSettings) -> Self {
+        info!(
+            "Coincidence trigger '{}': min_sum={}, window={}s, group_by={}",
+            settings.name, settings.min_sum, settings.window, settings.group_by
+        );
+        Self { settings, active: HashMap::new(), picks: HashMap::new(), declared: None, max_sum: 0.0 }
+    }
+
+    /// Station or channel key of a `NET.STA.LOC.CHA` id, depending on `group_by`.
+    fn group_key(&self, id: &str) -> String {
+        if self.settings.group_by.eq_ignore_ascii_case("channel") {
+            return id.to_string();
+        }
+        let mut parts = id.split('.');
+        match (parts.next(), parts.next()) {
+            (Some(net), Some(sta)) => format!("{}.{}", net, sta),
+            _ => id.to_string(),
+        }
+    }
+
+    /// Weight of a station/channel: exact id, then NET.STA, then STA, else 1.0.
+    fn weight(&self, key: &str) -> f64 {
+        let weights = &self.settings.weights;
+        if let Some(&w) = weights.get(key) {
+            return w;
+        }
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.len() >= 2 {
+            if let Some(&w) = weights.get(&format!("{}.{}", parts[0], parts[1])) {
+                return w;
+            }
+            if let Some(&w) = weights.get(parts[1]) {
+                return w;
+            }
+        }
+        1.0
+    }
+
+    /// Distinct stations/channels currently triggered, sorted.
+    fn active_groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self.active.keys().map(|(_, id)| self.group_key(id)).collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    /// First P arrival among the triggered channels, reported for the network event.
+    fn earliest_pick(&self) -> Option<PhasePick> {
+        self.picks.values().min_by_key(|pick| pick.time).cloned()
+    }
+
+    fn coincidence_sum(&self) -> f64 {
+        self.active_groups().iter().map(|k| self.weight(k)).sum()
+    }
+
+    /// Feed a station-level alert. STATUS events pass through unchanged; TRIGGER and
+    /// RESET are absorbed and only the network-level TRIGGER/RESET is returned.
+    pub fn process(&mut self, alert: AlertEvent) -> Option<AlertEvent> {
+        match alert.event_type {
+            AlertEventType::Status | AlertEventType::Early => Some(alert),
+            AlertEventType::Trigger => {
+                info!("Coincidence: {} triggered ({}) at {}", alert.channel, alert.trigger_name, alert.timestamp);
+                let key = (alert.trigger_name.clone(), alert.channel.clone());
+                self.active.entry(key.clone()).or_insert(alert.timestamp);
+                if let Some(pick) = alert.pick.clone() {
+                    self.picks.entry(key).or_insert(pick);
+                }
+
+                if self.declared.is_some() {
+                    self.max_sum = self.max_sum.max(self.coincidence_sum());
+                    return None;
+                }
+
+                // Contributors must lie within `window` of the newest trigger
+                let window = Duration::milliseconds((self.settings.window * 1000.0) as i64);
+                self.active.retain(|_, &mut t| alert.timestamp - t <= window);
+                self.picks.retain(|id, _| self.active.contains_key(id));
+
+                let sum = self.coincidence_sum();
+                if sum >= self.settings.min_sum {
+                    let members = self.active_groups();
+                    info!("Coincidence: network event declared (sum {:.2}): {}", sum, members.join(","));
+                    self.max_sum = sum;
+                    self.declared = Some(members.clone());
+                    return Some(AlertEvent {
+                        timestamp: alert.timestamp,
+                        channel: members.join(","),
+                        trigger_name: self.settings.name.clone(),
+                        event_type: AlertEventType::Trigger,
+                        ratio: sum,
+                        max_ratio: sum,
+                        message: "COINCIDENCE".to_string(),
+                        pick: self.earliest_pick(),
+                        early_warning: None,
+                    });
+                }
+                None
+            }
+            AlertEventType::Reset => {
+                let key = (alert.trigger_name.clone(), alert.channel.clone());
+                self.active.remove(&key);
+                self.picks.remove(&key);
+                let members = self.declared.as_ref()?;
+                // The network event lasts until every contributor has reset
+                let still_active = self.active_groups();
+                if members.iter().any(|m| still_active.contains(m)) {
+                    return None;
+                }
+                let members = self.declared.take().unwrap_or_default();
+                let max_sum = std::mem::take(&mut self.max_sum);
+                info!("Coincidence: network event reset (max sum {:.2})", max_sum);
+                Some(AlertEvent {
+                    timestamp: alert.timestamp,
+                    channel: members.join(","),
+                    trigger_name: self.settings.name.clone(),
+                    event_type: AlertEventType::Reset,
+                    ratio: self.coincidence_sum(),
+                    max_ratio: max_sum,
+                    message: "COINCIDENCE RESET".to_string(),
+                    pick: None,
+                    early_warning: None,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, event_type: AlertEventType, t: DateTime<Utc>) -> AlertEvent {
+        named_event("default", id, event_type, t)
+    }
+
+    fn named_event(name: &str, id: &str, event_type: AlertEventType, t: DateTime<Utc>) -> AlertEvent {
+        AlertEvent {
+            timestamp: t,
+            channel: id.to_string(),
+            trigger_name: name.to_string(),
+            event_type,
+            ratio: 2.0,
+            max_ratio: 2.0,
+            message: String::new(),
+            pick: None,
+            early_warning: None,
+        }
+    }
+
+    #[test]
+    fn test_declares_when_enough_stations_coincide() {
+        let mut cm = CoincidenceManager::new(CoincidenceSettings::default());
+        let t0 = Utc::now();
+
+        // One station (two channels) alone is not enough with group_by = "station"
+        assert!(cm.process(event("AM.R0001.00.EHZ", AlertEventType::Trigger, t0)).is_none());
+        assert!(cm.process(event("AM.R0001.00.ENZ", AlertEventType::Trigger, t0)).is_none());
+
+        let ev = cm
+            .process(event("AM.R0002.00.EHZ", AlertEventType::Trigger, t0 + Duration::seconds(3)))
+            .expect("second station should declare the event");
+        assert_eq!(ev.event_type, AlertEventType::Trigger);
+        assert_eq!(ev.trigger_name, "coincidence");
+        assert_eq!(ev.channel, "AM.R0001,AM.R0002");
+
+        // Reset only after every contributor resets
+        assert!(cm.process(event("AM.R0001.00.EHZ", AlertEventType::Reset, t0 + Duration::seconds(20))).is_none());
+        assert!(cm.process(event("AM.R0002.00.EHZ", AlertEventType::Reset, t0 + Duration::seconds(22))).is_none());
+        let ev = cm
+            .process(event("AM.R0001.00.ENZ", AlertEventType::Reset, t0 + Duration::seconds(25)))
+            .expect("network reset");
+        assert_eq!(ev.event_type, AlertEventType::Reset);
+        assert_eq!(ev.max_ratio, 2.0);
+    }
+
+    #[test]
+    fn test_window_and_weights() {
+        let mut settings = CoincidenceSettings { min_sum: 3.0, ..Default::default() };
+        settings.weights.insert("R0003".to_string(), 2.0);
+        let mut cm = CoincidenceManager::new(settings);
+        let t0 = Utc::now();
+
+        // Too far apart: the first trigger falls out of the window
+        assert!(cm.process(event("AM.R0001.00.EHZ", AlertEventType::Trigger, t0)).is_none());
+        assert!(cm.process(event("AM.R0003.00.EHZ", AlertEventType::Trigger, t0 + Duration::seconds(30))).is_none());
+
+        // Weighted station (2.0) plus one more (1.0) reaches 3.0
+        let ev = cm.process(event("AM.R0002.00.EHZ", AlertEventType::Trigger, t0 + Duration::seconds(32)));
+        assert_eq!(ev.map(|e| e.channel), Some("AM.R0002,AM.R0003".to_string()));
+    }
+
+    #[test]
+    fn test_triggers_on_the_same_channel_are_tracked_separately() {
+        let mut cm = CoincidenceManager::new(CoincidenceSettings::default());
+        let t0 = Utc::now();
+        assert!(cm.process(named_event("local", "AM.R0001.00.EHZ", AlertEventType::Trigger, t0)).is_none());
+        assert!(cm.process(named_event("teleseism", "AM.R0001.00.EHZ", AlertEventType::Trigger, t0)).is_none());
+        // Resetting one definition leaves the other active
+        assert!(cm.process(named_event("local", "AM.R0001.00.EHZ", AlertEventType::Reset, t0 + Duration::seconds(5))).is_none());
+        let ev = cm.process(named_event("local", "AM.R0002.00.EHZ", AlertEventType::Trigger, t0 + Duration::seconds(6)));
+        assert_eq!(ev.map(|e| e.channel), Some("AM.R0001,AM.R0002".to_string()));
+    }
+}
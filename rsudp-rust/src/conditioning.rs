@@ -0,0 +1,352 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::settings::ConditioningSettings;
+
+/// MAD to standard deviation for Gaussian noise.
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// Spread, in baseline standard deviations, within which the level after a shift
+/// counts as steady.
+const STEADY_SPREAD: f64 = 3.0;
+
+/// Kind of instrument artefact found by the conditioning stage. Spikes and DC
+/// steps are removed; clipping is only reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlitchKind {
+    Spike,
+    Clipping,
+    DcStep,
+}
+
+impl std::fmt::Display for GlitchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GlitchKind::Spike => "spike",
+            GlitchKind::Clipping => "clipping",
+            GlitchKind::DcStep => "DC step",
+        })
+    }
+}
+
+/// An artefact found on one channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Glitch {
+    pub kind: GlitchKind,
+    pub channel: String,
+    pub time: DateTime<Utc>,
+    /// Size of the artefact: MAD multiples for spikes and steps, samples for clipping
+    pub size: f64,
+}
+
+impl std::fmt::Display for Glitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size = match self.kind {
+            GlitchKind::Clipping => format!("{} clipped samples", self.size),
+            _ => format!("{:.1} MAD", self.size),
+        };
+        write!(f, "{} on {} at {} ({})", self.kind, self.channel, self.time.format("%H:%M:%S%.3f"), size)
+    }
+}
+
+/// Median and scaled MAD (≈ standard deviation) of `data`.
+pub fn median_mad(data: &[f64]) -> (f64, f64) {
+    let median = |v: &mut Vec<f64>| {
+        v.sort_by(|a, b| a.total_cmp(b));
+        let n = v.len();
+        if n == 0 { 0.0 } else if n.is_multiple_of(2) { (v[n / 2 - 1] + v[n / 2]) / 2.0 } else { v[n / 2] }
+    };
+    let mut sorted = data.to_vec();
+    let med = median(&mut sorted);
+    let mut deviations: Vec<f64> = data.iter().map(|x| (x - med).abs()).collect();
+    (med, median(&mut deviations) * MAD_TO_SIGMA)
+}
+
+/// Samples released by the conditioner for one segment.
+#[derive(Debug, Clone)]
+pub struct Conditioned {
+    /// Time of the first released sample
+    pub start: DateTime<Utc>,
+    pub samples: Vec<f64>,
+    /// Artefacts found in the segment: spikes and steps removed, clipping left in
+    pub glitches: Vec<Glitch>,
+}
+
+struct ChannelState {
+    sample_rate: f64,
+    /// Time of the first sample in `samples`
+    start: DateTime<Utc>,
+    /// Conditioned baseline followed by the samples awaiting a decision
+    samples: VecDeque<f64>,
+    /// Samples before this index have been released
+    released: usize,
+    /// DC offset removed so far, subtracted from every new sample
+    offset: f64,
+    /// Length of the clipped run at the end of the input
+    clipped_run: usize,
+}
+
+impl ChannelState {
+    fn new(sample_rate: f64, start: DateTime<Utc>) -> Self {
+        Self { sample_rate, start, samples: VecDeque::new(), released: 0, offset: 0.0, clipped_run: 0 }
+    }
+
+    fn time_at(&self, index: usize) -> DateTime<Utc> {
+        self.start + Duration::microseconds((index as f64 / self.sample_rate * 1e6) as i64)
+    }
+}
+
+/// Pre-trigger conditioning: replaces spikes (median/MAD outliers a few samples wide)
+/// with the median of the preceding window and subtracts DC steps, so the triggers
+/// only see the cleaned samples. Deciding needs a few samples of lookahead, by which
+/// the output lags the input. Clipping is only detected, for annotating alerts.
+pub struct SignalConditioner {
+    settings: ConditioningSettings,
+    channels: HashMap<String, ChannelState>,
+    /// Time of the latest clipped sample per channel id
+    last_clipped: HashMap<String, DateTime<Utc>>,
+}
+
+impl SignalConditioner {
+    pub fn new(settings: ConditioningSettings) -> Self {
+        info!(
+            "Signal conditioning: spikes > {} MAD, DC steps > {} MAD, clipping at {} counts, delay {} samples",
+            settings.spike_threshold, settings.step_threshold, settings.clip_level, (settings.spike_width + 1).max(settings.step_samples)
+        );
+        Self { settings, channels: HashMap::new(), last_clipped: HashMap::new() }
+    }
+
+    /// Samples of lookahead needed to tell an artefact from signal.
+    fn lookahead(&self) -> usize {
+        (self.settings.spike_width + 1).max(self.settings.step_samples)
+    }
+
+    /// Condition a segment (NET.STA.LOC.CHA id); returns the samples released so far,
+    /// which trail the input by up to `max(spike_width + 1, step_samples)` samples.
+    /// Samples still held back are dropped at a gap or rate change.
+    pub fn process_segment(&mut self, id: &str, start: DateTime<Utc>, sample_rate: f64, samples: &[f64]) -> Conditioned {
+        if sample_rate <= 0.0 {
+            return Conditioned { start, samples: Vec::new(), glitches: Vec::new() };
+        }
+        let window = ((self.settings.window * sample_rate) as usize).max(8);
+        let lookahead = self.lookahead();
+        let state = self.channels.entry(id.to_string()).or_insert_with(|| ChannelState::new(sample_rate, start));
+        // Restart on gaps or rate changes
+        let end = state.time_at(state.samples.len());
+        let gap = (start - end).num_microseconds().unwrap_or(i64::MAX).abs() as f64 / 1e6;
+        if state.sample_rate != sample_rate || gap > 1.5 / sample_rate {
+            *state = ChannelState::new(sample_rate, start);
+        }
+
+        // Clipping: a run of samples at or beyond the digitizer limit, reported once per run
+        let before = state.samples.len();
+        let mut glitches = Vec::new();
+        if self.settings.clip_level > 0.0 {
+            for (i, &x) in samples.iter().enumerate() {
+                if x.abs() >= self.settings.clip_level {
+                    state.clipped_run += 1;
+                    let clip_samples = self.settings.clip_samples.max(1);
+                    if state.clipped_run >= clip_samples {
+                        self.last_clipped.insert(id.to_string(), state.time_at(before + i));
+                    }
+                    if state.clipped_run == clip_samples {
+                        glitches.push(Glitch { kind: GlitchKind::Clipping, channel: id.to_string(), time: state.time_at((before + i + 1).saturating_sub(clip_samples)), size: clip_samples as f64 });
+                    }
+                } else {
+                    state.clipped_run = 0;
+                }
+            }
+        }
+
+        let offset = state.offset;
+        state.samples.extend(samples.iter().map(|x| x - offset));
+        let first = state.released;
+        let len = state.samples.len();
+        while state.released < len {
+            let i = state.released;
+            if i < window {
+                state.released += 1;
+                continue;
+            }
+            let baseline: Vec<f64> = state.samples.range(i - window..i).copied().collect();
+            let (median, sigma) = median_mad(&baseline);
+            let sigma = sigma.max(f64::EPSILON);
+            let limit = self.settings.spike_threshold * sigma;
+            let deviation = |x: f64| (x - median).abs();
+            if deviation(state.samples[i]) <= limit {
+                state.released += 1;
+                continue;
+            }
+            let run = (i..len).take_while(|&j| deviation(state.samples[j]) > limit).count();
+
+            // Spikes: outlier runs of at most `spike_width` samples whose neighbours stay
+            // within half the limit. The margin keeps the crests of a growing oscillation
+            // from looking like spikes.
+            if run <= self.settings.spike_width {
+                if i + run == len {
+                    break; // wait for the end of the run
+                }
+                let quiet = |j: usize| deviation(state.samples[j]) <= limit / 2.0;
+                if quiet(i - 1) && quiet(i + run) {
+                    let size = (i..i + run).map(|j| deviation(state.samples[j])).fold(0.0, f64::max) / sigma;
+                    glitches.push(Glitch { kind: GlitchKind::Spike, channel: id.to_string(), time: state.time_at(i), size });
+                    for j in i..i + run {
+                        state.samples[j] = median;
+                    }
+                }
+                state.released += run;
+                continue;
+            }
+
+            // DC step: the level jumps from a quiet sample and then holds steady for
+            // `step_samples`; a clipped run is left alone
+            let abrupt = deviation(state.samples[i - 1]) <= limit / 2.0;
+            if self.settings.step_threshold > 0.0 && abrupt {
+                if i + lookahead > len {
+                    break; // wait for the new level
+                }
+                let level: Vec<f64> = state.samples.range(i..i + lookahead).copied().collect();
+                let clipped = self.settings.clip_level > 0.0 && level.iter().any(|x| (x + state.offset).abs() >= self.settings.clip_level);
+                let (new_level, spread) = median_mad(&level);
+                let shift = new_level - median;
+                if !clipped && shift.abs() > self.settings.step_threshold * sigma && spread <= STEADY_SPREAD * sigma {
+                    glitches.push(Glitch { kind: GlitchKind::DcStep, channel: id.to_string(), time: state.time_at(i), size: shift.abs() / sigma });
+                    state.offset += shift;
+                    for j in i..len {
+                        state.samples[j] -= shift;
+                    }
+                    continue;
+                }
+            }
+            state.released += run;
+        }
+        let conditioned = Conditioned {
+            start: state.time_at(first),
+            samples: state.samples.range(first..state.released).copied().collect(),
+            glitches,
+        };
+
+        // Keep one window of released history as the baseline
+        while state.released > window {
+            state.samples.pop_front();
+            state.start = state.time_at(1);
+            state.released -= 1;
+        }
+
+        for glitch in &conditioned.glitches {
+            warn!("Glitch detected: {}", glitch);
+        }
+        conditioned
+    }
+
+    /// Channel ids that clipped at or after `since`.
+    pub fn clipped_since(&self, since: DateTime<Utc>) -> Vec<String> {
+        let mut channels: Vec<String> = self.last_clipped.iter().filter(|(_, &time)| time >= since).map(|(id, _)| id.clone()).collect();
+        channels.sort();
+        channels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random noise of unit-ish amplitude.
+    fn noise(n: usize, seed: u64) -> Vec<f64> {
+        let mut s = seed;
+        (0..n)
+            .map(|_| {
+                s = s.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((s >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 200.0
+            })
+            .collect()
+    }
+
+    fn t0() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    /// Feed `data` at 100 Hz in 0.25 s segments; returns the released samples and the glitches.
+    fn feed(sc: &mut SignalConditioner, data: &[f64]) -> (Vec<f64>, Vec<Glitch>) {
+        let mut out = Vec::new();
+        let mut glitches = Vec::new();
+        for (k, chunk) in data.chunks(25).enumerate() {
+            let start = t0() + Duration::milliseconds(k as i64 * 250);
+            let conditioned = sc.process_segment("AM.R0001.00.EHZ", start, 100.0, chunk);
+            // Released samples continue the output without a gap
+            assert_eq!(conditioned.start, t0() + Duration::milliseconds(out.len() as i64 * 10));
+            out.extend(conditioned.samples);
+            glitches.extend(conditioned.glitches);
+        }
+        (out, glitches)
+    }
+
+    #[test]
+    fn test_replaces_spike_with_median() {
+        let mut sc = SignalConditioner::new(ConditioningSettings::default());
+        let clean = noise(1000, 7);
+        let mut data = clean.clone();
+        data[600] += 50_000.0;
+        let (out, glitches) = feed(&mut sc, &data);
+        assert_eq!(glitches.len(), 1, "{:?}", glitches);
+        assert_eq!(glitches[0].kind, GlitchKind::Spike);
+        assert_eq!(glitches[0].time, t0() + Duration::seconds(6));
+
+        // Everything but the lookahead is released, and only the spike is changed
+        assert!(out.len() >= data.len() - sc.lookahead());
+        assert!(out[600].abs() < 100.0);
+        for (i, (o, c)) in out.iter().zip(&clean).enumerate().filter(|&(i, _)| i != 600) {
+            assert_eq!(o, c, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn test_earthquake_passes_unchanged() {
+        let mut sc = SignalConditioner::new(ConditioningSettings::default());
+        // Emergent oscillation growing to 100 times the noise
+        let data: Vec<f64> = noise(3000, 3)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let grow = if i < 1500 { 0.0 } else { ((i - 1500) as f64 / 100.0).min(1.0) * 10_000.0 };
+                x + grow * (2.0 * std::f64::consts::PI * 3.0 * i as f64 / 100.0).sin()
+            })
+            .collect();
+        let (out, glitches) = feed(&mut sc, &data);
+        assert!(glitches.is_empty(), "{:?}", glitches);
+        assert_eq!(out[..], data[..out.len()]);
+    }
+
+    #[test]
+    fn test_removes_dc_step() {
+        let mut sc = SignalConditioner::new(ConditioningSettings::default());
+        let mut data = noise(1500, 11);
+        for x in data.iter_mut().skip(800) {
+            *x += 20_000.0;
+        }
+        let (out, glitches) = feed(&mut sc, &data);
+        assert_eq!(glitches.len(), 1, "{:?}", glitches);
+        assert_eq!(glitches[0].kind, GlitchKind::DcStep);
+        let (after, _) = median_mad(&out[800..]);
+        assert!(after.abs() < 100.0, "level after the step: {}", after);
+    }
+
+    #[test]
+    fn test_reports_clipping_without_removing_it() {
+        let mut sc = SignalConditioner::new(ConditioningSettings::default());
+        let mut data = noise(1000, 5);
+        for x in data.iter_mut().skip(500).take(20) {
+            *x = 8_388_607.0;
+        }
+        let (out, glitches) = feed(&mut sc, &data);
+        assert_eq!(glitches.len(), 1, "{:?}", glitches);
+        assert_eq!(glitches[0].kind, GlitchKind::Clipping);
+        assert!(out[500..520].iter().all(|&x| x == 8_388_607.0));
+        assert_eq!(sc.clipped_since(t0() + Duration::seconds(4)), vec!["AM.R0001.00.EHZ"]);
+        assert!(sc.clipped_since(t0() + Duration::seconds(6)).is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A detection algorithm evaluated over a freshly filtered window of samples.
+///
+/// The characteristic function (CF) has one value per input sample; values inside
+/// the first `warmup()` samples are not meaningful and are zeroed, like ObsPy does.
+pub trait Detector: Send + Sync {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64>;
+    fn warmup(&self) -> usize;
+}
+
+/// Detector selectable per trigger definition (`detector = "..."`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DetectorKind {
+    /// rsudp-compatible recursive STA/LTA (ObsPy `recursive_sta_lta`)
+    Recursive,
+    /// ObsPy `classic_sta_lta`
+    Classic,
+    /// Delayed STA/LTA: the LTA window ends where the STA window begins
+    Delayed,
+    /// ObsPy `z_detect`: STA energy in standard deviations from the window mean
+    ZDetect,
+    /// Plain absolute amplitude of the filtered (or deconvolved) signal
+    Amplitude,
+}
+
+impl DetectorKind {
+    /// Parse a settings string; unknown names fall back to the rsudp recursive method.
+    pub fn parse(name: &str) -> Self {
+        Self::try_parse(name).unwrap_or(DetectorKind::Recursive)
+    }
+
+    /// Parse a detector name, `None` when it is unknown.
+    pub fn try_parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "recursive" | "recursivestalta" | "" => Some(DetectorKind::Recursive),
+            "classic" | "classicstalta" => Some(DetectorKind::Classic),
+            "delayed" | "delayedstalta" => Some(DetectorKind::Delayed),
+            "zdetect" | "z" => Some(DetectorKind::ZDetect),
+            "amplitude" | "amp" => Some(DetectorKind::Amplitude),
+            _ => None,
+        }
+    }
+
+    pub fn build(self, nsta: usize, nlta: usize) -> Box<dyn Detector> {
+        let nsta = nsta.max(1);
+        let nlta = nlta.max(1);
+        match self {
+            DetectorKind::Recursive => Box::new(RecursiveStaLta { nsta, nlta }),
+            DetectorKind::Classic => Box::new(ClassicStaLta { nsta, nlta }),
+            DetectorKind::Delayed => Box::new(DelayedStaLta { nsta, nlta }),
+            DetectorKind::ZDetect => Box::new(ZDetect { nsta }),
+            DetectorKind::Amplitude => Box::new(Amplitude { warmup: nlta }),
+        }
+    }
+}
+
+/// Segment of a characteristic function, streamed for plotting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacteristicFunction {
+    pub channel: String,
+    pub trigger_name: String,
+    /// Time of the first value
+    pub timestamp: DateTime<Utc>,
+    pub sample_rate: f64,
+    pub values: Vec<f64>,
+}
+
+pub struct RecursiveStaLta {
+    nsta: usize,
+    nlta: usize,
+}
+
+impl Detector for RecursiveStaLta {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64> {
+        let csta = 1.0 / self.nsta as f64;
+        let clta = 1.0 / self.nlta as f64;
+        let mut sta = 0.0_f64;
+        let mut lta = 1e-99_f64;
+        data.iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let energy = x * x;
+                sta = csta * energy + (1.0 - csta) * sta;
+                lta = clta * energy + (1.0 - clta) * lta;
+                // Match ObsPy: the first nlta values are zeroed
+                if i < self.nlta { 0.0 } else { sta / lta }
+            })
+            .collect()
+    }
+
+    fn warmup(&self) -> usize {
+        self.nlta
+    }
+}
+
+/// Mean of `data[i]^2` over the `n` samples ending at `i` (fewer at the start).
+fn moving_energy(data: &[f64], n: usize) -> Vec<f64> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut sum = 0.0_f64;
+    for i in 0..data.len() {
+        sum += data[i] * data[i];
+        if i >= n {
+            sum -= data[i - n] * data[i - n];
+        }
+        out.push(sum / n as f64);
+    }
+    out
+}
+
+pub struct ClassicStaLta {
+    nsta: usize,
+    nlta: usize,
+}
+
+impl Detector for ClassicStaLta {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64> {
+        let sta = moving_energy(data, self.nsta);
+        let lta = moving_energy(data, self.nlta);
+        sta.iter()
+            .zip(&lta)
+            .enumerate()
+            .map(|(i, (&s, &l))| {
+                if i + 1 < self.nlta { 0.0 } else { s / l.max(f64::MIN_POSITIVE) }
+            })
+            .collect()
+    }
+
+    fn warmup(&self) -> usize {
+        self.nlta - 1
+    }
+}
+
+pub struct DelayedStaLta {
+    nsta: usize,
+    nlta: usize,
+}
+
+impl Detector for DelayedStaLta {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64> {
+        let sta = moving_energy(data, self.nsta);
+        let lta = moving_energy(data, self.nlta);
+        // The LTA window ends just before the STA window starts, so the onset
+        // itself never inflates the long-term average
+        (0..data.len())
+            .map(|i| {
+                if i < self.warmup() {
+                    return 0.0;
+                }
+                sta[i] / lta[i - self.nsta].max(f64::MIN_POSITIVE)
+            })
+            .collect()
+    }
+
+    fn warmup(&self) -> usize {
+        self.nsta + self.nlta
+    }
+}
+
+pub struct ZDetect {
+    nsta: usize,
+}
+
+impl Detector for ZDetect {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+        let sta: Vec<f64> = moving_energy(data, self.nsta)
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| if i < self.nsta { 0.0 } else { e * self.nsta as f64 })
+            .collect();
+        let mean = sta.iter().sum::<f64>() / sta.len() as f64;
+        let std = (sta.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / sta.len() as f64).sqrt();
+        if std <= 0.0 {
+            return vec![0.0; sta.len()];
+        }
+        sta.iter()
+            .enumerate()
+            .map(|(i, &s)| if i < self.nsta { 0.0 } else { (s - mean) / std })
+            .collect()
+    }
+
+    fn warmup(&self) -> usize {
+        self.nsta
+    }
+}
+
+pub struct Amplitude {
+    /// Skip the fresh filter's start-up transient
+    warmup: usize,
+}
+
+impl Detector for Amplitude {
+    fn characteristic(&self, data: &[f64]) -> Vec<f64> {
+        data.iter()
+            .enumerate()
+            .map(|(i, &x)| if i < self.warmup { 0.0 } else { x.abs() })
+            .collect()
+    }
+
+    fn warmup(&self) -> usize {
+        self.warmup
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// 1 Hz background with a 10x burst starting at `onset`.
+    fn burst(n: usize, onset: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| {
+                let amp = if i >= onset { 10.0 } else { 1.0 };
+                amp * (2.0 * PI * i as f64 / 100.0).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detector_kind_parse() {
+        assert_eq!(DetectorKind::parse("classic"), DetectorKind::Classic);
+        assert_eq!(DetectorKind::parse("delayed_sta_lta"), DetectorKind::Delayed);
+        assert_eq!(DetectorKind::parse("Z-Detect"), DetectorKind::ZDetect);
+        assert_eq!(DetectorKind::parse("amplitude"), DetectorKind::Amplitude);
+        assert_eq!(DetectorKind::parse("recursive"), DetectorKind::Recursive);
+        assert_eq!(DetectorKind::parse(""), DetectorKind::Recursive);
+        assert_eq!(DetectorKind::try_parse("clasic"), None);
+        assert_eq!(DetectorKind::parse("clasic"), DetectorKind::Recursive);
+    }
+
+    #[test]
+    fn test_detectors_respond_to_onset() {
+        let (nsta, nlta) = (100, 1000);
+        let data = burst(2000, 1600);
+        for kind in [
+            DetectorKind::Recursive,
+            DetectorKind::Classic,
+            DetectorKind::Delayed,
+            DetectorKind::ZDetect,
+            DetectorKind::Amplitude,
+        ] {
+            let detector = kind.build(nsta, nlta);
+            let cf = detector.characteristic(&data);
+            assert_eq!(cf.len(), data.len());
+            assert!(cf[..detector.warmup()].iter().all(|&v| v == 0.0), "{:?} warmup not zeroed", kind);
+
+            let quiet = cf[detector.warmup()..1590].iter().cloned().fold(f64::MIN, f64::max);
+            let loud = cf[1700..].iter().cloned().fold(f64::MIN, f64::max);
+            assert!(loud > 3.0 * quiet.max(1e-9), "{:?}: quiet={} loud={}", kind, quiet, loud);
+        }
+    }
+
+    #[test]
+    fn test_classic_sta_lta_steady_state() {
+        // A stationary sine has equal short- and long-term energy
+        let data = burst(3000, usize::MAX);
+        let cf = DetectorKind::Classic.build(100, 1000).characteristic(&data);
+        assert!((cf[2500] - 1.0).abs() < 0.02, "cf={}", cf[2500]);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::filter::{butter_bandpass_sos, deconvolve_response_to, OutputUnits};
+use crate::magnitude::{local_magnitude, wood_anderson_peak_mm, LocalMagnitude};
+use crate::parser::stationxml::{infer_input_units, ChannelResponse};
+use crate::picker::{aic_curve, onset_from_aic, PhasePick, PICK_WINDOW_SECONDS};
+use crate::settings::{DistanceSettings, MagnitudeSettings};
+
+/// The S search starts this long after the P pick, past the P onset itself.
+const MIN_S_MINUS_P: f64 = 0.5;
+/// Data before the search window fed through the filter to settle its transient.
+const FILTER_LEAD_SECONDS: f64 = 5.0;
+
+/// Water level for displacement restitution; the pre-filter does the band limiting.
+const MAGNITUDE_WATER_LEVEL_DB: f64 = 60.0;
+
+/// Everything estimated for one event once its S window has been received.
+#[derive(Debug, Clone, Default)]
+pub struct SourceEstimate {
+    pub distance: Option<DistanceEstimate>,
+    pub magnitude: Option<LocalMagnitude>,
+}
+
+/// Hypocentral distance and origin time from an S-P time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistanceEstimate {
+    pub s_pick: PhasePick,
+    /// S-P time (s)
+    pub s_minus_p: f64,
+    /// Hypocentral distance (km)
+    pub distance_km: f64,
+    pub origin_time: DateTime<Utc>,
+}
+
+impl DistanceEstimate {
+    /// Distance and origin time for a homogeneous `vp`/`vs` model (km/s).
+    pub fn from_picks(p: &PhasePick, s_pick: PhasePick, vp: f64, vs: f64) -> Self {
+        let s_minus_p = (s_pick.time - p.time).num_microseconds().unwrap_or(0) as f64 / 1e6;
+        let distance_km = s_minus_p * vp * vs / (vp - vs);
+        let travel = distance_km / vp;
+        Self {
+            s_pick,
+            s_minus_p,
+            distance_km,
+            origin_time: p.time - Duration::microseconds((travel * 1e6) as i64),
+        }
+    }
+}
+
+impl std::fmt::Display for DistanceEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "~{:.0} km (S-P {:.2} s), origin {}",
+            self.distance_km,
+            self.s_minus_p,
+            self.origin_time.format("%Y-%m-%dT%H:%M:%S%.1fZ")
+        )
+    }
+}
+
+/// Contiguous recent samples of one horizontal channel.
+struct ChannelBuffer {
+    start: DateTime<Utc>,
+    sample_rate: f64,
+    samples: VecDeque<f64>,
+}
+
+impl ChannelBuffer {
+    fn end(&self) -> DateTime<Utc> {
+        self.time_at(self.samples.len())
+    }
+
+    fn time_at(&self, index: usize) -> DateTime<Utc> {
+        self.start + Duration::microseconds((index as f64 / self.sample_rate * 1e6) as i64)
+    }
+
+    fn index_at(&self, time: DateTime<Utc>) -> usize {
+        let offset = (time - self.start).num_microseconds().unwrap_or(0) as f64 / 1e6;
+        ((offset * self.sample_rate).round().max(0.0) as usize).min(self.samples.len())
+    }
+}
+
+/// Waits for the S phase after each P pick, picks it on the horizontals of the same
+/// station and converts the S-P time into a distance and origin time. Optionally
+/// measures a local magnitude on the same horizontal window.
+pub struct DistanceEstimator {
+    settings: DistanceSettings,
+    magnitude: Option<MagnitudeSettings>,
+    /// Instrument responses and sensitivities keyed by channel code, for ML
+    responses: HashMap<String, ChannelResponse>,
+    sensitivities: HashMap<String, f64>,
+    buffers: HashMap<String, ChannelBuffer>,
+    /// P picks still waiting for `window` seconds of horizontal data
+    pending: Vec<(Uuid, PhasePick)>,
+    /// Newest data time seen on any channel
+    latest: Option<DateTime<Utc>>,
+}
+
+impl DistanceEstimator {
+    pub fn new(settings: DistanceSettings) -> Self {
+        info!(
+            "Distance estimation on {:?}: vp={} km/s, vs={} km/s, S window {} s",
+            settings.channels, settings.vp, settings.vs, settings.window
+        );
+        Self {
+            settings,
+            magnitude: None,
+            responses: HashMap::new(),
+            sensitivities: HashMap::new(),
+            buffers: HashMap::new(),
+            pending: Vec::new(),
+            latest: None,
+        }
+    }
+
+    /// Also estimate ML, restoring displacement with these responses (or sensitivities).
+    pub fn with_magnitude(
+        mut self,
+        settings: MagnitudeSettings,
+        responses: HashMap<String, ChannelResponse>,
+        sensitivities: HashMap<String, f64>,
+    ) -> Self {
+        info!("Local magnitude enabled ({} correction)", settings.correction);
+        self.magnitude = Some(settings);
+        self.responses = responses;
+        self.sensitivities = sensitivities;
+        self
+    }
+
+    fn is_horizontal(&self, id: &str) -> bool {
+        let channel = id.rsplit('.').next().unwrap_or(id);
+        self.settings.channels.iter().any(|c| channel.ends_with(c.as_str()))
+    }
+
+    /// Buffer a segment (NET.STA.LOC.CHA id); non-horizontal channels only advance the clock.
+    pub fn add_segment(&mut self, id: &str, start: DateTime<Utc>, sample_rate: f64, samples: &[f64]) {
+        let seg = ChannelBuffer { start, sample_rate, samples: samples.iter().copied().collect() };
+        let seg_end = seg.end();
+        self.latest = Some(self.latest.map_or(seg_end, |t| t.max(seg_end)));
+        if !self.is_horizontal(id) || sample_rate <= 0.0 {
+            return;
+        }
+
+        let max_len = ((self.settings.window + PICK_WINDOW_SECONDS + FILTER_LEAD_SECONDS) * sample_rate) as usize;
+        let buf = self.buffers.entry(id.to_string()).or_insert_with(|| ChannelBuffer {
+            start, sample_rate, samples: VecDeque::new(),
+        });
+        // Restart on gaps or rate changes
+        let gap = (start - buf.end()).num_microseconds().unwrap_or(i64::MAX).abs() as f64 / 1e6;
+        if buf.sample_rate != sample_rate || gap > 1.5 / sample_rate {
+            *buf = seg;
+        } else {
+            buf.samples.extend(samples);
+        }
+        while buf.samples.len() > max_len {
+            buf.samples.pop_front();
+            buf.start = buf.time_at(1);
+        }
+    }
+
+    /// Start waiting for the S phase of an alert's P pick.
+    pub fn watch(&mut self, alert_id: Uuid, p: PhasePick) {
+        self.pending.push((alert_id, p));
+    }
+
+    /// Estimates for every P pick whose S window has been fully received.
+    pub fn poll(&mut self) -> Vec<(Uuid, SourceEstimate)> {
+        let Some(latest) = self.latest else { return Vec::new() };
+        let window = Duration::milliseconds((self.settings.window * 1000.0) as i64);
+        let (ready, waiting): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|(_, p)| latest >= p.time + window);
+        self.pending = waiting;
+
+        ready
+            .into_iter()
+            .filter_map(|(id, p)| {
+                let distance = if self.settings.enabled { self.estimate(&p) } else { None };
+                if self.settings.enabled && distance.is_none() {
+                    debug!("No S pick for P on {} at {}", p.channel, p.time);
+                }
+                let magnitude = self.magnitude(&p, distance.as_ref());
+                (distance.is_some() || magnitude.is_some()).then_some((id, SourceEstimate { distance, magnitude }))
+            })
+            .collect()
+    }
+
+    /// Horizontal buffers of the station that recorded `p`, sorted by id.
+    fn station_horizontals(&self, p: &PhasePick) -> Vec<(&String, &ChannelBuffer)> {
+        let station: String = p.channel.split('.').take(2).collect::<Vec<_>>().join(".");
+        let mut horizontals: Vec<(&String, &ChannelBuffer)> = self
+            .buffers
+            .iter()
+            .filter(|(id, _)| id.starts_with(&format!("{}.", station)))
+            .collect();
+        horizontals.sort_by_key(|(id, _)| id.as_str());
+        horizontals
+    }
+
+    /// ML from the larger peak Wood-Anderson amplitude of the horizontals in
+    /// `[P, P + window]`, corrected for the configured or S-P distance.
+    fn magnitude(&self, p: &PhasePick, distance: Option<&DistanceEstimate>) -> Option<LocalMagnitude> {
+        let settings = self.magnitude.as_ref()?;
+        let distance_km = if settings.distance_km > 0.0 { settings.distance_km } else { distance?.distance_km };
+        let lead = Duration::milliseconds((FILTER_LEAD_SECONDS * 1000.0) as i64);
+        let end = p.time + Duration::milliseconds((self.settings.window * 1000.0) as i64);
+
+        let mut peak_mm = 0.0_f64;
+        for (id, buf) in self.station_horizontals(p) {
+            let channel = id.rsplit('.').next().unwrap_or(id);
+            let Some(response) = self.responses.get(channel).cloned().or_else(|| {
+                self.sensitivities
+                    .get(channel)
+                    .filter(|&&s| s > 0.0)
+                    .map(|&s| ChannelResponse::from_sensitivity(s, infer_input_units(channel)))
+            }) else {
+                continue;
+            };
+            let (first, from, to) = (buf.index_at(p.time - lead), buf.index_at(p.time), buf.index_at(end));
+            if to <= from + 8 {
+                continue;
+            }
+            let fs = buf.sample_rate;
+            let raw: Vec<f64> = buf.samples.range(first..to).copied().collect();
+            let mean = raw.iter().sum::<f64>() / raw.len() as f64;
+            let demeaned: Vec<f64> = raw.iter().map(|x| x - mean).collect();
+            let pre_filt = [0.2, 0.5, 0.4 * fs, 0.45 * fs];
+            let displacement =
+                deconvolve_response_to(&demeaned, &response, fs, pre_filt, MAGNITUDE_WATER_LEVEL_DB, OutputUnits::Disp);
+            peak_mm = peak_mm.max(wood_anderson_peak_mm(&displacement[from - first..], fs));
+        }
+
+        (peak_mm > 0.0).then(|| {
+            let ml = local_magnitude(peak_mm, distance_km, settings);
+            info!("{} for P on {}", ml, p.channel);
+            ml
+        })
+    }
+
+    /// Joint AIC S pick over the station's horizontals in `[P + 0.5 s, P + window]`.
+    fn estimate(&self, p: &PhasePick) -> Option<DistanceEstimate> {
+        let search_start = p.time + Duration::milliseconds((MIN_S_MINUS_P * 1000.0) as i64);
+        let search_end = p.time + Duration::milliseconds((self.settings.window * 1000.0) as i64);
+        let lead = Duration::milliseconds((FILTER_LEAD_SECONDS * 1000.0) as i64);
+
+        let mut joint: Option<Vec<(usize, f64)>> = None;
+        let mut reference: Option<(&String, &ChannelBuffer, usize)> = None;
+        for (id, buf) in self.station_horizontals(p) {
+            let fs = buf.sample_rate;
+            let (first, from, to) = (buf.index_at(search_start - lead), buf.index_at(search_start), buf.index_at(search_end));
+            if to <= from + 8 {
+                continue;
+            }
+            let raw: Vec<f64> = buf.samples.range(first..to).copied().collect();
+            let mean = raw.iter().sum::<f64>() / raw.len() as f64;
+            let mut filters = butter_bandpass_sos(4, self.settings.highpass, self.settings.lowpass.min(0.45 * fs), fs);
+            let filtered: Vec<f64> = raw
+                .iter()
+                .map(|&x| filters.iter_mut().fold(x - mean, |v, section| section.process(v)))
+                .collect();
+            let aic = aic_curve(&filtered[from - first..]);
+            joint = Some(match joint {
+                None => aic,
+                Some(sum) => sum.iter().zip(&aic).map(|(&(k, a), &(_, b))| (k, a + b)).collect(),
+            });
+            reference.get_or_insert((id, buf, from));
+        }
+
+        let (onset, sigma) = onset_from_aic(&joint?)?;
+        let (id, buf, from) = reference?;
+        let s_pick = PhasePick {
+            phase: "S".to_string(),
+            channel: id.clone(),
+            time: buf.time_at(from + onset),
+            uncertainty: sigma / buf.sample_rate,
+        };
+        let estimate = DistanceEstimate::from_picks(p, s_pick, self.settings.vp, self.settings.vs);
+        info!("S pick for P on {}: {}", p.channel, estimate);
+        Some(estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_distance_from_s_minus_p() {
+        let t0 = Utc::now();
+        let p = PhasePick { phase: "P".to_string(), channel: "AM.R0001.00.EHZ".to_string(), time: t0, uncertainty: 0.05 };
+        let s = PhasePick { time: t0 + Duration::seconds(5), phase: "S".to_string(), ..p.clone() };
+        let e = DistanceEstimate::from_picks(&p, s, 6.0, 3.5);
+        // 5 s * (6 * 3.5 / 2.5) km/s = 42 km, 7 s of P travel time
+        assert!((e.distance_km - 42.0).abs() < 1e-6);
+        assert_eq!((t0 - e.origin_time).num_milliseconds(), 7000);
+    }
+
+    /// Feed N/E records with noise, a weak P at 20 s and an S phase 5 s later,
+    /// scaled by `scale` counts, and poll the estimator.
+    fn run_event(mut est: DistanceEstimator, scale: f64) -> Vec<(Uuid, SourceEstimate)> {
+        let fs = 100.0;
+        let t0 = Utc::now();
+        let (p_index, s_index) = (2000, 2500);
+        let p = PhasePick {
+            phase: "P".to_string(),
+            channel: "AM.R0001.00.EHZ".to_string(),
+            time: t0 + Duration::seconds(20),
+            uncertainty: 0.05,
+        };
+        est.watch(Uuid::new_v4(), p);
+
+        let mut seed = 99_u64;
+        for (component, phase) in [("EHN", 0.0), ("EHE", 1.0)] {
+            let samples: Vec<f64> = (0..7000)
+                .map(|i| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    let noise = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                    let t = i as f64 / fs;
+                    let amp = if i >= s_index { 20.0 } else if i >= p_index { 2.0 } else { 0.0 };
+                    scale * (noise + amp * (2.0 * PI * 3.0 * t + phase).sin())
+                })
+                .collect();
+            // Delivered in 25-sample packets, as from a Shake
+            for (n, chunk) in samples.chunks(25).enumerate() {
+                let start = t0 + Duration::milliseconds(n as i64 * 250);
+                est.add_segment(&format!("AM.R0001.00.{}", component), start, fs, chunk);
+            }
+        }
+        est.poll()
+    }
+
+    #[test]
+    fn test_picks_s_on_horizontals() {
+        let results = run_event(DistanceEstimator::new(DistanceSettings::default()), 1.0);
+        assert_eq!(results.len(), 1);
+        let e = results[0].1.distance.as_ref().unwrap();
+        assert!((e.s_minus_p - 5.0).abs() < 0.1, "S-P {}", e.s_minus_p);
+        assert!((e.distance_km - 42.0).abs() < 1.0, "distance {}", e.distance_km);
+        assert_eq!(e.s_pick.phase, "S");
+        assert!(results[0].1.magnitude.is_none());
+    }
+
+    #[test]
+    fn test_local_magnitude_scales_with_amplitude() {
+        let magnitude = |scale: f64| {
+            let sensitivities: HashMap<String, f64> =
+                [("EHN".to_string(), 3.99e8), ("EHE".to_string(), 3.99e8)].into_iter().collect();
+            let est = DistanceEstimator::new(DistanceSettings::default())
+                .with_magnitude(MagnitudeSettings::default(), HashMap::new(), sensitivities);
+            let results = run_event(est, scale);
+            let m = results[0].1.magnitude.clone().expect("ML");
+            assert!((m.distance_km - 42.0).abs() < 1.0, "ML distance {}", m.distance_km);
+            m.ml
+        };
+        let (weak, strong) = (magnitude(100.0), magnitude(1000.0));
+        assert!(weak.is_finite());
+        assert!(((strong - weak) - 1.0).abs() < 0.05, "weak={} strong={}", weak, strong);
+    }
+}
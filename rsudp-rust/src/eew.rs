@@ -0,0 +1,345 @@
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::{PI, SQRT_2};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::filter::{deconvolve_response_to, OutputUnits};
+use crate::ground_motion::intensity_from_pgv;
+use crate::parser::stationxml::{infer_input_units, ChannelResponse};
+use crate::picker::PhasePick;
+use crate::settings::EewSettings;
+use crate::trigger::{AlertEvent, AlertEventType, Biquad};
+
+/// Data before the P pick fed through the restitution to settle its transient.
+const LEAD_SECONDS: f64 = 5.0;
+/// Water level for restitution; the pre-filter does the band limiting.
+const WATER_LEVEL_DB: f64 = 60.0;
+
+/// Wu & Kanamori (2005) τc magnitude relation, `M = a·log10(τc) + b`.
+const TAU_C_MAGNITUDE: (f64, f64) = (3.373, 5.787);
+/// Wu & Kanamori (2005) peak ground velocity from Pd, `log10(PGV) = a·log10(Pd) + b` (cm, cm/s).
+const PD_PGV: (f64, f64) = (0.920, 1.642);
+
+/// On-site early warning parameters from the first seconds after a P pick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EarlyWarning {
+    /// Vertical channel id (NET.STA.LOC.CHA) the parameters were measured on
+    pub channel: String,
+    /// Predominant period τc (s)
+    pub tau_c: f64,
+    /// Peak vertical displacement Pd (m)
+    pub pd: f64,
+    /// Magnitude from τc
+    pub magnitude: f64,
+    /// Peak ground velocity predicted from Pd (m/s)
+    pub pgv: f64,
+    /// JMA-scale intensity predicted from that PGV
+    pub intensity: f64,
+    /// Seconds of P wave analysed
+    pub window: f64,
+}
+
+impl EarlyWarning {
+    /// τc and Pd of the vertical displacement and velocity in the P window.
+    pub fn from_motion(channel: &str, displacement: &[f64], velocity: &[f64], window: f64) -> Option<Self> {
+        let u2: f64 = displacement.iter().map(|u| u * u).sum();
+        let v2: f64 = velocity.iter().map(|v| v * v).sum();
+        if u2 <= 0.0 || v2 <= 0.0 {
+            return None;
+        }
+        let tau_c = 2.0 * std::f64::consts::PI / (v2 / u2).sqrt();
+        let pd = displacement.iter().fold(0.0_f64, |m, u| m.max(u.abs()));
+        let magnitude = TAU_C_MAGNITUDE.0 * tau_c.log10() + TAU_C_MAGNITUDE.1;
+        let pgv = 10f64.powf(PD_PGV.0 * (pd * 100.0).log10() + PD_PGV.1) / 100.0;
+        Some(Self {
+            channel: channel.to_string(),
+            tau_c,
+            pd,
+            magnitude,
+            pgv,
+            intensity: intensity_from_pgv(pgv),
+            window,
+        })
+    }
+}
+
+impl std::fmt::Display for EarlyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "τc {:.2} s, Pd {:.4} cm → M {:.1}, intensity {:.1} ({:.0} s of P)",
+            self.tau_c,
+            self.pd * 100.0,
+            self.magnitude,
+            self.intensity,
+            self.window
+        )
+    }
+}
+
+/// Contiguous recent samples of one vertical channel.
+struct ChannelBuffer {
+    start: DateTime<Utc>,
+    sample_rate: f64,
+    samples: VecDeque<f64>,
+}
+
+impl ChannelBuffer {
+    fn end(&self) -> DateTime<Utc> {
+        self.time_at(self.samples.len())
+    }
+
+    fn time_at(&self, index: usize) -> DateTime<Utc> {
+        self.start + Duration::microseconds((index as f64 / self.sample_rate * 1e6) as i64)
+    }
+
+    fn index_at(&self, time: DateTime<Utc>) -> usize {
+        let offset = (time - self.start).num_microseconds().unwrap_or(0) as f64 / 1e6;
+        ((offset * self.sample_rate).round().max(0.0) as usize).min(self.samples.len())
+    }
+}
+
+/// Measures τc and Pd on the station's vertical channel `window` seconds after
+/// each P pick and emits them as an early `AlertEvent`.
+pub struct EarlyWarningEstimator {
+    settings: EewSettings,
+    responses: HashMap<String, ChannelResponse>,
+    sensitivities: HashMap<String, f64>,
+    buffers: HashMap<String, ChannelBuffer>,
+    /// P picks still waiting for `window` seconds of data, with their trigger name
+    pending: Vec<(Uuid, String, PhasePick)>,
+    /// Newest data time seen on any channel
+    latest: Option<DateTime<Utc>>,
+}
+
+impl EarlyWarningEstimator {
+    pub fn new(
+        settings: EewSettings,
+        responses: HashMap<String, ChannelResponse>,
+        sensitivities: HashMap<String, f64>,
+    ) -> Self {
+        info!("Early warning on {:?}: {} s of P", settings.channels, settings.window);
+        Self { settings, responses, sensitivities, buffers: HashMap::new(), pending: Vec::new(), latest: None }
+    }
+
+    /// Index of the channel in `settings.channels` (suffix match), the lower the preferred.
+    fn rank(&self, id: &str) -> Option<usize> {
+        let channel = id.rsplit('.').next().unwrap_or(id);
+        self.settings.channels.iter().position(|c| channel.ends_with(c.as_str()))
+    }
+
+    /// Buffer a segment (NET.STA.LOC.CHA id); other channels only advance the clock.
+    pub fn add_segment(&mut self, id: &str, start: DateTime<Utc>, sample_rate: f64, samples: &[f64]) {
+        let seg = ChannelBuffer { start, sample_rate, samples: samples.iter().copied().collect() };
+        let seg_end = seg.end();
+        self.latest = Some(self.latest.map_or(seg_end, |t| t.max(seg_end)));
+        if self.rank(id).is_none() || sample_rate <= 0.0 {
+            return;
+        }
+
+        let max_len = ((self.settings.window + LEAD_SECONDS + crate::picker::PICK_WINDOW_SECONDS) * sample_rate) as usize;
+        let buf = self.buffers.entry(id.to_string()).or_insert_with(|| ChannelBuffer {
+            start, sample_rate, samples: VecDeque::new(),
+        });
+        // Restart on gaps or rate changes
+        let gap = (start - buf.end()).num_microseconds().unwrap_or(i64::MAX).abs() as f64 / 1e6;
+        if buf.sample_rate != sample_rate || gap > 1.5 / sample_rate {
+            *buf = seg;
+        } else {
+            buf.samples.extend(samples);
+        }
+        while buf.samples.len() > max_len {
+            buf.samples.pop_front();
+            buf.start = buf.time_at(1);
+        }
+    }
+
+    /// Start waiting for the P window of an alert.
+    pub fn watch(&mut self, alert_id: Uuid, trigger_name: &str, p: PhasePick) {
+        self.pending.push((alert_id, trigger_name.to_string(), p));
+    }
+
+    /// Early `AlertEvent`s for every P pick whose window has been fully received.
+    pub fn poll(&mut self) -> Vec<(Uuid, AlertEvent)> {
+        let Some(latest) = self.latest else { return Vec::new() };
+        let window = Duration::milliseconds((self.settings.window * 1000.0) as i64);
+        let (ready, waiting): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|(_, _, p)| latest >= p.time + window);
+        self.pending = waiting;
+
+        ready
+            .into_iter()
+            .filter_map(|(id, trigger_name, p)| {
+                let Some(ew) = self.estimate(&p) else {
+                    debug!("No early warning parameters for P on {} at {}", p.channel, p.time);
+                    return None;
+                };
+                let event = AlertEvent {
+                    timestamp: p.time + window,
+                    channel: ew.channel.clone(),
+                    trigger_name,
+                    event_type: AlertEventType::Early,
+                    ratio: 0.0,
+                    max_ratio: 0.0,
+                    message: "EARLY".to_string(),
+                    pick: Some(p),
+                    early_warning: Some(ew),
+                };
+                Some((id, event))
+            })
+            .collect()
+    }
+
+    /// τc and Pd on the preferred vertical of the station that recorded `p`.
+    fn estimate(&self, p: &PhasePick) -> Option<EarlyWarning> {
+        let station: String = p.channel.split('.').take(2).collect::<Vec<_>>().join(".");
+        let (id, buf) = self
+            .buffers
+            .iter()
+            .filter(|(id, _)| id.starts_with(&format!("{}.", station)))
+            .min_by_key(|(id, _)| (self.rank(id), id.as_str()))?;
+        let channel = id.rsplit('.').next().unwrap_or(id);
+        let response = self.responses.get(channel).cloned().or_else(|| {
+            self.sensitivities
+                .get(channel)
+                .filter(|&&s| s > 0.0)
+                .map(|&s| ChannelResponse::from_sensitivity(s, infer_input_units(channel)))
+        })?;
+
+        let lead = Duration::milliseconds((LEAD_SECONDS * 1000.0) as i64);
+        let end = p.time + Duration::milliseconds((self.settings.window * 1000.0) as i64);
+        let (first, from, to) = (buf.index_at(p.time - lead), buf.index_at(p.time), buf.index_at(end));
+        if to <= from + 8 {
+            return None;
+        }
+        let fs = buf.sample_rate;
+        let raw: Vec<f64> = buf.samples.range(first..to).copied().collect();
+        let mean = raw.iter().take(from - first).sum::<f64>() / (from - first).max(1) as f64;
+        let demeaned: Vec<f64> = raw.iter().map(|x| x - mean).collect();
+        // Restore the native units, then integrate causally as in Kanamori (2005) so the
+        // end of the window is not distorted by block processing
+        let native = if OutputUnits::Acc.derivative_order(&response.input_units) == 0 { OutputUnits::Acc } else { OutputUnits::Vel };
+        let hp = self.settings.highpass;
+        let pre_filt = [hp * 0.5, hp, 0.4 * fs, 0.45 * fs];
+        let restored = deconvolve_response_to(&demeaned, &response, fs, pre_filt, WATER_LEVEL_DB, native);
+        let velocity = if native == OutputUnits::Acc {
+            integrate_highpass(&restored, fs, hp)
+        } else {
+            highpass(&restored, fs, hp)
+        };
+        let displacement = integrate_highpass(&velocity, fs, hp);
+
+        let ew = EarlyWarning::from_motion(id, &displacement[from - first..], &velocity[from - first..], self.settings.window)?;
+        info!("Early warning for P on {}: {}", p.channel, ew);
+        Some(ew)
+    }
+}
+
+/// Second-order Butterworth high-pass at `corner` Hz (bilinear transform, pre-warped).
+fn butter_highpass(sample_rate: f64, corner: f64) -> Biquad {
+    let k = (PI * corner / sample_rate).tan();
+    let norm = 1.0 / (1.0 + SQRT_2 * k + k * k);
+    Biquad::new(norm, -2.0 * norm, norm, 2.0 * (k * k - 1.0) * norm, (1.0 - SQRT_2 * k + k * k) * norm)
+}
+
+/// Causal 2-pole Butterworth high-pass at `corner` Hz.
+fn highpass(x: &[f64], sample_rate: f64, corner: f64) -> Vec<f64> {
+    let mut filter = butter_highpass(sample_rate, corner);
+    x.iter().map(|&v| filter.process(v)).collect()
+}
+
+/// Trapezoidal integration followed by a high-pass that removes the drift.
+fn integrate_highpass(x: &[f64], sample_rate: f64, corner: f64) -> Vec<f64> {
+    let dt = 1.0 / sample_rate;
+    let mut sum = 0.0;
+    let mut prev = x.first().copied().unwrap_or(0.0);
+    let integral: Vec<f64> = x
+        .iter()
+        .map(|&v| {
+            sum += (prev + v) * dt / 2.0;
+            prev = v;
+            sum
+        })
+        .collect();
+    highpass(&integral, sample_rate, corner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_tau_c_of_harmonic_motion() {
+        // For a pure sinusoid τc is its period
+        let fs = 100.0;
+        let (period, amp) = (0.5, 1e-4);
+        let w = 2.0 * PI / period;
+        let u: Vec<f64> = (0..300).map(|i| amp * (w * i as f64 / fs).sin()).collect();
+        let v: Vec<f64> = (0..300).map(|i| amp * w * (w * i as f64 / fs).cos()).collect();
+        let ew = EarlyWarning::from_motion("AM.R0001.00.ENZ", &u, &v, 3.0).unwrap();
+        assert!((ew.tau_c - period).abs() < 0.01, "tau_c={}", ew.tau_c);
+        assert!((ew.pd - amp).abs() < 1e-6);
+        // Longer predominant periods mean larger events
+        let slow = EarlyWarning::from_motion("AM.R0001.00.ENZ", &u, &v.iter().map(|x| x / 4.0).collect::<Vec<_>>(), 3.0).unwrap();
+        assert!(slow.magnitude > ew.magnitude);
+    }
+
+    #[test]
+    fn test_highpass_is_second_order_butterworth() {
+        let (fs, corner) = (100.0, 0.075);
+        let f = butter_highpass(fs, corner);
+        let gain = |freq: f64| {
+            let z_inv = rustfft::num_complex::Complex::from_polar(1.0, -2.0 * PI * freq / fs);
+            ((f.b0 + f.b1 * z_inv + f.b2 * z_inv * z_inv) / (1.0 + f.a1 * z_inv + f.a2 * z_inv * z_inv)).norm()
+        };
+        assert!((gain(corner) - SQRT_2 / 2.0).abs() < 1e-6);
+        assert!((gain(5.0) - 1.0).abs() < 1e-3);
+        // 40 dB per decade below the corner
+        assert!((gain(corner / 10.0) - 0.01).abs() < 1e-3, "{}", gain(corner / 10.0));
+    }
+
+    #[test]
+    fn test_early_event_after_window() {
+        let fs = 100.0;
+        let sensitivity = 3.99e8; // counts per m/s
+        let sensitivities: HashMap<String, f64> = [("EHZ".to_string(), sensitivity)].into_iter().collect();
+        let settings = EewSettings { enabled: true, ..Default::default() };
+        let mut est = EarlyWarningEstimator::new(settings, HashMap::new(), sensitivities);
+        let t0 = Utc::now();
+        let p = PhasePick {
+            phase: "P".to_string(),
+            channel: "AM.R0001.00.EHZ".to_string(),
+            time: t0 + Duration::seconds(10),
+            uncertainty: 0.01,
+        };
+        let id = Uuid::new_v4();
+        est.watch(id, "strong", p);
+
+        // 1 Hz P-wave velocity of 1 mm/s from 10 s
+        let w = 2.0 * PI;
+        let mut events = Vec::new();
+        for packet in 0..60 {
+            let start = t0 + Duration::milliseconds(packet * 250);
+            let samples: Vec<f64> = (0..25)
+                .map(|i| {
+                    let n = packet as usize * 25 + i;
+                    if n < 1000 { 0.0 } else { 1e-3 * (w * (n - 1000) as f64 / fs).sin() * sensitivity }
+                })
+                .collect();
+            est.add_segment("AM.R0001.00.EHZ", start, fs, &samples);
+            events.extend(est.poll());
+        }
+
+        assert_eq!(events.len(), 1);
+        let (alert_id, event) = &events[0];
+        assert_eq!(*alert_id, id);
+        assert_eq!(event.event_type, AlertEventType::Early);
+        assert_eq!(event.trigger_name, "strong");
+        let ew = event.early_warning.as_ref().unwrap();
+        assert!((ew.tau_c - 1.0).abs() < 0.3, "tau_c={}", ew.tau_c);
+        assert!(ew.intensity > -2.0);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::catalog::{Catalog, CatalogMatch};
+use crate::picker::PhasePick;
+use crate::ground_motion::GroundMotion;
+use crate::response_spectrum::{LongPeriodMotion, ResponseSpectrum};
+use crate::settings::EventSettings;
+
+/// Closed events are kept this long for the API.
+const RETENTION_HOURS: i64 = 24;
+
+/// Lifecycle stage of an event, as of its latest update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventState {
+    /// First trigger of a new event
+    Declared,
+    /// Another trigger joined, or a contributor reset while others are still on
+    Updated,
+    /// Every contributing trigger has reset
+    Closed,
+}
+
+impl std::fmt::Display for EventState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EventState::Declared => "DECLARED",
+            EventState::Updated => "UPDATED",
+            EventState::Closed => "CLOSED",
+        })
+    }
+}
+
+/// Triggers from any channel and trigger definition that belong to the same shaking,
+/// with their aggregated metrics. Notifiers act on these rather than on raw triggers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Same as the id of the alert that declared the event
+    pub id: Uuid,
+    pub state: EventState,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    /// Contributing alerts (history entries), in trigger order
+    pub alerts: Vec<Uuid>,
+    pub channels: Vec<String>,
+    pub triggers: Vec<String>,
+    pub max_ratio: f64,
+    /// Highest intensity of the contributing alerts, on the configured scale
+    pub max_intensity: Option<f64>,
+    /// Highest PGA of the contributing alerts (gal)
+    pub pga: Option<f64>,
+    pub picks: Vec<PhasePick>,
+    /// Declared during a maintenance window or manual mute
+    pub muted: bool,
+    /// Reference catalog earthquake the event was attributed to
+    #[serde(default)]
+    pub catalog: Option<CatalogMatch>,
+    /// Largest JMA long-period ground motion of the contributing alerts
+    #[serde(default)]
+    pub long_period: Option<LongPeriodMotion>,
+    /// Strongest response spectrum of the contributing alerts
+    #[serde(default)]
+    pub spectrum: Option<ResponseSpectrum>,
+}
+
+impl Event {
+    pub fn is_open(&self) -> bool {
+        self.state != EventState::Closed
+    }
+
+    /// Earliest P arrival among the contributors.
+    pub fn first_pick(&self) -> Option<&PhasePick> {
+        self.picks.iter().min_by_key(|pick| pick.time)
+    }
+
+    /// Onset used for catalog matching: the first P pick, else the first trigger.
+    pub fn detection_time(&self) -> DateTime<Utc> {
+        self.first_pick().map_or(self.start, |pick| pick.time)
+    }
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Event {} {}: {} channel(s) [{}], max ratio {:.2}",
+            self.id, self.state, self.channels.len(), self.channels.join(", "), self.max_ratio
+        )?;
+        if let Some(intensity) = self.max_intensity {
+            write!(f, ", intensity {:.1}", intensity)?;
+        }
+        if let Some(pga) = self.pga {
+            write!(f, ", PGA {:.2} gal", pga)?;
+        }
+        if let Some(lp) = &self.long_period {
+            write!(f, ", {}", lp)?;
+        }
+        if let Some(catalog) = &self.catalog {
+            write!(f, ", catalog {}", catalog)?;
+        }
+        Ok(())
+    }
+}
+
+/// Associates alerts into events: a trigger joins the open event whose latest
+/// trigger is at most `window` seconds earlier, otherwise it declares a new one.
+/// The event closes once all of its alerts have reset.
+#[derive(Debug, Default)]
+pub struct EventManager {
+    settings: EventSettings,
+    /// Open and recently closed events, oldest first
+    events: VecDeque<Event>,
+    /// Event id of every alert seen
+    alert_event: HashMap<Uuid, Uuid>,
+    /// Alerts still triggered, per open event
+    active: HashMap<Uuid, HashSet<Uuid>>,
+    /// Time of the latest trigger, per open event
+    last_trigger: HashMap<Uuid, DateTime<Utc>>,
+}
+
+impl EventManager {
+    pub fn new(settings: EventSettings) -> Self {
+        if settings.enabled {
+            info!("Event association: triggers within {} s join the same event", settings.window);
+        }
+        Self { settings, ..Default::default() }
+    }
+
+    fn get_mut(&mut self, event_id: Uuid) -> Option<&mut Event> {
+        self.events.iter_mut().find(|e| e.id == event_id)
+    }
+
+    /// Record a TRIGGER; returns the event it declared (muted when `muted`) or joined.
+    #[allow(clippy::too_many_arguments)]
+    pub fn trigger(
+        &mut self,
+        alert_id: Uuid,
+        channel: &str,
+        trigger_name: &str,
+        time: DateTime<Utc>,
+        ratio: f64,
+        pick: Option<PhasePick>,
+        muted: bool,
+    ) -> Event {
+        let window = Duration::milliseconds((self.settings.window * 1000.0) as i64);
+        let joined = self
+            .settings
+            .enabled
+            .then(|| {
+                self.last_trigger
+                    .iter()
+                    .filter(|(_, &last)| time - last <= window)
+                    .max_by_key(|(_, &last)| last)
+                    .map(|(&id, _)| id)
+            })
+            .flatten();
+
+        let event_id = joined.unwrap_or(alert_id);
+        self.alert_event.insert(alert_id, event_id);
+        self.active.entry(event_id).or_default().insert(alert_id);
+        let last = self.last_trigger.entry(event_id).or_insert(time);
+        *last = (*last).max(time);
+
+        if joined.is_none() {
+            self.cleanup(time);
+            self.events.push_back(Event {
+                id: event_id,
+                state: EventState::Declared,
+                start: time,
+                end: None,
+                alerts: Vec::new(),
+                channels: Vec::new(),
+                triggers: Vec::new(),
+                max_ratio: 0.0,
+                max_intensity: None,
+                pga: None,
+                picks: Vec::new(),
+                muted,
+                catalog: None,
+                long_period: None,
+                spectrum: None,
+            });
+        }
+        let event = self.get_mut(event_id).expect("open event is stored");
+        if joined.is_some() {
+            event.state = EventState::Updated;
+        }
+        event.alerts.push(alert_id);
+        if !event.channels.iter().any(|c| c == channel) {
+            event.channels.push(channel.to_string());
+        }
+        if !event.triggers.iter().any(|t| t == trigger_name) {
+            event.triggers.push(trigger_name.to_string());
+        }
+        event.max_ratio = event.max_ratio.max(ratio);
+        event.picks.extend(pick);
+        event.clone()
+    }
+
+    /// Record a RESET with the alert's peak values; returns the event, closed when
+    /// this was its last triggered alert, or None for an unknown alert.
+    pub fn reset(
+        &mut self,
+        alert_id: Uuid,
+        time: DateTime<Utc>,
+        max_ratio: f64,
+        intensity: Option<f64>,
+        pga: Option<f64>,
+    ) -> Option<Event> {
+        let event_id = *self.alert_event.get(&alert_id)?;
+        let active = self.active.get_mut(&event_id)?;
+        if !active.remove(&alert_id) {
+            return None;
+        }
+        let closed = active.is_empty();
+        if closed {
+            self.active.remove(&event_id);
+            self.last_trigger.remove(&event_id);
+        }
+        let event = self.get_mut(event_id)?;
+        event.max_ratio = event.max_ratio.max(max_ratio);
+        if let Some(intensity) = intensity {
+            event.max_intensity = Some(event.max_intensity.map_or(intensity, |i| i.max(intensity)));
+        }
+        if let Some(pga) = pga {
+            event.pga = Some(event.pga.map_or(pga, |p| p.max(pga)));
+        }
+        if closed {
+            event.state = EventState::Closed;
+            event.end = Some(time);
+        } else {
+            event.state = EventState::Updated;
+        }
+        Some(event.clone())
+    }
+
+    /// Record the long-period ground motion and response spectrum measured for an
+    /// alert on its event, keeping the strongest.
+    pub fn add_ground_motion(&mut self, alert_id: Uuid, motion: &GroundMotion) {
+        let Some(&event_id) = self.alert_event.get(&alert_id) else { return };
+        let Some(event) = self.get_mut(event_id) else { return };
+        if let Some(lp) = motion.long_period.as_ref().filter(|lp| event.long_period.as_ref().is_none_or(|e| lp.sva > e.sva)) {
+            event.long_period = Some(lp.clone());
+        }
+        if let Some(spectrum) = motion.spectrum.as_ref().filter(|s| event.spectrum.as_ref().is_none_or(|e| s.peak() > e.peak())) {
+            event.spectrum = Some(spectrum.clone());
+        }
+    }
+
+    /// Attribute a closed event to the catalog earthquake whose P arrival matches it.
+    pub fn set_catalog(&mut self, event_id: Uuid, catalog: Option<CatalogMatch>) {
+        if let Some(event) = self.get_mut(event_id) {
+            event.catalog = catalog;
+        }
+    }
+
+    /// Re-match every closed event against `catalog`, which may have changed since.
+    pub fn label(&mut self, catalog: &Catalog) {
+        for event in self.events.iter_mut().filter(|e| !e.is_open()) {
+            event.catalog = catalog.match_detection(event.detection_time());
+        }
+    }
+
+    /// The event an alert belongs to.
+    pub fn event_of(&self, alert_id: Uuid) -> Option<&Event> {
+        let event_id = self.alert_event.get(&alert_id)?;
+        self.events.iter().find(|e| e.id == *event_id)
+    }
+
+    /// Ids of the events still open.
+    pub fn open_ids(&self) -> Vec<Uuid> {
+        self.active.keys().copied().collect()
+    }
+
+    pub fn get_event(&self, id: Uuid) -> Option<Event> {
+        self.events.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// Open and recently closed events, oldest first.
+    pub fn get_events(&self) -> Vec<Event> {
+        self.events.iter().cloned().collect()
+    }
+
+    fn cleanup(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::hours(RETENTION_HOURS);
+        while let Some(event) = self.events.front() {
+            if event.is_open() || event.end.is_none_or(|end| end >= cutoff) {
+                break;
+            }
+            let event = self.events.pop_front().expect("front exists");
+            for alert_id in event.alerts {
+                self.alert_event.remove(&alert_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(enabled: bool) -> EventSettings {
+        EventSettings { enabled, window: 30.0 }
+    }
+
+    #[test]
+    fn test_associates_triggers_into_one_event() {
+        let mut em = EventManager::new(settings(true));
+        let t0 = Utc::now();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let declared = em.trigger(a, "EHZ", "stalta", t0, 4.0, None, false);
+        assert_eq!(declared.state, EventState::Declared);
+        assert_eq!(declared.id, a);
+        let updated = em.trigger(b, "ENZ", "stalta", t0 + Duration::seconds(2), 6.0, None, false);
+        assert_eq!(updated.state, EventState::Updated);
+        assert_eq!(updated.id, a);
+        assert_eq!(updated.channels, vec!["EHZ", "ENZ"]);
+
+        let event = em.reset(a, t0 + Duration::seconds(20), 5.0, Some(2.4), Some(8.0)).unwrap();
+        assert_eq!(event.state, EventState::Updated);
+        let event = em.reset(b, t0 + Duration::seconds(25), 9.0, Some(3.1), Some(5.0)).unwrap();
+        assert_eq!(event.state, EventState::Closed);
+        assert_eq!(event.max_ratio, 9.0);
+        assert_eq!(event.max_intensity, Some(3.1));
+        assert_eq!(event.pga, Some(8.0));
+
+        // A later trigger declares a new event
+        let next = em.trigger(c, "EHZ", "stalta", t0 + Duration::seconds(40), 4.0, None, false);
+        assert_eq!(next.state, EventState::Declared);
+        assert_eq!(em.get_events().len(), 2);
+    }
+
+    #[test]
+    fn test_disabled_keeps_one_event_per_alert() {
+        let mut em = EventManager::new(settings(false));
+        let t0 = Utc::now();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(em.trigger(a, "EHZ", "stalta", t0, 4.0, None, true).state, EventState::Declared);
+        assert_eq!(em.trigger(b, "ENZ", "stalta", t0, 4.0, None, false).state, EventState::Declared);
+        assert!(em.event_of(a).unwrap().muted);
+        assert_eq!(em.reset(a, t0, 4.0, None, None).unwrap().state, EventState::Closed);
+        assert_eq!(em.open_ids(), vec![b]);
+        assert!(em.reset(a, t0, 4.0, None, None).is_none());
+    }
+}
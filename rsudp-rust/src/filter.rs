@@ -1,6 +1,7 @@
 use crate::trigger::Biquad;
 use crate::parser::stationxml::ChannelResponse;
 use rustfft::{FftPlanner, num_complex::Complex};
+use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
 /// Compute Butterworth bandpass filter as cascaded second-order sections (SOS)
@@ -64,6 +65,20 @@ pub fn butter_bandpass_sos(order: usize, low_freq: f64, high_freq: f64, fs: f64)
         // Step 4: Bilinear transform each pole pair into a digital biquad section
         // Each unique prototype pole generates two biquad sections (one per bandpass pole pair).
 
+        // For odd orders the real prototype pole (k = (N-1)/2) has no conjugate partner:
+        // its two bandpass poles s1, s2 (a conjugate pair, or both real for wide bands)
+        // form a single biquad together.
+        if order % 2 == 1 && k + 1 == n_unique {
+            let bilinear = |re: f64, im: f64| {
+                let s = Complex::new(re, im) / (2.0 * fs);
+                (1.0 + s) / (1.0 - s)
+            };
+            let z1 = bilinear(s1_re, s1_im);
+            let z2 = bilinear(s2_re, s2_im);
+            sections.push(Biquad::new(1.0, 0.0, -1.0, -(z1 + z2).re, (z1 * z2).re));
+            continue;
+        }
+
         // Section for pole s1 (and its conjugate s1*)
         let bq1 = analog_pole_pair_to_biquad(s1_re, s1_im, omega_0, fs);
         sections.push(bq1);
@@ -73,8 +88,9 @@ pub fn butter_bandpass_sos(order: usize, low_freq: f64, high_freq: f64, fs: f64)
         sections.push(bq2);
     }
 
-    // Normalize gain: compute and apply gain correction at center frequency
-    let w0 = 2.0 * PI * (low_freq * high_freq).sqrt() / fs;
+    // Normalize gain to unity at the analog center frequency mapped back through the
+    // bilinear transform, which reproduces scipy/ObsPy's zpk gain exactly
+    let w0 = 2.0 * (omega_0 / (2.0 * fs)).atan();
     let mut gain_re = 1.0;
     let mut gain_im = 0.0;
     let ej_re = w0.cos();
@@ -277,8 +293,76 @@ fn pre_filter_weight(freq: f64, pre_filt: &[f64; 4]) -> f64 {
     }
 }
 
+/// Physical output units for response removal, matching rsudp's `units` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputUnits {
+    /// Displacement (m)
+    Disp,
+    /// Velocity (m/s)
+    Vel,
+    /// Acceleration (m/s²)
+    Acc,
+    /// Acceleration in multiples of g
+    Grav,
+    /// The instrument's native units (velocity for geophones, acceleration for accelerometers)
+    Chan,
+}
+
+/// Standard gravity used for `GRAV` output (matches rsudp).
+pub const GRAVITY: f64 = 9.81;
+
+impl OutputUnits {
+    /// Parse an rsudp units string (case-insensitive). Unknown values map to `Chan`.
+    pub fn parse(units: &str) -> Self {
+        Self::try_parse(units).unwrap_or(OutputUnits::Chan)
+    }
+
+    /// Parse an rsudp units string, `None` when it is unknown.
+    pub fn try_parse(units: &str) -> Option<Self> {
+        match units.trim().to_uppercase().as_str() {
+            "DISP" => Some(OutputUnits::Disp),
+            "VEL" => Some(OutputUnits::Vel),
+            "ACC" => Some(OutputUnits::Acc),
+            "GRAV" => Some(OutputUnits::Grav),
+            "CHAN" | "COUNTS" => Some(OutputUnits::Chan),
+            _ => None,
+        }
+    }
+
+    /// Number of time derivatives needed to go from `input_units` to these units.
+    /// Negative values mean integration.
+    pub fn derivative_order(self, input_units: &str) -> i32 {
+        let target = match self {
+            OutputUnits::Disp => 0,
+            OutputUnits::Vel => 1,
+            OutputUnits::Acc | OutputUnits::Grav => 2,
+            OutputUnits::Chan => return 0,
+        };
+        match ground_motion_order(input_units) {
+            Some(native) => target - native,
+            None => 0,
+        }
+    }
+
+    /// Final scalar applied after conversion (1/g for `GRAV`).
+    pub fn scale(self) -> f64 {
+        if self == OutputUnits::Grav { 1.0 / GRAVITY } else { 1.0 }
+    }
+}
+
+/// Time-derivative order of a StationXML unit name: M → 0, M/S → 1, M/S**2 → 2.
+fn ground_motion_order(units: &str) -> Option<i32> {
+    let u: String = units.to_uppercase().chars().filter(|c| !c.is_whitespace()).collect();
+    match u.as_str() {
+        "M" | "METER" | "METERS" => Some(0),
+        "M/S" | "M/SEC" => Some(1),
+        "M/S**2" | "M/S^2" | "M/S/S" | "M/S2" | "M/SEC**2" => Some(2),
+        _ => None,
+    }
+}
+
 /// Apply frequency-domain deconvolution to a batch of samples.
-/// This matches obspy's `Trace.remove_response()` with output='VEL'.
+/// This matches obspy's `Trace.remove_response()` with output in the instrument's native units.
 ///
 /// - `samples`: raw ADC counts (f64)
 /// - `response`: instrument response (poles/zeros)
@@ -293,12 +377,29 @@ pub fn deconvolve_response(
     sample_rate: f64,
     pre_filt: [f64; 4],
     water_level_db: f64,
+) -> Vec<f64> {
+    deconvolve_response_to(samples, response, sample_rate, pre_filt, water_level_db, OutputUnits::Chan)
+}
+
+/// Frequency-domain deconvolution with conversion to the requested output units.
+/// Matches obspy's `Trace.remove_response(output=...)`: the response is multiplied by
+/// `(jω)^n` for the required derivative order before the water level is applied, so
+/// velocity sensors can yield displacement or acceleration and vice versa.
+pub fn deconvolve_response_to(
+    samples: &[f64],
+    response: &ChannelResponse,
+    sample_rate: f64,
+    pre_filt: [f64; 4],
+    water_level_db: f64,
+    units: OutputUnits,
 ) -> Vec<f64> {
     let n = samples.len();
-    if n == 0 || response.poles.is_empty() {
+    let order = units.derivative_order(&response.input_units);
+    let scale = units.scale();
+    if n == 0 || (response.poles.is_empty() && order == 0) {
         // No response data — fall back to simple sensitivity division
         if response.sensitivity > 0.0 {
-            return samples.iter().map(|&s| s / response.sensitivity).collect();
+            return samples.iter().map(|&s| s / response.sensitivity * scale).collect();
         }
         return samples.to_vec();
     }
@@ -331,7 +432,10 @@ pub fn deconvolve_response(
             (k as f64 - nfft as f64) * freq_resolution
         };
         let omega = 2.0 * PI * freq;
-        let r = evaluate_response_at(response, omega);
+        // Counts per output unit: H(jω)·(jω)^-order, so dividing by it differentiates
+        // (order > 0) or integrates (order < 0) the native ground motion.
+        let r = evaluate_response_at(response, omega) * Complex::new(0.0, omega).powi(-order);
+        let r = if r.is_finite() { r } else { Complex::new(0.0, 0.0) };
         max_response_mag = max_response_mag.max(r.norm());
         response_values.push(r);
     }
@@ -380,8 +484,8 @@ pub fn deconvolve_response(
     fft_inverse.process(&mut fft_input);
 
     // Normalize (rustfft doesn't normalize) and extract real part
-    let scale = 1.0 / nfft as f64;
-    fft_input[..n].iter().map(|c| c.re * scale).collect()
+    let norm = scale / nfft as f64;
+    fft_input[..n].iter().map(|c| c.re * norm).collect()
 }
 
 /// Streaming deconvolution state for real-time processing.
@@ -391,6 +495,8 @@ pub struct StreamingDeconvolver {
     sample_rate: f64,
     pre_filt: [f64; 4],
     water_level_db: f64,
+    /// Requested output units
+    units: OutputUnits,
     /// Accumulated input buffer
     buffer: Vec<f64>,
     /// Overlap from previous block (for continuity)
@@ -415,6 +521,7 @@ impl StreamingDeconvolver {
             sample_rate,
             pre_filt,
             water_level_db,
+            units: OutputUnits::Chan,
             buffer: Vec::new(),
             overlap: Vec::new(),
             block_size,
@@ -422,6 +529,12 @@ impl StreamingDeconvolver {
         }
     }
 
+    /// Convert output to the given units instead of the instrument's native units.
+    pub fn with_units(mut self, units: OutputUnits) -> Self {
+        self.units = units;
+        self
+    }
+
     /// Add new samples and return any fully deconvolved output.
     /// Returns (deconvolved_samples, timestamp_offset_samples) where
     /// timestamp_offset_samples is how many samples from the START of the
@@ -438,12 +551,13 @@ impl StreamingDeconvolver {
             segment.extend_from_slice(&self.buffer[..self.block_size]);
 
             // Deconvolve the full segment
-            let deconv = deconvolve_response(
+            let deconv = deconvolve_response_to(
                 &segment,
                 &self.response,
                 self.sample_rate,
                 self.pre_filt,
                 self.water_level_db,
+                self.units,
             );
 
             // Keep only the new part (discard overlap region from output)
@@ -473,12 +587,13 @@ impl StreamingDeconvolver {
         segment.extend_from_slice(&self.overlap);
         segment.extend_from_slice(&self.buffer);
 
-        let deconv = deconvolve_response(
+        let deconv = deconvolve_response_to(
             &segment,
             &self.response,
             self.sample_rate,
             self.pre_filt,
             self.water_level_db,
+            self.units,
         );
 
         let valid_start = self.overlap.len();
@@ -602,6 +717,99 @@ mod tests {
         assert!(max_output < 1.15, "filtfilt passband gain too high: {}", max_output);
     }
 
+    #[test]
+    fn test_bandpass_matches_obspy_sos() {
+        // ObsPy bandpass(freqmin, freqmax, df, corners) SOS; the first case is rsudp's alert filter
+        let cases = [
+            ((4, 0.1, 2.0, 100.0), vec![
+                Biquad::new(1.091_166_705_330_671e-5, 2.182_333_410_661_342e-5, 1.091_166_705_330_671e-5, -1.799_856_289_596_911, 0.811_800_723_049_033_8),
+                Biquad::new(1.0, 2.0, 1.0, -1.902_151_139_520_083, 0.916_896_668_955_194_2),
+                Biquad::new(1.0, -2.0, 1.0, -1.987_573_235_639_373, 0.987_620_193_210_148),
+                Biquad::new(1.0, -2.0, 1.0, -1.995_513_812_922_410_6, 0.995_554_195_149_240_2),
+            ]),
+            ((4, 1.0, 20.0, 100.0), vec![
+                Biquad::new(0.039_602_662_652_766_844, 0.079_205_325_305_533_69, 0.039_602_662_652_766_844, -0.409_571_388_080_190_45, 0.085_814_659_199_895_89),
+                Biquad::new(1.0, 2.0, 1.0, -0.477_463_794_944_420_2, 0.497_011_838_876_397_55),
+                Biquad::new(1.0, -2.0, 1.0, -1.879_501_037_691_992_5, 0.883_839_457_037_365_6),
+                Biquad::new(1.0, -2.0, 1.0, -1.952_033_456_316_926, 0.955_979_007_394_660_6),
+            ]),
+            ((2, 0.5, 10.0, 50.0), vec![
+                Biquad::new(0.190_616_600_097_497_6, 0.381_233_200_194_995_2, 0.190_616_600_097_497_6, -0.423_531_164_568_301_75, 0.225_709_867_542_097_76),
+                Biquad::new(1.0, -2.0, 1.0, -1.911_551_237_508_210_7, 0.915_653_300_483_273_8),
+            ]),
+            ((4, 0.1, 25.0, 250.0), vec![
+                Biquad::new(0.004_758_947_871_519_674_5, 0.009_517_895_743_039_349, 0.004_758_947_871_519_674_5, -1.053_966_441_598_274_1, 0.299_115_090_152_754_45),
+                Biquad::new(1.0, 2.0, 1.0, -1.323_367_210_839_722_5, 0.634_991_132_688_226_7),
+                Biquad::new(1.0, -2.0, 1.0, -1.995_335_073_655_558_2, 0.995_341_459_616_550_4),
+                Biquad::new(1.0, -2.0, 1.0, -1.998_082_489_797_620_8, 0.998_088_814_445_462_4),
+            ]),
+        ];
+
+        for ((order, low, high, fs), reference) in cases {
+            let mut expected = BiquadChain::new(reference);
+            let mut actual = BiquadChain::bandpass(order, low, high, fs);
+
+            // Compare impulse responses: section ordering and zero placement differ,
+            // but the cascaded transfer function must be identical
+            let mut peak = 0.0_f64;
+            let mut max_err = 0.0_f64;
+            for i in 0..(30.0 * fs) as usize {
+                let x = if i == 0 { 1.0 } else { 0.0 };
+                let e = expected.process(x);
+                let a = actual.process(x);
+                peak = peak.max(e.abs());
+                max_err = max_err.max((e - a).abs());
+            }
+            assert!(
+                max_err / peak < 1e-9,
+                "{}-{} Hz @ {} Hz: impulse response mismatch: {}",
+                low, high, fs, max_err / peak
+            );
+        }
+    }
+
+    #[test]
+    fn test_bandpass_matches_butterworth_magnitude() {
+        // ObsPy designs an analog Butterworth bandpass with pre-warped corners and applies
+        // the bilinear transform, so |H(e^jw)| = 1 / sqrt(1 + ((Ω² - Ω0²) / (Ω·BW))^(2N))
+        // with Ω = 2·fs·tan(w/2). Check several bands and sample rates.
+        let cases = [
+            (4, 0.1, 2.0, 100.0),
+            (4, 0.7, 2.0, 100.0),
+            (4, 1.0, 20.0, 100.0),
+            (2, 0.5, 10.0, 50.0),
+            (3, 1.0, 45.0, 200.0),
+            (4, 0.1, 25.0, 250.0),
+        ];
+        for &(order, low, high, fs) in &cases {
+            let sections = butter_bandpass_sos(order, low, high, fs);
+            let omega_low = 2.0 * fs * (PI * low / fs).tan();
+            let omega_high = 2.0 * fs * (PI * high / fs).tan();
+            let omega_0_sq = omega_low * omega_high;
+            let bw = omega_high - omega_low;
+
+            for step in 1..200 {
+                let freq = step as f64 * 0.5 * fs / 200.0;
+                let w = 2.0 * PI * freq / fs;
+                let z_inv = Complex::new(w.cos(), -w.sin());
+                let h = sections.iter().fold(Complex::new(1.0, 0.0), |acc, s| {
+                    let num = s.b0 + s.b1 * z_inv + s.b2 * z_inv * z_inv;
+                    let den = 1.0 + s.a1 * z_inv + s.a2 * z_inv * z_inv;
+                    acc * num / den
+                });
+
+                let omega = 2.0 * fs * (w / 2.0).tan();
+                let x = (omega * omega - omega_0_sq) / (omega * bw);
+                let expected = 1.0 / (1.0 + x.powi(2 * order as i32)).sqrt();
+                assert!(
+                    (h.norm() - expected).abs() < 1e-6,
+                    "order={} band={}-{} fs={} f={}: |H|={} expected {}",
+                    order, low, high, fs, freq, h.norm(), expected
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_biquad_chain_reset() {
         let mut chain = BiquadChain::bandpass(2, 1.0, 5.0, 100.0);
@@ -630,6 +838,7 @@ mod tests {
             normalization_factor: 673.744,
             stage_gain: 399_650_000.0,
             sensitivity: 399_650_000.0,
+            input_units: "M/S".to_string(),
         };
 
         // At reference frequency (5 Hz), |H| should ≈ sensitivity
@@ -673,6 +882,7 @@ mod tests {
             normalization_factor: 673.744,
             stage_gain: 399_650_000.0,
             sensitivity: 399_650_000.0,
+            input_units: "M/S".to_string(),
         };
 
         let sample_rate = 100.0;
@@ -712,6 +922,7 @@ mod tests {
             normalization_factor: 673.744,
             stage_gain: 399_650_000.0,
             sensitivity: 399_650_000.0,
+            input_units: "M/S".to_string(),
         };
 
         let sample_rate = 100.0;
@@ -769,6 +980,7 @@ mod tests {
             normalization_factor: 673.744,
             stage_gain: 399_650_000.0,
             sensitivity: 399_650_000.0,
+            input_units: "M/S".to_string(),
         };
 
         let sample_rate = 100.0;
@@ -827,4 +1039,86 @@ mod tests {
             ratio
         );
     }
+
+    #[test]
+    fn test_output_units_derivative_order() {
+        assert_eq!(OutputUnits::parse("disp"), OutputUnits::Disp);
+        assert_eq!(OutputUnits::parse("GRAV"), OutputUnits::Grav);
+        assert_eq!(OutputUnits::parse("CHAN"), OutputUnits::Chan);
+        assert_eq!(OutputUnits::parse("counts"), OutputUnits::Chan);
+
+        // Geophone (velocity) channel
+        assert_eq!(OutputUnits::Disp.derivative_order("M/S"), -1);
+        assert_eq!(OutputUnits::Vel.derivative_order("M/S"), 0);
+        assert_eq!(OutputUnits::Acc.derivative_order("M/S"), 1);
+        assert_eq!(OutputUnits::Grav.derivative_order("M/S"), 1);
+        // Accelerometer channel
+        assert_eq!(OutputUnits::Disp.derivative_order("M/S**2"), -2);
+        assert_eq!(OutputUnits::Vel.derivative_order("m/s**2"), -1);
+        assert_eq!(OutputUnits::Acc.derivative_order("M/S**2"), 0);
+        // Native units never convert
+        assert_eq!(OutputUnits::Chan.derivative_order("M/S**2"), 0);
+    }
+
+    #[test]
+    fn test_deconvolve_response_to_disp_and_acc() {
+        // A 5 Hz velocity sine of amplitude v integrates to v/ω and differentiates to v·ω
+        let response = ChannelResponse {
+            zeros: vec![(0.0, 0.0), (0.0, 0.0), (0.0, 0.0)],
+            poles: vec![(-1.0, 0.0), (-3.03, 0.0), (-3.03, 0.0), (-666.67, 0.0)],
+            normalization_factor: 673.744,
+            stage_gain: 399_650_000.0,
+            sensitivity: 399_650_000.0,
+            input_units: "M/S".to_string(),
+        };
+
+        let sample_rate = 100.0;
+        let n = 1000;
+        let amp = 10000.0;
+        let omega = 2.0 * PI * 5.0;
+        let signal: Vec<f64> = (0..n)
+            .map(|i| amp * (omega * i as f64 / sample_rate).sin())
+            .collect();
+        let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
+        let vel_amp = amp / response.sensitivity;
+
+        // obspy's default 60 dB water level; rsudp's 4.5 dB clips the (jω)^n-weighted
+        // response far from its peak, just as remove_response(water_level=4.5) does.
+        let peak = |units: OutputUnits| {
+            let out = deconvolve_response_to(&signal, &response, sample_rate, pre_filt, 60.0, units);
+            out[n / 4..3 * n / 4].iter().map(|&x| x.abs()).fold(0.0_f64, f64::max)
+        };
+
+        let disp_ratio = peak(OutputUnits::Disp) / (vel_amp / omega);
+        assert!((disp_ratio - 1.0).abs() < 0.15, "DISP amplitude ratio={}", disp_ratio);
+
+        let acc_ratio = peak(OutputUnits::Acc) / (vel_amp * omega);
+        assert!((acc_ratio - 1.0).abs() < 0.15, "ACC amplitude ratio={}", acc_ratio);
+
+        let grav_ratio = peak(OutputUnits::Grav) / (vel_amp * omega / GRAVITY);
+        assert!((grav_ratio - 1.0).abs() < 0.15, "GRAV amplitude ratio={}", grav_ratio);
+    }
+
+    #[test]
+    fn test_deconvolve_sensitivity_only_integration() {
+        // Accelerometer with flat response: 2 Hz acceleration sine → velocity a/ω
+        let response = ChannelResponse::from_sensitivity(384_500.0, "M/S**2");
+        let sample_rate = 100.0;
+        let n = 2000;
+        let amp = 384_500.0; // 1 m/s² in counts
+        let omega = 2.0 * PI * 2.0;
+        let signal: Vec<f64> = (0..n)
+            .map(|i| amp * (omega * i as f64 / sample_rate).sin())
+            .collect();
+        let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
+
+        // Native units: plain division, no FFT
+        let acc = deconvolve_response_to(&signal, &response, sample_rate, pre_filt, 4.5, OutputUnits::Chan);
+        assert!((acc[12] - signal[12] / amp).abs() < 1e-12);
+
+        let vel = deconvolve_response_to(&signal, &response, sample_rate, pre_filt, 60.0, OutputUnits::Vel);
+        let peak = vel[n / 4..3 * n / 4].iter().map(|&x| x.abs()).fold(0.0_f64, f64::max);
+        let ratio = peak / (1.0 / omega);
+        assert!((ratio - 1.0).abs() < 0.1, "VEL amplitude ratio={}", ratio);
+    }
 }
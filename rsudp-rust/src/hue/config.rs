@@ -6,6 +6,9 @@ pub struct HueConfig {
     pub app_key: String,
     pub bridge_id: Option<String>,
     pub target_ids: Vec<String>,
+    /// Trigger names that drive the lights (empty = all)
+    #[serde(default)]
+    pub triggers: Vec<String>,
 }
 
 impl Default for HueConfig {
@@ -15,6 +18,7 @@ impl Default for HueConfig {
             app_key: "".to_string(),
             bridge_id: None,
             target_ids: Vec::new(),
+            triggers: Vec::new(),
         }
     }
 }
@@ -4,6 +4,7 @@ use std::time::Duration;
 use crate::hue::config::HueConfig;
 use crate::hue::client::HueClient;
 use crate::hue::discovery::Discovery;
+use crate::intensity::IntensityScale;
 use tracing::{info, warn, error};
 use serde_json::json;
 use std::collections::HashMap;
@@ -45,6 +46,8 @@ pub struct HueIntegration {
     pre_alert_states: Arc<Mutex<HashMap<String, StoredState>>>,
     // Token to manage preemption
     reset_token: Arc<Mutex<u64>>,
+    // Scale the alert colors follow
+    scale: IntensityScale,
 }
 
 impl HueIntegration {
@@ -54,9 +57,21 @@ impl HueIntegration {
             client: Arc::new(Mutex::new(None)),
             pre_alert_states: Arc::new(Mutex::new(HashMap::new())),
             reset_token: Arc::new(Mutex::new(0)),
+            scale: IntensityScale::default(),
         }
     }
 
+    /// Color alerts by `scale` instead of JMA.
+    pub fn with_scale(mut self, scale: IntensityScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Whether alerts from the named trigger should drive the lights.
+    pub fn routes_trigger(&self, trigger_name: &str) -> bool {
+        crate::settings::routes_trigger(&self.config.triggers, trigger_name)
+    }
+
     pub async fn start(&self) {
         if !self.config.enabled {
             return;
@@ -163,10 +178,33 @@ impl HueIntegration {
                                     }
                                 }
                             
+                                /// Switch the alert pulse to the color of an early intensity estimate, ahead of the reset.
+                                pub async fn early_alert(&self, estimated_intensity: f64) {
+                                    if !self.config.enabled { return; }
+
+                                    let color = self.scale.color(estimated_intensity);
+                                    let (x, y) = rgb_to_xy(color.0, color.1, color.2);
+                                    let guard = self.client.lock().await;
+                                    if let Some(client) = &*guard {
+                                        info!("Sending Early Warning Pulse (Intensity {:.1}) to Hue lights...", estimated_intensity);
+                                        let payload = json!({
+                                            "on": { "on": true },
+                                            "alert": { "action": "breathe" },
+                                            "color": { "xy": { "x": x, "y": y } }
+                                        });
+
+                                        for id in &self.config.target_ids {
+                                            if let Err(e) = client.set_light_state(id, &payload).await {
+                                                error!("Failed to pulse light {}: {}", id, e);
+                                            }
+                                        }
+                                    }
+                                }
+
                                 pub async fn reset_alert(&self, max_intensity: f64) {
                                     if !self.config.enabled { return; }
                             
-                                    let color = self.get_jma_color(max_intensity);
+                                    let color = self.scale.color(max_intensity);
                                     let (x, y) = rgb_to_xy(color.0, color.1, color.2);
                                     
                                     // Get current token
@@ -320,26 +358,6 @@ impl HueIntegration {
         }
     }
 
-    fn get_jma_color(&self, intensity: f64) -> (u8, u8, u8) {
-        // Int 7: 180, 0, 104
-        if intensity >= 6.5 { return (180, 0, 104); }
-        // Int 6+: 165, 0, 33
-        if intensity >= 6.0 { return (165, 0, 33); }
-        // Int 6-: 255, 40, 0
-        if intensity >= 5.5 { return (255, 40, 0); }
-        // Int 5+: 255, 153, 0
-        if intensity >= 5.0 { return (255, 153, 0); }
-        // Int 5-: 255, 230, 0
-        if intensity >= 4.5 { return (255, 230, 0); }
-        // Int 4: 250, 230, 150
-        if intensity >= 3.5 { return (250, 230, 150); }
-        // Int 3: 0, 65, 255
-        if intensity >= 2.5 { return (0, 65, 255); }
-        // Int 2: 0, 170, 255
-        if intensity >= 1.5 { return (0, 170, 255); }
-        // Int 1: 242, 242, 255
-        (242, 242, 255)
-    }
 }
 
 #[cfg(test)]
@@ -371,10 +389,10 @@ mod tests {
         let integration = HueIntegration::new(HueConfig::default());
         
         // Intensity 7
-        assert_eq!(integration.get_jma_color(6.8), (180, 0, 104));
+        assert_eq!(integration.scale.color(6.8), (180, 0, 104));
         // Intensity 4
-        assert_eq!(integration.get_jma_color(3.7), (250, 230, 150));
+        assert_eq!(integration.scale.color(3.7), (250, 230, 150));
         // Intensity 1
-        assert_eq!(integration.get_jma_color(1.0), (242, 242, 255));
+        assert_eq!(integration.scale.color(1.0), (242, 242, 255));
     }
 }
\ No newline at end of file
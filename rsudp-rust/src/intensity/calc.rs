@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 use chrono::{DateTime, Utc, Duration};
-use crate::intensity::{IntensityConfig, IntensityResult, get_shindo_class, filter::JmaFilter};
+use crate::filter::{deconvolve_response_to, OutputUnits};
+use crate::intensity::{GroundMotionPeaks, IntensityConfig, IntensityResult, IntensityScale, filter::JmaFilter};
 use tracing::info;
 
+/// Water level for the restitution of velocity channels.
+const VELOCITY_WATER_LEVEL_DB: f64 = 60.0;
+
 pub struct IntensityManager {
     config: IntensityConfig,
     buffers: HashMap<String, Vec<f64>>,
@@ -16,14 +20,25 @@ impl IntensityManager {
         let buffers = config.channels.iter().map(|c| (c.clone(), Vec::new())).collect();
         let buffer_start_times = HashMap::new();
         Self {
-            config,
             buffers,
             buffer_start_times,
-            filter: JmaFilter::new(100.0),
+            filter: JmaFilter::new(config.sample_rate),
+            config,
             results: Vec::new(),
         }
     }
 
+    /// Follow the sample rate of the incoming data; a change restarts the window.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        if sample_rate <= 0.0 || (sample_rate - self.config.sample_rate).abs() < 1e-9 {
+            return;
+        }
+        info!("Intensity: sample rate {} Hz -> {} Hz, resetting intensity buffers", self.config.sample_rate, sample_rate);
+        self.config.sample_rate = sample_rate;
+        self.filter = JmaFilter::new(sample_rate);
+        self.reset();
+    }
+
     pub fn config(&self) -> &IntensityConfig {
         &self.config
     }
@@ -69,16 +84,17 @@ impl IntensityManager {
                     let diff_ms = (start_time - expected_next).num_milliseconds();
                     let sample_interval_ms = 1000.0 / self.config.sample_rate;
 
-                    if diff_ms.abs() < 100 { // Ignore small jitter (< 10 samples)
+                    if diff_ms.abs() < 100 { // Ignore small jitter (< 100 ms)
                         buf.extend(data);
                     } else if diff_ms > 0 {
                         // Gap: pad with last value but with a slight fade to zero (mean) if it's a long gap
                         let pad_samples = (diff_ms as f64 / sample_interval_ms).round() as usize;
                         if pad_samples > 0 {
                             let last_val = buf.last().cloned().unwrap_or(0.0);
+                            let fade = self.config.sample_rate.round().max(1.0) as usize;
                             for i in 0..pad_samples {
-                                // Linear fade over 1 second (100 samples)
-                                let factor = if i < 100 { 1.0 - (i as f64 / 100.0) } else { 0.0 };
+                                // Linear fade over 1 second
+                                let factor = if i < fade { 1.0 - (i as f64 / fade as f64) } else { 0.0 };
                                 buf.push(last_val * factor);
                             }
                         }
@@ -125,26 +141,52 @@ impl IntensityManager {
         }
 
         while self.config.channels.iter().all(|ch| self.buffers.get(ch).unwrap().len() >= window_len) {
-            let mut window_data = [Vec::new(), Vec::new(), Vec::new()];
-            for (i, ch) in self.config.channels.iter().enumerate().take(3) {
-                let buf = self.buffers.get(ch).unwrap();
-                window_data[i] = buf[0..window_len].to_vec();
+            let mut window_data: Vec<Vec<f64>> = Vec::with_capacity(self.config.channels.len());
+            for (i, ch) in self.config.channels.iter().enumerate() {
+                let window = &self.buffers.get(ch).unwrap()[0..window_len];
+                let gal = match self.config.responses.get(i).and_then(|r| r.as_ref()) {
+                    // Velocity sensor: deconvolve to acceleration (m/s²)
+                    Some(response) => {
+                        let fs = self.config.sample_rate;
+                        let pre_filt = [0.05, 0.1, 0.4 * fs, 0.45 * fs];
+                        deconvolve_response_to(window, response, fs, pre_filt, VELOCITY_WATER_LEVEL_DB, OutputUnits::Acc)
+                            .into_iter()
+                            .map(|v| v * 100.0)
+                            .collect()
+                    }
+                    None => {
+                        let sens = self.config.sensitivities.get(i).unwrap_or(&1.0);
+                        window.iter().map(|v| v * 100.0 * sens).collect()
+                    }
+                };
+                window_data.push(gal);
             }
 
-            for (i, wd) in window_data.iter_mut().enumerate() {
-                let sens = self.config.sensitivities.get(i).unwrap_or(&1.0);
-                for val in wd.iter_mut() {
-                    *val *= 100.0 * sens;
+            // Rotate 1/2 horizontals to north/east
+            if let (Some([a1, a2]), [_, h1, h2]) = (self.config.azimuths, &mut window_data[..]) {
+                let (s1, c1) = a1.to_radians().sin_cos();
+                let (s2, c2) = a2.to_radians().sin_cos();
+                for (x1, x2) in h1.iter_mut().zip(h2.iter_mut()) {
+                    let (n, e) = (*x1 * c1 + *x2 * c2, *x1 * s1 + *x2 * s2);
+                    *x1 = n;
+                    *x2 = e;
                 }
             }
 
-            let intensity = self.filter.calculate_intensity(&window_data[0], &window_data[1], &window_data[2]);
-            let shindo_class = get_shindo_class(intensity);
+            let mut peaks = GroundMotionPeaks { jma: Some(self.filter.calculate_intensity_components(&window_data)), ..Default::default() };
+            if self.config.scale != IntensityScale::Jma {
+                let (pga, pgv) = self.filter.peak_motion(&window_data, self.config.scale.peak_measure());
+                peaks.pga = Some(pga);
+                peaks.pgv = Some(pgv);
+            }
+            let scale = self.config.scale;
+            let intensity = scale.intensity(&peaks);
 
             self.results.push(IntensityResult {
                 timestamp: latest_start + Duration::seconds(60),
                 intensity,
-                shindo_class,
+                shindo_class: scale.class(intensity),
+                scale,
             });
 
             let slide_samples = (self.config.sample_rate * 1.0) as usize;
@@ -1,10 +1,13 @@
 use rustfft::{FftPlanner, num_complex::Complex};
 
+use crate::intensity::PeakMeasure;
+
 pub struct JmaFilter {
     sample_rate: f64,
 }
 
 impl JmaFilter {
+    /// Filter for components sampled at `sample_rate` (Hz), e.g. 50, 100, 200 or 250.
     pub fn new(sample_rate: f64) -> Self {
         Self { sample_rate }
     }
@@ -16,13 +19,12 @@ impl JmaFilter {
         }
 
         // 1. Period effect filter (A_1(f))
-        let x = 1.0 / f; // period T
-        let period_gain = (x / (1.0 + 0.694 * x.powi(2) + 0.241 * x.powi(4) + 0.0557 * x.powi(6) 
-                          + 0.009664 * x.powi(8) + 0.00134 * x.powi(10) + 0.000155 * x.powi(12))).sqrt();
+        let period_gain = (1.0 / f).sqrt();
 
-        // 2. High-cut filter (A_2(f))
-        let f_h = f / 10.0;
-        let high_cut = 1.0 / (1.0 + f_h.powi(6)).sqrt();
+        // 2. High-cut filter (A_2(f)), y = f / 10 Hz
+        let y = f / 10.0;
+        let high_cut = 1.0 / (1.0 + 0.694 * y.powi(2) + 0.241 * y.powi(4) + 0.0557 * y.powi(6)
+                          + 0.009664 * y.powi(8) + 0.00134 * y.powi(10) + 0.000155 * y.powi(12)).sqrt();
 
         // 3. Low-cut filter (A_3(f))
         // This is the official low-cut specified by JMA. 
@@ -48,90 +50,168 @@ impl JmaFilter {
         }
     }
 
-    pub fn calculate_intensity(&self, x: &[f64], y: &[f64], z: &[f64]) -> f64 {
-        let n = x.len();
-        if n == 0 { return -2.0; }
-
-        let mut planner = FftPlanner::new();
+    /// Detrend, taper and filter one component in the frequency domain; `response`
+    /// is evaluated at the signed frequency of each bin (Hz).
+    fn filter_component(&self, planner: &mut FftPlanner<f64>, data: &[f64], response: impl Fn(f64) -> Complex<f64>) -> Vec<f64> {
+        let n = data.len();
         let fft = planner.plan_fft_forward(n);
-
-        let mut process_component = |data: &[f64]| -> Vec<f64> {
-            let mut d = data.to_vec();
-            let dn = d.len() as f64;
-            
-            // 1. Robust Linear Detrend
-            // Removing DC offset and linear drift is required for FFT stability 
-            // especially when huge gravity offset (1g) exists on ENZ.
-            let sum_x: f64 = (0..d.len()).map(|i| i as f64).sum();
-            let sum_x2: f64 = (0..d.len()).map(|i| (i as f64).powi(2)).sum();
-            let sum_y: f64 = d.iter().sum();
-            let sum_xy: f64 = d.iter().enumerate().map(|(i, &v)| i as f64 * v).sum();
-            
-            let denom = dn * sum_x2 - sum_x.powi(2);
-            if denom.abs() > 1e-9 {
-                let slope = (dn * sum_xy - sum_x * sum_y) / denom;
-                let intercept = (sum_y - slope * sum_x) / dn;
-                for (i, val) in d.iter_mut().enumerate() {
-                    *val -= slope * i as f64 + intercept;
-                }
-            }
-            
-            // 2. Apply Taper (5% on each side)
-            // This suppresses the "edge effects" that cause spikes in noise data.
-            Self::apply_taper(&mut d, 0.05);
-
-            let mut buffer: Vec<Complex<f64>> = d
-                .iter()
-                .map(|&v| Complex { re: v, im: 0.0 })
-                .collect();
-
-            fft.process(&mut buffer);
-
-            for (i, coeff) in buffer.iter_mut().enumerate() {
-                let f = if i <= n / 2 {
-                    i as f64 * self.sample_rate / dn
-                } else {
-                    (n - i) as f64 * self.sample_rate / dn
-                };
-                let gain = self.calculate_gain(f);
-                *coeff *= gain;
+        let mut d = data.to_vec();
+        let dn = d.len() as f64;
+        
+        // 1. Robust Linear Detrend
+        // Removing DC offset and linear drift is required for FFT stability 
+        // especially when huge gravity offset (1g) exists on ENZ.
+        let sum_x: f64 = (0..d.len()).map(|i| i as f64).sum();
+        let sum_x2: f64 = (0..d.len()).map(|i| (i as f64).powi(2)).sum();
+        let sum_y: f64 = d.iter().sum();
+        let sum_xy: f64 = d.iter().enumerate().map(|(i, &v)| i as f64 * v).sum();
+        
+        let denom = dn * sum_x2 - sum_x.powi(2);
+        if denom.abs() > 1e-9 {
+            let slope = (dn * sum_xy - sum_x * sum_y) / denom;
+            let intercept = (sum_y - slope * sum_x) / dn;
+            for (i, val) in d.iter_mut().enumerate() {
+                *val -= slope * i as f64 + intercept;
             }
+        }
+        
+        // 2. Apply Taper (5% on each side)
+        // This suppresses the "edge effects" that cause spikes in noise data.
+        Self::apply_taper(&mut d, 0.05);
 
-            let ifft = planner.plan_fft_inverse(n);
-            ifft.process(&mut buffer);
+        let mut buffer: Vec<Complex<f64>> = d
+            .iter()
+            .map(|&v| Complex { re: v, im: 0.0 })
+            .collect();
+
+        fft.process(&mut buffer);
+
+        for (i, coeff) in buffer.iter_mut().enumerate() {
+            let f = if i <= n / 2 {
+                i as f64 * self.sample_rate / dn
+            } else {
+                -((n - i) as f64) * self.sample_rate / dn
+            };
+            *coeff *= response(f);
+        }
+
+        let ifft = planner.plan_fft_inverse(n);
+        ifft.process(&mut buffer);
 
-            buffer.iter().map(|c| c.re / dn).collect()
+        buffer.iter().map(|c| c.re / dn).collect()
+    }
+
+    /// Peak acceleration (gal) and velocity (cm/s) of acceleration components in gal,
+    /// band-limited to 0.1–10 Hz as for the PGA/PGV-based scales. `components` are the
+    /// vertical followed by the two horizontals; a vertical-only station stands in for
+    /// the horizontals.
+    pub fn peak_motion(&self, components: &[Vec<f64>], measure: PeakMeasure) -> (f64, f64) {
+        let n = components.first().map_or(0, |c| c.len());
+        if n == 0 { return (0.0, 0.0); }
+        let band = |f: f64| {
+            let f = f.abs();
+            if f <= 0.0 { 0.0 } else { 1.0 / ((1.0 + (0.1 / f).powi(8)) * (1.0 + (f / 10.0).powi(8))).sqrt() }
+        };
+        let components = match (measure, components) {
+            (PeakMeasure::LargerHorizontal | PeakMeasure::HorizontalVector, [_, h1, h2]) => vec![h1.clone(), h2.clone()],
+            _ => components.to_vec(),
         };
+        let mut planner = FftPlanner::new();
+        let acc: Vec<Vec<f64>> = components.iter().map(|c| self.filter_component(&mut planner, c, |f| Complex::new(band(f), 0.0))).collect();
+        // Integrate by dividing by jω
+        let vel: Vec<Vec<f64>> = components
+            .iter()
+            .map(|c| self.filter_component(&mut planner, c, |f| if f == 0.0 { Complex::new(0.0, 0.0) } else { Complex::new(0.0, -band(f) / (2.0 * std::f64::consts::PI * f)) }))
+            .collect();
+        let peak = |series: &[Vec<f64>]| match measure {
+            PeakMeasure::LargerHorizontal => series.iter().flatten().fold(0.0, |max: f64, v| max.max(v.abs())),
+            PeakMeasure::HorizontalVector | PeakMeasure::Vector => {
+                (0..n).map(|i| series.iter().map(|s| s[i] * s[i]).sum::<f64>().sqrt()).fold(0.0, f64::max)
+            }
+        };
+        (peak(&acc), peak(&vel))
+    }
+
+    pub fn calculate_intensity(&self, x: &[f64], y: &[f64], z: &[f64]) -> f64 {
+        self.calculate_intensity_components(&[x.to_vec(), y.to_vec(), z.to_vec()])
+    }
 
-        let fx = process_component(x);
-        let fy = process_component(y);
-        let fz = process_component(z);
+    /// Intensity from any number of acceleration components (gal), e.g. a
+    /// vertical-only station; the composite is the vector sum of all of them.
+    pub fn calculate_intensity_components(&self, components: &[Vec<f64>]) -> f64 {
+        let n = components.first().map_or(0, |c| c.len());
+        if n == 0 { return -2.0; }
 
-        let mut composite: Vec<f64> = fx
+        let mut planner = FftPlanner::new();
+        let filtered: Vec<Vec<f64>> = components
             .iter()
-            .zip(fy.iter())
-            .zip(fz.iter())
-            .map(|((&ax, &ay), &az)| (ax * ax + ay * ay + az * az).sqrt())
+            .map(|c| self.filter_component(&mut planner, c, |f| Complex::new(self.calculate_gain(f.abs()), 0.0)))
+            .collect();
+
+        let mut composite: Vec<f64> = (0..n)
+            .map(|i| filtered.iter().map(|f| f[i] * f[i]).sum::<f64>().sqrt())
             .collect();
 
-        // 3. Sort to find the acceleration 'a' where duration is >= 0.3s
+        // 3. Sort to find the acceleration 'a' exceeded for a total of 0.3 s:
+        // each sample stands for 1/fs seconds, so 'a' is the (0.3·fs)-th largest
         composite.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
 
-        let idx = (0.3 * self.sample_rate).round() as usize;
-        let a_03 = if idx < composite.len() {
-            composite[idx]
-        } else {
-            composite.last().cloned().unwrap_or(0.0)
-        };
+        let count = (0.3 * self.sample_rate).round().max(1.0) as usize;
+        let a_03 = composite.get(count - 1).or(composite.last()).cloned().unwrap_or(0.0);
 
         // 4. Calculate intensity without arbitrary thresholds
         if a_03 > 1e-15 { // Minimal guard for log10
             let intensity = 2.0 * a_03.log10() + 0.94;
-            // Floor to 1 decimal place as per JMA standard for display
-            let result = (intensity * 10.0).floor() / 10.0;
+            // JMA rounds to 2 decimal places, then truncates to 1
+            let result = ((intensity * 100.0).round() / 10.0).floor() / 10.0;
             if result < -2.0 { -2.0 } else { result }
         } else {
             -2.0
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// 60 s of a tone (gal) on one component.
+    fn tone(amplitude: f64, f: f64, fs: f64) -> Vec<f64> {
+        (0..(60.0 * fs) as usize).map(|i| amplitude * (2.0 * PI * f * i as f64 / fs).sin()).collect()
+    }
+
+    #[test]
+    fn test_intensity_independent_of_sample_rate() {
+        // A long tone is exceeded for 0.3 s just below its peak, so the intensity is
+        // 2·log10(A·gain(f)) + 0.94 at every rate
+        for (amplitude, f) in [(50.0, 0.7), (20.0, 2.0), (100.0, 4.7)] {
+            for fs in [50.0, 100.0, 200.0, 250.0] {
+                let filter = JmaFilter::new(fs);
+                let expected = 2.0 * (amplitude * filter.calculate_gain(f)).log10() + 0.94;
+                let intensity = filter.calculate_intensity_components(&[tone(amplitude, f, fs)]);
+                assert!(intensity <= expected + 0.005 && expected - intensity < 0.1, "{} Hz at {} Hz: {} vs {}", f, fs, intensity, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_peak_motion_measures() {
+        // Strong vertical, 30 and 40 gal horizontals in phase (a 50 gal horizontal vector)
+        let fs = 100.0;
+        let components = [tone(100.0, 2.0, fs), tone(30.0, 2.0, fs), tone(40.0, 2.0, fs)];
+        let filter = JmaFilter::new(fs);
+
+        let (larger, _) = filter.peak_motion(&components, PeakMeasure::LargerHorizontal);
+        let (horizontal, _) = filter.peak_motion(&components, PeakMeasure::HorizontalVector);
+        let (vector, _) = filter.peak_motion(&components, PeakMeasure::Vector);
+        assert!((larger - 40.0).abs() < 1.0, "{}", larger);
+        assert!((horizontal - 50.0).abs() < 1.0, "{}", horizontal);
+        assert!(vector > 100.0, "{}", vector);
+
+        // A vertical-only station measures the vertical
+        let (vertical, pgv) = filter.peak_motion(&components[..1], PeakMeasure::LargerHorizontal);
+        assert!((vertical - 100.0).abs() < 2.0, "{}", vertical);
+        assert!((pgv - 100.0 / (2.0 * PI * 2.0)).abs() < 0.2, "{}", pgv);
+    }
+}
@@ -1,23 +1,114 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::parser::stationxml::{infer_input_units, ChannelResponse};
+use crate::settings::IntensitySettings;
 
 mod calc;
 pub mod filter;
+pub mod realtime;
+pub mod scale;
 
 pub use calc::IntensityManager;
+pub use realtime::{RealtimeIntensity, RealtimeIntensitySample};
+pub use scale::{GroundMotionPeaks, IntensityScale, PeakMeasure};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct IntensityConfig {
+    /// Vertical channel first, then the horizontals (one or three channels)
     pub channels: Vec<String>,
+    /// Sample rate of the channels (Hz); the manager follows the rate of the data
     pub sample_rate: f64,
+    /// Counts to m/s² of the accelerometer channels (1/sensitivity)
     pub sensitivities: Vec<f64>,
+    /// Azimuths of the two horizontals (degrees from north), when they are not N/E
+    pub azimuths: Option<[f64; 2]>,
+    /// Responses of velocity channels, which are deconvolved to acceleration;
+    /// `None` for accelerometers, which are scaled by `sensitivities`
+    pub responses: Vec<Option<ChannelResponse>>,
+    /// Scale the results are reported in
+    pub scale: IntensityScale,
+}
+
+/// Default Raspberry Shake MEMS accelerometer sensitivity (counts per m/s²).
+const DEFAULT_ACC_SENSITIVITY: f64 = 384500.0;
+/// Default Raspberry Shake geophone sensitivity (counts per m/s).
+const DEFAULT_VEL_SENSITIVITY: f64 = 399000000.0;
+
+impl IntensityConfig {
+    /// Build the component mapping from `[intensity]` and the station metadata.
+    /// Returns `None` (with a warning) when the mapping is unusable.
+    pub fn from_settings(
+        settings: &IntensitySettings,
+        sensitivities: &HashMap<String, f64>,
+        responses: &HashMap<String, ChannelResponse>,
+    ) -> Option<Self> {
+        if settings.vertical.is_empty() {
+            warn!("Intensity: no vertical channel configured");
+            return None;
+        }
+        if !matches!(settings.horizontals.len(), 0 | 2) {
+            warn!("Intensity: expected two horizontal channels, got {:?}", settings.horizontals);
+            return None;
+        }
+        let azimuths = match settings.azimuths[..] {
+            [] => None,
+            // N/E need no rotation
+            [a1, a2] if a1 == 0.0 && a2 == 90.0 => None,
+            [a1, a2] if !settings.horizontals.is_empty() => Some([a1, a2]),
+            _ => {
+                warn!("Intensity: expected one azimuth per horizontal channel, got {:?}", settings.azimuths);
+                return None;
+            }
+        };
+
+        let mut channels = vec![settings.vertical.clone()];
+        channels.extend(settings.horizontals.iter().cloned());
+        let mut config = IntensityConfig { channels: channels.clone(), sample_rate: 100.0, azimuths, scale: IntensityScale::parse(&settings.scale), ..Default::default() };
+        for ch in &channels {
+            let velocity = match settings.input.to_lowercase().as_str() {
+                "velocity" | "vel" => true,
+                "acceleration" | "acc" => false,
+                _ => {
+                    let units = responses.get(ch).map_or(infer_input_units(ch), |r| r.input_units.as_str());
+                    units.to_uppercase().replace(' ', "") == "M/S"
+                }
+            };
+            if velocity {
+                let response = responses.get(ch).cloned().unwrap_or_else(|| {
+                    let s = sensitivities.get(ch).copied().unwrap_or(DEFAULT_VEL_SENSITIVITY);
+                    ChannelResponse::from_sensitivity(s, "M/S")
+                });
+                info!("Intensity: {} is a velocity channel, differentiated to acceleration", ch);
+                config.sensitivities.push(1.0 / response.sensitivity);
+                config.responses.push(Some(response));
+            } else {
+                let s = sensitivities.get(ch).copied().unwrap_or(DEFAULT_ACC_SENSITIVITY);
+                config.sensitivities.push(1.0 / s);
+                config.responses.push(None);
+            }
+        }
+        Some(config)
+    }
+
+    /// The configured channel that `id` (NET.STA.LOC.CHA or a bare code) belongs to.
+    pub fn channel_of(&self, id: &str) -> Option<&str> {
+        let code = id.rsplit('.').next().unwrap_or(id);
+        self.channels.iter().find(|c| code.ends_with(c.as_str())).map(|c| c.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntensityResult {
     pub timestamp: DateTime<Utc>,
+    /// Intensity on `scale`
     pub intensity: f64,
+    /// Class label on `scale`
     pub shindo_class: String,
+    pub scale: IntensityScale,
 }
 
 pub fn get_shindo_class(intensity: f64) -> String {
@@ -43,3 +134,45 @@ pub fn get_shindo_class(intensity: f64) -> String {
         "7".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::f64::consts::PI;
+
+    fn vertical_only(channel: &str) -> IntensitySettings {
+        IntensitySettings { vertical: channel.to_string(), horizontals: vec![], azimuths: vec![], ..Default::default() }
+    }
+
+    /// Intensity of 70 s of a 2 Hz sine on a single channel.
+    fn run(config: IntensityConfig, counts: impl Fn(f64) -> f64) -> f64 {
+        let channel = config.channels[0].clone();
+        let mut manager = IntensityManager::new(config);
+        let t0 = Utc.with_ymd_and_hms(2026, 1, 5, 0, 0, 0).unwrap();
+        let mut results = Vec::new();
+        for second in 0..70 {
+            let samples = (0..100).map(|i| counts((second * 100 + i) as f64 / 100.0)).collect();
+            manager.add_samples(HashMap::from([(channel.clone(), samples)]), t0 + chrono::Duration::seconds(second));
+            results.extend(manager.get_results());
+        }
+        results.iter().map(|r| r.intensity).fold(f64::MIN, f64::max)
+    }
+
+    #[test]
+    fn test_geophone_only_station_matches_accelerometer() {
+        let sensitivities = HashMap::from([("EHZ".to_string(), 4.0e8), ("ENZ".to_string(), 384500.0)]);
+        let geophone = IntensityConfig::from_settings(&vertical_only("EHZ"), &sensitivities, &HashMap::new()).unwrap();
+        assert!(geophone.responses[0].is_some());
+        assert_eq!(geophone.channel_of("AM.R0001.00.EHZ"), Some("EHZ"));
+        let accelerometer = IntensityConfig::from_settings(&vertical_only("ENZ"), &sensitivities, &HashMap::new()).unwrap();
+        assert!(accelerometer.responses[0].is_none());
+
+        // 1 cm/s at 2 Hz is 12.6 gal
+        let w = 2.0 * PI * 2.0;
+        let from_velocity = run(geophone, |t| 0.01 * (w * t).sin() * 4.0e8);
+        let from_acceleration = run(accelerometer, |t| 0.01 * w * (w * t).cos() * 384500.0);
+        assert!(from_acceleration > 2.0, "{}", from_acceleration);
+        assert!((from_velocity - from_acceleration).abs() <= 0.2, "{} vs {}", from_velocity, from_acceleration);
+    }
+}
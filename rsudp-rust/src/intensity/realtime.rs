@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::filter::BiquadChain;
+use crate::intensity::IntensityConfig;
+use crate::settings::RealtimeIntensitySettings;
+use crate::trigger::{AlertEvent, AlertEventType, Biquad};
+
+/// Filter settling time before values are reported (s).
+const WARMUP_SECONDS: f64 = 10.0;
+/// Largest unmatched backlog of one component, e.g. when another one stops (s).
+const MAX_BACKLOG_SECONDS: f64 = 10.0;
+
+/// One update of the real-time intensity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeIntensitySample {
+    pub timestamp: DateTime<Utc>,
+    /// Real-time JMA intensity Ir
+    pub intensity: f64,
+    /// Filtered vector acceleration at `timestamp` (gal)
+    pub acceleration: f64,
+}
+
+/// Bilinear transform of the analog section `(n0 s² + n1 s + n2) / (d0 s² + d1 s + d2)`.
+fn bilinear(n: [f64; 3], d: [f64; 3], fs: f64) -> Biquad {
+    let k = 2.0 * fs;
+    let z = |p: [f64; 3]| [p[0] * k * k + p[1] * k + p[2], 2.0 * (p[2] - p[0] * k * k), p[0] * k * k - p[1] * k + p[2]];
+    let (b, a) = (z(n), z(d));
+    Biquad::new(b[0] / a[0], b[1] / a[0], b[2] / a[0], a[1] / a[0], a[2] / a[0])
+}
+
+/// Second-order low-passes (Hz, damping) whose product is the JMA high-cut
+/// `(1 + 0.694y² + 0.241y⁴ + … + 0.000155y¹²)^-½`, y = f / 10 Hz: the spectral
+/// factors of that polynomial, within 0.05 % of it.
+const HIGH_CUT: [(f64, f64); 3] = [(19.00, 0.985), (20.19, 0.869), (23.36, 0.628)];
+
+// Approximation of the JMA period effect and low-cut, `√(1/f) · √(1 − exp(−(f/0.5)³))`,
+// which have no rational form: the gain and corners (Hz) below are a least-squares
+// fit of the log amplitude over 0.1–30 Hz (weighted to 0.2–20 Hz, corners kept at
+// or above 0.1 Hz so the filter settles within the warm-up), within 1.5 % of it
+// over 0.2–30 Hz.
+const PERIOD_GAIN: f64 = 0.1457;
+/// First-order high-pass corner
+const PERIOD_HIGH_PASS: f64 = 2.19;
+/// Shelf: natural frequency, zero damping, pole damping
+const PERIOD_SHELF: (f64, f64, f64) = (0.573, 3.29, 0.768);
+/// Zero/pole pairs
+const PERIOD_ZERO_POLE: [(f64, f64); 2] = [(1.30, 0.100), (25.7, 8.63)];
+
+/// Recursive approximation of the JMA frequency weighting (JMA 1996), input and
+/// output in gal: the exact high-cut and the fitted period effect and low-cut,
+/// mapped with the bilinear transform. Within 0.03 intensity of the JMA weighting
+/// over 0.2–10 Hz at 100 Hz and above, and over 0.2–7 Hz (0.05) at 50 Hz.
+pub fn jma_recursive_filter(sample_rate: f64) -> BiquadChain {
+    let w = |f: f64| 2.0 * PI * f;
+    let (shelf, zero_damping, pole_damping) = PERIOD_SHELF;
+    let [(z1, p1), (z2, p2)] = PERIOD_ZERO_POLE;
+    let (wc, ws) = (w(PERIOD_HIGH_PASS), w(shelf));
+    let mut sections = vec![
+        bilinear([PERIOD_GAIN, PERIOD_GAIN * w(z1), 0.0], [1.0, wc + w(p1), wc * w(p1)], sample_rate),
+        bilinear([1.0, 2.0 * zero_damping * ws, ws * ws], [1.0, 2.0 * pole_damping * ws, ws * ws], sample_rate),
+        bilinear([0.0, 1.0, w(z2)], [0.0, 1.0, w(p2)], sample_rate),
+    ];
+    for (f, h) in HIGH_CUT {
+        let wh = w(f);
+        sections.push(bilinear([0.0, 0.0, wh * wh], [1.0, 2.0 * h * wh, wh * wh], sample_rate));
+    }
+    BiquadChain::new(sections)
+}
+
+struct Component {
+    filter: BiquadChain,
+    /// First sample, removed so the low-cuts start without a step
+    offset: Option<f64>,
+    next_time: Option<DateTime<Utc>>,
+    /// Filtered acceleration (gal) awaiting the other components
+    queue: VecDeque<(DateTime<Utc>, f64)>,
+}
+
+/// Continuous JMA intensity after Kunugi et al. (2013): each component is weighted
+/// by recursive filters as it arrives (`jma_recursive_filter`, not their
+/// coefficients), so the value follows the shaking without the delay of the
+/// windowed calculation. Ir is `2 log10(a) + 0.94` where `a` is
+/// the level the filtered vector exceeded for 0.3 s within the last `window` seconds.
+pub struct RealtimeIntensity {
+    settings: RealtimeIntensitySettings,
+    config: IntensityConfig,
+    sample_rate: f64,
+    components: Vec<Component>,
+    /// Filtered vector acceleration over the lookback window (gal)
+    window: VecDeque<f64>,
+    warmup: usize,
+    current: Option<f64>,
+    last_output: Option<DateTime<Utc>>,
+    samples: Vec<RealtimeIntensitySample>,
+    alerts: Vec<AlertEvent>,
+    triggered: bool,
+    max_intensity: f64,
+}
+
+impl RealtimeIntensity {
+    /// `None` when a channel is a velocity sensor: the recursive filter expects
+    /// acceleration and velocity channels are not restituted.
+    pub fn new(config: IntensityConfig, settings: RealtimeIntensitySettings) -> Option<Self> {
+        if let Some(channel) = config.channels.iter().zip(&config.responses).find_map(|(c, r)| r.is_some().then_some(c)) {
+            warn!("Real-time intensity: {} is a velocity channel; only accelerometers are supported", channel);
+            return None;
+        }
+        Some(Self {
+            settings,
+            config,
+            sample_rate: 0.0,
+            components: Vec::new(),
+            window: VecDeque::new(),
+            warmup: 0,
+            current: None,
+            last_output: None,
+            samples: Vec::new(),
+            alerts: Vec::new(),
+            triggered: false,
+            max_intensity: f64::MIN,
+        })
+    }
+
+    /// Trigger name of the alerts.
+    pub fn name(&self) -> &str {
+        &self.settings.name
+    }
+
+    /// Latest real-time intensity, once the filters have settled.
+    pub fn intensity(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.components = self
+            .config
+            .channels
+            .iter()
+            .map(|_| Component { filter: jma_recursive_filter(sample_rate), offset: None, next_time: None, queue: VecDeque::new() })
+            .collect();
+        self.window.clear();
+        self.warmup = (WARMUP_SECONDS * sample_rate) as usize;
+        self.current = None;
+    }
+
+    /// Feed one segment of a configured channel (`NET.STA.LOC.CHA` or a bare code).
+    pub fn add_segment(&mut self, id: &str, start: DateTime<Utc>, sample_rate: f64, samples: &[f64]) {
+        let Some(channel) = self.config.channel_of(id) else { return };
+        let Some(index) = self.config.channels.iter().position(|c| c == channel) else { return };
+        if sample_rate <= 0.0 {
+            return;
+        }
+        if sample_rate != self.sample_rate {
+            if self.sample_rate > 0.0 {
+                info!("Real-time intensity: {} Hz on {}, restarting the filters", sample_rate, id);
+            }
+            self.reset(sample_rate);
+        }
+        let dt = 1.0 / sample_rate;
+        if let Some(expected) = self.components[index].next_time {
+            if (start - expected).num_milliseconds().abs() as f64 > 1000.0 * dt.max(0.1) {
+                info!("Real-time intensity: gap on {} ({} -> {}), restarting the filters", id, expected, start);
+                self.reset(sample_rate);
+            }
+        }
+
+        let scale = self.config.sensitivities.get(index).copied().unwrap_or(1.0) * 100.0;
+        let component = &mut self.components[index];
+        for (i, &count) in samples.iter().enumerate() {
+            let value = count * scale;
+            let offset = *component.offset.get_or_insert(value);
+            let time = start + Duration::nanoseconds((i as f64 * dt * 1e9) as i64);
+            component.queue.push_back((time, component.filter.process(value - offset)));
+        }
+        component.next_time = Some(start + Duration::nanoseconds((samples.len() as f64 * dt * 1e9) as i64));
+        let backlog = (MAX_BACKLOG_SECONDS * sample_rate) as usize;
+        while component.queue.len() > backlog {
+            component.queue.pop_front();
+        }
+
+        while self.components.iter().all(|c| !c.queue.is_empty()) {
+            let mut time = None;
+            let mut sum = 0.0;
+            for c in &mut self.components {
+                let (t, a) = c.queue.pop_front().unwrap_or_default();
+                time.get_or_insert(t);
+                sum += a * a;
+            }
+            self.push(time.unwrap_or(start), sum.sqrt());
+        }
+    }
+
+    fn push(&mut self, time: DateTime<Utc>, acceleration: f64) {
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return;
+        }
+        self.window.push_back(acceleration);
+        let window_len = ((self.settings.window * self.sample_rate) as usize).max(1);
+        while self.window.len() > window_len {
+            self.window.pop_front();
+        }
+
+        let mut sorted: Vec<f64> = self.window.iter().copied().collect();
+        let idx = ((0.3 * self.sample_rate).round() as usize).min(sorted.len() - 1);
+        let (_, &mut a, _) = sorted.select_nth_unstable_by(idx, |x, y| y.total_cmp(x));
+        let intensity = if a > 1e-15 { (2.0 * a.log10() + 0.94).max(-2.0) } else { -2.0 };
+        self.current = Some(intensity);
+
+        let interval = Duration::nanoseconds((1e9 / self.settings.rate.max(0.1)) as i64);
+        if self.last_output.is_none_or(|t| time - t >= interval) {
+            self.last_output = Some(time);
+            self.samples.push(RealtimeIntensitySample { timestamp: time, intensity, acceleration });
+        }
+
+        if !self.settings.trigger {
+            return;
+        }
+        if !self.triggered && intensity >= self.settings.threshold {
+            self.triggered = true;
+            self.max_intensity = intensity;
+            self.alerts.push(self.alert(time, AlertEventType::Trigger, intensity, "REALTIME INTENSITY"));
+        } else if self.triggered {
+            self.max_intensity = self.max_intensity.max(intensity);
+            if intensity < self.settings.reset {
+                self.triggered = false;
+                self.alerts.push(self.alert(time, AlertEventType::Reset, intensity, "REALTIME INTENSITY RESET"));
+            }
+        }
+    }
+
+    fn alert(&self, timestamp: DateTime<Utc>, event_type: AlertEventType, intensity: f64, message: &str) -> AlertEvent {
+        AlertEvent {
+            timestamp,
+            channel: self.config.channels[0].clone(),
+            trigger_name: self.settings.name.clone(),
+            event_type,
+            ratio: intensity,
+            max_ratio: self.max_intensity,
+            message: message.to_string(),
+            pick: None,
+            early_warning: None,
+        }
+    }
+
+    /// Updates since the last call, at most `rate` per second.
+    pub fn drain_samples(&mut self) -> Vec<RealtimeIntensitySample> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// TRIGGER/RESET events since the last call.
+    pub fn drain_alerts(&mut self) -> Vec<AlertEvent> {
+        std::mem::take(&mut self.alerts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::stationxml::ChannelResponse;
+    use chrono::TimeZone;
+
+    fn config(channels: &[&str]) -> IntensityConfig {
+        IntensityConfig {
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            sample_rate: 100.0,
+            sensitivities: vec![1.0 / 384500.0; channels.len()],
+            responses: vec![None; channels.len()],
+            ..Default::default()
+        }
+    }
+
+    /// JMA frequency weighting as published (JMA 1996).
+    fn jma_weighting(f: f64) -> f64 {
+        let y = f / 10.0;
+        let high_cut = 1.0 + 0.694 * y.powi(2) + 0.241 * y.powi(4) + 0.0557 * y.powi(6) + 0.009664 * y.powi(8) + 0.00134 * y.powi(10) + 0.000155 * y.powi(12);
+        (1.0 / f).sqrt() / high_cut.sqrt() * (1.0 - (-(f / 0.5).powi(3)).exp()).sqrt()
+    }
+
+    #[test]
+    fn test_recursive_filter_follows_jma_weighting() {
+        // Steady-state gain of a tone through the recursive filter, as intensity,
+        // against the published weighting
+        for fs in [50.0, 100.0, 200.0, 250.0] {
+            for f in [0.2, 0.5, 0.7, 1.0, 2.0, 3.5, 5.0, 7.0, 10.0] {
+                if f > 0.14 * fs {
+                    continue;
+                }
+                let mut chain = jma_recursive_filter(fs);
+                let n = (40.0 * fs) as usize;
+                let phase = |i: usize| 2.0 * PI * f * i as f64 / fs;
+                let x: Vec<f64> = (0..n).map(|i| phase(i).sin()).collect();
+                let y = chain.process_vec(&x);
+                // Amplitude over the last 20 s (whole periods), free of the sampling grid
+                let (sin, cos) = (n / 2..n).fold((0.0, 0.0), |(s, c), i| (s + y[i] * phase(i).sin(), c + y[i] * phase(i).cos()));
+                let amplitude = 2.0 * sin.hypot(cos) / (n - n / 2) as f64;
+                let error = 2.0 * (amplitude / jma_weighting(f)).log10();
+                assert!(error.abs() < 0.05, "{} Hz at {} Hz: {:+.3}", f, fs, error);
+            }
+        }
+    }
+
+    #[test]
+    fn test_velocity_channels_are_rejected() {
+        let mut velocity = config(&["EHZ"]);
+        velocity.responses = vec![Some(ChannelResponse::from_sensitivity(3.99e8, "M/S"))];
+        assert!(RealtimeIntensity::new(velocity, RealtimeIntensitySettings::default()).is_none());
+        assert!(RealtimeIntensity::new(config(&["ENZ"]), RealtimeIntensitySettings::default()).is_some());
+    }
+
+    #[test]
+    fn test_realtime_intensity_triggers_and_resets() {
+        let settings = RealtimeIntensitySettings { window: 5.0, ..Default::default() };
+        let mut rt = RealtimeIntensity::new(config(&["ENZ", "ENN", "ENE"]), settings).unwrap();
+        let t0 = Utc.with_ymd_and_hms(2026, 1, 5, 0, 0, 0).unwrap();
+        let mut samples = Vec::new();
+        let mut alerts = Vec::new();
+        // 20 s quiet, 10 s of 20 gal at 2 Hz on N, 20 s quiet
+        for second in 0..50 {
+            let start = t0 + Duration::seconds(second);
+            let shaking = (20..30).contains(&second);
+            let north: Vec<f64> = (0..100)
+                .map(|i| if shaking { 20.0 / 100.0 * 384500.0 * (2.0 * PI * 2.0 * i as f64 / 100.0).sin() } else { 0.0 })
+                .collect();
+            rt.add_segment("AM.R0001.00.ENZ", start, 100.0, &[0.0; 100]);
+            rt.add_segment("AM.R0001.00.ENN", start, 100.0, &north);
+            rt.add_segment("AM.R0001.00.ENE", start, 100.0, &[0.0; 100]);
+            samples.extend(rt.drain_samples());
+            alerts.extend(rt.drain_alerts());
+        }
+        // Filters settle first, then ten updates per second
+        assert!(samples.len() >= 390 && samples.len() <= 401, "{}", samples.len());
+        let peak = samples.iter().map(|s| s.intensity).fold(f64::MIN, f64::max);
+        assert!((3.0..3.6).contains(&peak), "{}", peak);
+
+        assert_eq!(alerts.len(), 2, "{:?}", alerts);
+        assert_eq!(alerts[0].event_type, AlertEventType::Trigger);
+        assert_eq!(alerts[0].trigger_name, "realtime_intensity");
+        // No window delay: the alert follows the onset within a second
+        assert!(alerts[0].timestamp < t0 + Duration::seconds(21), "{}", alerts[0].timestamp);
+        assert_eq!(alerts[1].event_type, AlertEventType::Reset);
+        assert!((alerts[1].max_ratio - peak).abs() < 1e-9);
+        assert!(alerts[1].timestamp < t0 + Duration::seconds(36));
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ground_motion::intensity_from_pgv;
+use crate::intensity::get_shindo_class;
+
+/// Ground motion a scale value is derived from; scales use whichever measures are present.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GroundMotionPeaks {
+    /// JMA instrumental intensity from the JMA filter
+    pub jma: Option<f64>,
+    /// Peak ground acceleration (gal), measured as the scale's `PeakMeasure`
+    pub pga: Option<f64>,
+    /// Peak ground velocity (cm/s), measured as the scale's `PeakMeasure`
+    pub pgv: Option<f64>,
+}
+
+/// How the components are combined into the PGA/PGV a scale is defined on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakMeasure {
+    /// Larger of the two horizontal peaks
+    LargerHorizontal,
+    /// Peak of the horizontal vector sum
+    HorizontalVector,
+    /// Peak of the vector sum of all components
+    Vector,
+}
+
+/// Seismic intensity scale used for reported intensities, alert messages, sounds and colors
+/// (`scale = "..."` in `[intensity]`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntensityScale {
+    /// JMA instrumental intensity (shindo 0–7)
+    #[default]
+    Jma,
+    /// Modified Mercalli Intensity from PGA/PGV (Worden et al. 2012)
+    Mmi,
+    /// Taiwan Central Weather Administration scale (2020): PGA up to level 4, PGV above
+    Cwa,
+    /// China Seismic Intensity Scale (GB/T 17742-2020)
+    Csis,
+}
+
+const ROMAN: [&str; 12] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII"];
+const SHINDO: [&str; 10] = ["0", "1", "2", "3", "4", "5-", "5+", "6-", "6+", "7"];
+
+/// Worden et al. (2012) MMI from PGA (gal).
+fn mmi_from_pga(pga: f64) -> f64 {
+    let l = pga.max(1e-6).log10();
+    if l <= 1.57 { 1.78 + 1.55 * l } else { -1.60 + 3.70 * l }
+}
+
+/// Worden et al. (2012) MMI from PGV (cm/s).
+fn mmi_from_pgv(pgv: f64) -> f64 {
+    let l = pgv.max(1e-6).log10();
+    if l <= 0.53 { 3.78 + 1.47 * l } else { 2.89 + 3.16 * l }
+}
+
+/// CWA 2020 level, encoded on the JMA numeric range so 5-…7 fall in the same bins.
+fn cwa_level(pga: Option<f64>, pgv: Option<f64>) -> f64 {
+    const PGA_LEVELS: [f64; 4] = [0.8, 2.5, 8.0, 25.0];
+    const PGV_LEVELS: [(f64, f64); 5] = [(140.0, 6.5), (80.0, 6.0), (50.0, 5.5), (30.0, 5.0), (15.0, 4.5)];
+    let from_pgv = |pgv: f64| PGV_LEVELS.iter().find(|&&(v, _)| pgv >= v).map(|&(_, level)| level);
+    match (pga, pgv) {
+        (Some(pga), _) if pga < 80.0 => PGA_LEVELS.iter().filter(|&&a| pga >= a).count() as f64,
+        (Some(_), Some(pgv)) => from_pgv(pgv).unwrap_or(4.0),
+        (Some(_), None) => 4.0,
+        // Without PGA, the lower levels follow the JMA PGV relation
+        (None, Some(pgv)) => from_pgv(pgv).unwrap_or_else(|| intensity_from_pgv(pgv / 100.0).round().clamp(0.0, 4.0)),
+        (None, None) => 0.0,
+    }
+}
+
+/// GB/T 17742-2020 instrumental intensity from PGA (gal) and PGV (cm/s).
+fn csis_intensity(pga: Option<f64>, pgv: Option<f64>) -> f64 {
+    let from_pga = pga.map(|a| 3.17 * (a / 100.0).max(1e-9).log10() + 6.59);
+    let from_pgv = pgv.map(|v| 3.00 * (v / 100.0).max(1e-9).log10() + 9.77);
+    let value = match (from_pga, from_pgv) {
+        (Some(ia), Some(iv)) if ia >= 6.0 && iv >= 6.0 => iv,
+        (Some(ia), Some(iv)) => (ia + iv) / 2.0,
+        (Some(i), None) | (None, Some(i)) => i,
+        (None, None) => 1.0,
+    };
+    value.clamp(1.0, 12.0)
+}
+
+/// Roman-numeral class of a Mercalli-type value, rounded and clamped to `I..=max`.
+fn roman(value: f64, max: usize) -> String {
+    let level = (value.round() as usize).clamp(1, max);
+    ROMAN[level - 1].to_string()
+}
+
+impl IntensityScale {
+    /// Parse a settings string; unknown names fall back to JMA.
+    pub fn parse(name: &str) -> Self {
+        match name.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "mmi" | "mercalli" | "worden2012" => IntensityScale::Mmi,
+            "cwa" | "cwa2020" | "taiwan" => IntensityScale::Cwa,
+            "csis" | "china" | "gb17742" => IntensityScale::Csis,
+            _ => IntensityScale::Jma,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IntensityScale::Jma => "JMA",
+            IntensityScale::Mmi => "MMI",
+            IntensityScale::Cwa => "CWA",
+            IntensityScale::Csis => "CSIS",
+        }
+    }
+
+    /// Combination of components the scale's PGA/PGV relations were derived for:
+    /// Worden et al. (2012) use the larger horizontal, GB/T 17742-2020 the horizontal vector.
+    pub fn peak_measure(self) -> PeakMeasure {
+        match self {
+            IntensityScale::Mmi => PeakMeasure::LargerHorizontal,
+            IntensityScale::Csis => PeakMeasure::HorizontalVector,
+            IntensityScale::Jma | IntensityScale::Cwa => PeakMeasure::Vector,
+        }
+    }
+
+    /// Numeric intensity on this scale.
+    pub fn intensity(self, peaks: &GroundMotionPeaks) -> f64 {
+        match self {
+            IntensityScale::Jma => peaks.jma.or(peaks.pgv.map(|v| intensity_from_pgv(v / 100.0))).unwrap_or(-2.0),
+            // Wald et al. (1999) weighting: PGA below MMI V, PGV above VII, blended in between
+            IntensityScale::Mmi => {
+                let value = match (peaks.pga.map(mmi_from_pga), peaks.pgv.map(mmi_from_pgv)) {
+                    (Some(a), Some(v)) => {
+                        let w = ((a - 5.0) / 2.0).clamp(0.0, 1.0);
+                        (1.0 - w) * a + w * v
+                    }
+                    (Some(i), None) | (None, Some(i)) => i,
+                    (None, None) => 1.0,
+                };
+                value.clamp(1.0, 10.0)
+            }
+            IntensityScale::Cwa => cwa_level(peaks.pga, peaks.pgv),
+            IntensityScale::Csis => csis_intensity(peaks.pga, peaks.pgv),
+        }
+    }
+
+    /// Class label of a numeric intensity, as used for `intensity_files` keys.
+    pub fn class(self, intensity: f64) -> String {
+        match self {
+            IntensityScale::Jma | IntensityScale::Cwa => get_shindo_class(intensity),
+            IntensityScale::Mmi => roman(intensity, 10),
+            IntensityScale::Csis => roman(intensity, 12),
+        }
+    }
+
+    /// Every class label, lowest first.
+    pub fn classes(self) -> &'static [&'static str] {
+        match self {
+            IntensityScale::Jma | IntensityScale::Cwa => &SHINDO,
+            IntensityScale::Mmi => &ROMAN[..10],
+            IntensityScale::Csis => &ROMAN,
+        }
+    }
+
+    /// Display color of a numeric intensity (RGB).
+    pub fn color(self, intensity: f64) -> (u8, u8, u8) {
+        match self {
+            // JMA palette; CWA levels share the JMA bins
+            IntensityScale::Jma | IntensityScale::Cwa => {
+                if intensity >= 6.5 { (180, 0, 104) }
+                else if intensity >= 6.0 { (165, 0, 33) }
+                else if intensity >= 5.5 { (255, 40, 0) }
+                else if intensity >= 5.0 { (255, 153, 0) }
+                else if intensity >= 4.5 { (255, 230, 0) }
+                else if intensity >= 3.5 { (250, 230, 150) }
+                else if intensity >= 2.5 { (0, 65, 255) }
+                else if intensity >= 1.5 { (0, 170, 255) }
+                else { (242, 242, 255) }
+            }
+            // USGS ShakeMap palette, extended for CSIS XI–XII
+            IntensityScale::Mmi | IntensityScale::Csis => match intensity.round() as i64 {
+                i64::MIN..=1 => (255, 255, 255),
+                2 => (191, 204, 255),
+                3 => (160, 230, 255),
+                4 => (128, 255, 255),
+                5 => (122, 255, 147),
+                6 => (255, 255, 0),
+                7 => (255, 200, 0),
+                8 => (255, 145, 0),
+                9 => (255, 0, 0),
+                10 => (200, 0, 0),
+                _ => (128, 0, 0),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peaks(pga: f64, pgv: f64) -> GroundMotionPeaks {
+        GroundMotionPeaks { jma: None, pga: Some(pga), pgv: Some(pgv) }
+    }
+
+    #[test]
+    fn test_scale_values_and_classes() {
+        // Weak motion: PGA-based MMI, log10(10 gal) = 1 → 1.78 + 1.55
+        let mmi = IntensityScale::Mmi.intensity(&peaks(10.0, 0.5));
+        assert!((mmi - 3.33).abs() < 1e-9);
+        assert_eq!(IntensityScale::Mmi.class(mmi), "III");
+        // Strong motion: PGV-based, log10(50 cm/s) → VIII
+        let mmi = IntensityScale::Mmi.intensity(&peaks(400.0, 50.0));
+        assert_eq!(IntensityScale::Mmi.class(mmi), "VIII");
+
+        assert_eq!(IntensityScale::Cwa.class(IntensityScale::Cwa.intensity(&peaks(10.0, 1.0))), "3");
+        assert_eq!(IntensityScale::Cwa.class(IntensityScale::Cwa.intensity(&peaks(120.0, 10.0))), "4");
+        assert_eq!(IntensityScale::Cwa.class(IntensityScale::Cwa.intensity(&peaks(250.0, 60.0))), "6-");
+
+        // 1 m/s² and 0.1 m/s: I_A = 6.59, I_V = 6.77, both ≥ 6 → I_V
+        let csis = IntensityScale::Csis.intensity(&peaks(100.0, 10.0));
+        assert!((csis - 6.77).abs() < 1e-9);
+        assert_eq!(IntensityScale::Csis.class(csis), "VII");
+
+        assert_eq!(IntensityScale::Jma.intensity(&GroundMotionPeaks { jma: Some(4.2), ..peaks(1.0, 1.0) }), 4.2);
+        assert_eq!(IntensityScale::parse("Worden2012"), IntensityScale::Mmi);
+        assert_eq!(IntensityScale::Jma.color(6.8), (180, 0, 104));
+        assert_eq!(IntensityScale::Csis.classes().len(), 12);
+    }
+}
@@ -1,13 +1,28 @@
+pub mod adaptive;
+pub mod catalog;
+pub mod coincidence;
+pub mod conditioning;
+pub mod detector;
+pub mod distance;
+pub mod eew;
+pub mod event;
 pub mod filter;
 pub mod forward;
+pub mod ground_motion;
 pub mod hue;
 pub mod intensity;
+pub mod magnitude;
+pub mod maintenance;
+pub mod notifier;
 pub mod pubsub;
+pub mod response_spectrum;
 pub mod rsam;
 pub mod sound;
 pub mod parser;
+pub mod picker;
 pub mod pipeline;
 pub mod receiver;
 pub mod settings;
 pub mod trigger;
+pub mod tuning;
 pub mod web;
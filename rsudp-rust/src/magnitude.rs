@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::MagnitudeSettings;
+use crate::trigger::Biquad;
+
+/// Wood-Anderson torsion seismometer as used by ObsPy (`paz_wa`): natural period
+/// 0.8 s, damping 0.8, static magnification 2080 (Uhrhammer & Collins 1990).
+const WA_PERIOD: f64 = 0.8;
+const WA_DAMPING: f64 = 0.8;
+const WA_GAIN: f64 = 2080.0;
+
+/// Hutton & Boore (1987) southern California attenuation, written as
+/// `ML = log10(A) + a·log10(R) + b·R + c` with A in mm and R hypocentral km.
+const HUTTON_BOORE: (f64, f64, f64) = (1.11, 0.00189, 3.0 - 2.0 * 1.11 - 100.0 * 0.00189);
+
+/// Local magnitude of one event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalMagnitude {
+    pub ml: f64,
+    /// Peak Wood-Anderson amplitude (mm), larger of the horizontals
+    pub amplitude_mm: f64,
+    /// Distance used for the correction (km)
+    pub distance_km: f64,
+    /// Distance correction name ("hutton_boore" or "regional")
+    pub correction: String,
+}
+
+impl std::fmt::Display for LocalMagnitude {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ML {:.1} ({:.3} mm at {:.0} km, {})", self.ml, self.amplitude_mm, self.distance_km, self.correction)
+    }
+}
+
+/// Simulate a Wood-Anderson seismometer on ground displacement (m); output in m
+/// of trace amplitude. Bilinear transform of `G·s² / (s² + 2hω₀s + ω₀²)`.
+pub fn wood_anderson(displacement: &[f64], sample_rate: f64) -> Vec<f64> {
+    let w0 = 2.0 * std::f64::consts::PI / WA_PERIOD;
+    let k = 2.0 * sample_rate;
+    let a0 = k * k + 2.0 * WA_DAMPING * w0 * k + w0 * w0;
+    let a1 = 2.0 * (w0 * w0 - k * k);
+    let a2 = k * k - 2.0 * WA_DAMPING * w0 * k + w0 * w0;
+    let b = WA_GAIN * k * k / a0;
+    let mut section = Biquad::new(b, -2.0 * b, b, a1 / a0, a2 / a0);
+    displacement.iter().map(|&x| section.process(x)).collect()
+}
+
+/// `ML = log10(A_mm) + a·log10(R) + b·R + c`, with the coefficients of the configured correction:
+/// Hutton & Boore's, or `a`, `b`, `c` for "regional" (the only other value settings validation accepts).
+pub fn local_magnitude(amplitude_mm: f64, distance_km: f64, settings: &MagnitudeSettings) -> LocalMagnitude {
+    let (a, b, c) = if settings.correction.eq_ignore_ascii_case("regional") {
+        (settings.a, settings.b, settings.c)
+    } else {
+        HUTTON_BOORE
+    };
+    let r = distance_km.max(1.0);
+    LocalMagnitude {
+        ml: amplitude_mm.log10() + a * r.log10() + b * r + c,
+        amplitude_mm,
+        distance_km,
+        correction: settings.correction.clone(),
+    }
+}
+
+/// Peak absolute Wood-Anderson amplitude (mm) of a displacement record (m).
+pub fn wood_anderson_peak_mm(displacement: &[f64], sample_rate: f64) -> f64 {
+    wood_anderson(displacement, sample_rate).iter().fold(0.0_f64, |m, &x| m.max(x.abs())) * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn sine(freq: f64, amp: f64, fs: f64, n: usize) -> Vec<f64> {
+        (0..n).map(|i| amp * (2.0 * PI * freq * i as f64 / fs).sin()).collect()
+    }
+
+    #[test]
+    fn test_wood_anderson_response() {
+        let fs = 100.0;
+        // Above the natural frequency the instrument magnifies displacement by 2080
+        let out = wood_anderson(&sine(10.0, 1e-6, fs, 2000), fs);
+        let peak = out[1000..].iter().fold(0.0_f64, |m, &x| m.max(x.abs()));
+        assert!((peak / 1e-6 / WA_GAIN - 1.0).abs() < 0.05, "peak={}", peak);
+
+        // At the natural period the gain is 2080 / (2h)
+        let out = wood_anderson(&sine(1.0 / WA_PERIOD, 1e-6, fs, 4000), fs);
+        let peak = out[2000..].iter().fold(0.0_f64, |m, &x| m.max(x.abs()));
+        let expected = 1e-6 * WA_GAIN / (2.0 * WA_DAMPING);
+        assert!((peak / expected - 1.0).abs() < 0.02, "peak={} expected={}", peak, expected);
+    }
+
+    #[test]
+    fn test_hutton_boore_reference() {
+        // Richter's definition: 1 mm at 100 km is ML 3
+        let settings = MagnitudeSettings::default();
+        let m = local_magnitude(1.0, 100.0, &settings);
+        assert!((m.ml - 3.0).abs() < 1e-9, "ml={}", m.ml);
+        // Ten times the amplitude is one magnitude unit more
+        assert!((local_magnitude(10.0, 100.0, &settings).ml - 4.0).abs() < 1e-9);
+
+        let regional = MagnitudeSettings { correction: "regional".to_string(), a: 1.0, b: 0.0, c: 1.0, ..Default::default() };
+        assert!((local_magnitude(1.0, 100.0, &regional).ml - 3.0).abs() < 1e-9);
+    }
+}
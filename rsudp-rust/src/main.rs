@@ -1,8 +1,8 @@
 use rsudp_rust::pipeline::run_pipeline;
 use rsudp_rust::sound::AudioManager;
 use rsudp_rust::hue::HueIntegration;
-use rsudp_rust::trigger::TriggerConfig;
-use rsudp_rust::intensity::IntensityConfig;
+use rsudp_rust::trigger::{TriggerConfig, TriggerRegistry};
+use rsudp_rust::intensity::{IntensityConfig, IntensityScale, RealtimeIntensity};
 use rsudp_rust::web::WebState;
 use rsudp_rust::receiver::start_receiver;
 use rsudp_rust::parser::stationxml::{fetch_sensitivity, fetch_response};
@@ -16,6 +16,14 @@ use std::path::PathBuf;
 use rsudp_rust::forward::ForwardManager;
 use rsudp_rust::pubsub;
 use rsudp_rust::rsam::RsamManager;
+use rsudp_rust::coincidence::CoincidenceManager;
+use rsudp_rust::conditioning::SignalConditioner;
+use rsudp_rust::distance::DistanceEstimator;
+use rsudp_rust::eew::EarlyWarningEstimator;
+use rsudp_rust::catalog::Catalog;
+use rsudp_rust::event::EventManager;
+use rsudp_rust::maintenance::Maintenance;
+use rsudp_rust::ground_motion::GroundMotionTracker;
 use rsudp_rust::web::sns::SNSManager;
 use std::sync::Arc;
 
@@ -42,7 +50,7 @@ struct Args {
     #[arg(short, long)]
     station: Option<String>,
 
-    /// Channels for intensity calculation (must be 3, overrides config)
+    /// Channels for intensity calculation: vertical only, or vertical and two horizontals (overrides config)
     #[arg(short, long)]
     channels: Option<String>,
 
@@ -80,7 +88,7 @@ async fn main() {
         // We do this here instead of in Default implementation to avoid
         // config crate parsing errors (Char error) when loading defaults
         let mut intensity_files = std::collections::BTreeMap::new();
-        for key in ["0", "1", "2", "3", "4", "5-", "5+", "6-", "6+", "7"] {
+        for key in IntensityScale::parse(&default_settings.intensity.scale).classes() {
             intensity_files.insert(key.to_string(), "".to_string());
         }
         default_settings.alertsound.intensity_files = intensity_files;
@@ -112,7 +120,8 @@ async fn main() {
     };
     
     // Initialize Hue Integration
-    let hue_integration = HueIntegration::new(settings.hue.clone());
+    let intensity_scale = IntensityScale::parse(&settings.intensity.scale);
+    let hue_integration = HueIntegration::new(settings.hue.clone()).with_scale(intensity_scale);
     hue_integration.start().await; // Starts discovery loop
 
     // Initialize Audio Manager (Optional, if device exists)
@@ -161,6 +170,27 @@ async fn main() {
         plot_settings.filter_corners = settings.plot.filter_corners as usize;
     }
     
+    *web_state.maintenance.write().unwrap() = Maintenance::from_settings(&settings.maintenance);
+    *web_state.events.lock().unwrap() = EventManager::new(settings.event.clone());
+    *web_state.catalog.lock().unwrap() = Catalog::new(settings.catalog.clone());
+    if settings.catalog.enabled {
+        // Pick up external catalog updates off the pipeline's path
+        let catalog = web_state.catalog.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                // Parse outside the lock; only the swap holds it
+                let current = catalog.lock().unwrap().clone();
+                if let Ok(Some(reloaded)) = tokio::task::spawn_blocking(move || current.reloaded()).await {
+                    *catalog.lock().unwrap() = reloaded;
+                }
+            }
+        });
+    }
+    *web_state.intensity_scale.write().unwrap() = intensity_scale;
+
     // Update default history settings as well
     {
         let mut history = web_state.history.lock().unwrap();
@@ -236,37 +266,54 @@ async fn main() {
     }
     {
         let mut rm = web_state.response_map.write().unwrap();
-        *rm = resp_map;
+        *rm = resp_map.clone();
     }
 
     // 2. Setup Configs
-    let trigger_config = TriggerConfig {
-        sta_sec: settings.alert.sta,
-        lta_sec: settings.alert.lta,
-        threshold: settings.alert.threshold,
-        reset_threshold: settings.alert.reset,
-        highpass: settings.alert.highpass,
-        lowpass: settings.alert.lowpass,
-        target_channel: settings.alert.channel.clone(),
-        duration: settings.alert.duration,
-    };
-
-    let channels_str = args.channels.unwrap_or_else(|| "ENE,ENN,ENZ".to_string());
-    let target_channels: Vec<String> = channels_str.split(',').map(|s| s.to_string()).collect();
-    let intensity_config = if target_channels.len() == 3 {
-        let mut sensitivities = Vec::new();
-        for ch in &target_channels {
-            let s = sens_map.get(ch).cloned().unwrap_or(384500.0);
-            sensitivities.push(1.0 / s);
+    let trigger_configs: Vec<TriggerConfig> = match settings.alert.trigger_definitions().iter()
+        .map(|t| t.to_config().map_err(|e| format!("trigger '{}': {}", t.name, e)))
+        .collect() {
+        Ok(configs) => configs,
+        Err(e) => {
+            eprintln!("Error loading configuration: {}", e);
+            std::process::exit(1);
         }
-        Some(IntensityConfig {
-            channels: target_channels,
-            sample_rate: 100.0,
-            sensitivities,
-        })
-    } else {
-        None
     };
+    *web_state.triggers.write().unwrap() = TriggerRegistry { revision: 0, configs: trigger_configs.clone() };
+    let coincidence = settings.coincidence.enabled.then(|| CoincidenceManager::new(settings.coincidence.clone()));
+    let distance = (settings.distance.enabled || settings.magnitude.enabled).then(|| {
+        let estimator = DistanceEstimator::new(settings.distance.clone());
+        if settings.magnitude.enabled {
+            estimator.with_magnitude(settings.magnitude.clone(), resp_map.clone(), sens_map.clone())
+        } else {
+            estimator
+        }
+    });
+
+    let adaptive = settings.adaptive.enabled.then(|| settings.adaptive.clone());
+    let conditioner = settings.conditioning.enabled.then(|| SignalConditioner::new(settings.conditioning.clone()));
+    let early_warning = settings.eew.enabled
+        .then(|| EarlyWarningEstimator::new(settings.eew.clone(), resp_map.clone(), sens_map.clone()));
+    let ground_motion = settings.ground_motion.enabled
+        .then(|| GroundMotionTracker::new(settings.ground_motion.clone(), resp_map.clone(), sens_map.clone()));
+
+    let mut intensity_settings = settings.intensity.clone();
+    if let Some(channels_str) = args.channels {
+        // Vertical is the channel ending in Z; horizontals ordered N/1 before E/2
+        let mut channels: Vec<String> = channels_str.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        let vertical = channels.iter().position(|c| c.ends_with('Z')).unwrap_or(0);
+        intensity_settings.vertical = if channels.is_empty() { String::new() } else { channels.remove(vertical) };
+        channels.sort_by_key(|c| c.ends_with('E') || c.ends_with('2'));
+        intensity_settings.horizontals = channels;
+    }
+    let intensity_config = intensity_settings
+        .enabled
+        .then(|| IntensityConfig::from_settings(&intensity_settings, &sens_map, &resp_map))
+        .flatten();
+    let realtime = settings.realtime_intensity.enabled
+        .then(|| IntensityConfig::from_settings(&intensity_settings, &sens_map, &resp_map))
+        .flatten()
+        .and_then(|config| RealtimeIntensity::new(config, settings.realtime_intensity.clone()));
 
     // 4. Initialize SNS Manager
     let sns_manager = Arc::new(SNSManager::from_settings(&settings).await);
@@ -286,7 +333,7 @@ async fn main() {
 
     // 6. Initialize RSAM Manager
     let rsam_manager = if settings.rsam.enabled {
-        match RsamManager::new(&settings.rsam, sens_map.clone()) {
+        match RsamManager::new(&settings.rsam, sens_map.clone()).map(|m| m.with_responses(resp_map.clone())) {
             Ok(rm) => Some(rm),
             Err(e) => {
                 tracing::error!("RSAM configuration error: {}", e);
@@ -329,7 +376,7 @@ async fn main() {
         let fwd_sim = forward_manager.clone();
         let rsam_sim = rsam_manager;
         let pipeline_handle = tokio::spawn(async move {
-            run_pipeline(pipe_rx, trigger_config, intensity_config, ws, sm, sns_sim, hue_sim, audio_sim, sound_sim, fwd_sim, rsam_sim, None, settings.capture.clone()).await;
+            run_pipeline(pipe_rx, trigger_configs, adaptive, intensity_config, ws, sm, sns_sim, hue_sim, audio_sim, sound_sim, fwd_sim, rsam_sim, coincidence, conditioner, distance, ground_motion, early_warning, realtime, None, settings.capture.clone()).await;
         });
 
         let bytes = std::fs::read(&path).unwrap();
@@ -376,7 +423,7 @@ async fn main() {
         };
 
         tokio::spawn(async move {
-            run_pipeline(pipe_rx, trigger_config, intensity_config, ws, sm, sns_live, hue_live, audio_live, sound_live, fwd_live, rsam_live, publisher_tx, settings.capture.clone()).await;
+            run_pipeline(pipe_rx, trigger_configs, adaptive, intensity_config, ws, sm, sns_live, hue_live, audio_live, sound_live, fwd_live, rsam_live, coincidence, conditioner, distance, ground_motion, early_warning, realtime, publisher_tx, settings.capture.clone()).await;
         });
 
         if settings.pubsub.enabled && settings.pubsub.input_mode == "pubsub" {
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::settings::{MaintenanceSettings, MaintenanceWindowSettings};
+
+/// One field of a cron expression: the allowed values in its range.
+#[derive(Debug, Clone, PartialEq)]
+struct CronField(Vec<bool>);
+
+impl CronField {
+    /// Parse `*`, `5`, `1-5`, `*/15`, `8-18/2` and comma-separated lists thereof.
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut allowed = vec![false; (max + 1) as usize];
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>().map_err(|_| format!("invalid step in '{}'", part))?),
+                None => (part, 1),
+            };
+            let (lo, hi) = if range == "*" {
+                (min, max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                let a = a.parse::<u32>().map_err(|_| format!("invalid range '{}'", range))?;
+                let b = b.parse::<u32>().map_err(|_| format!("invalid range '{}'", range))?;
+                (a, b)
+            } else {
+                let v = range.parse::<u32>().map_err(|_| format!("invalid value '{}'", range))?;
+                (v, v)
+            };
+            if lo < min || hi > max || lo > hi || step == 0 {
+                return Err(format!("'{}' outside {}-{}", part, min, max));
+            }
+            for v in (lo..=hi).step_by(step as usize) {
+                allowed[v as usize] = true;
+            }
+        }
+        Ok(Self(allowed))
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.0.get(value as usize).copied().unwrap_or(false)
+    }
+}
+
+/// A five-field cron expression (minute hour day-of-month month day-of-week, Sunday = 0).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minute: CronField,
+    hour: CronField,
+    day: CronField,
+    month: CronField,
+    weekday: CronField,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields in '{}'", expr));
+        };
+        // Day of week 7 is also Sunday
+        let mut weekday = CronField::parse(weekday, 0, 7)?;
+        if weekday.0[7] {
+            weekday.0[0] = true;
+        }
+        Ok(Self {
+            minute: CronField::parse(minute, 0, 59)?,
+            hour: CronField::parse(hour, 0, 23)?,
+            day: CronField::parse(day, 1, 31)?,
+            month: CronField::parse(month, 1, 12)?,
+            weekday,
+        })
+    }
+
+    /// True when the schedule fires in the minute containing `local` (local wall time).
+    pub fn matches(&self, local: DateTime<Utc>) -> bool {
+        self.minute.matches(local.minute())
+            && self.hour.matches(local.hour())
+            && self.day.matches(local.day())
+            && self.month.matches(local.month())
+            && self.weekday.matches(local.weekday().num_days_from_sunday())
+    }
+
+    /// The latest minute at or before `local` in which the schedule fires, looking back
+    /// no further than `earliest`. Walks back over the matching days, hours and minutes
+    /// rather than testing every minute.
+    pub fn last_fire(&self, local: DateTime<Utc>, earliest: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut date = local.date_naive();
+        while date >= earliest.date_naive() {
+            let today = date == local.date_naive();
+            let day_matches = self.day.matches(date.day())
+                && self.month.matches(date.month())
+                && self.weekday.matches(date.weekday().num_days_from_sunday());
+            if day_matches {
+                let last_hour = if today { local.hour() } else { 23 };
+                for hour in (0..=last_hour).rev().filter(|&h| self.hour.matches(h)) {
+                    let last_minute = if today && hour == local.hour() { local.minute() } else { 59 };
+                    if let Some(minute) = (0..=last_minute).rev().find(|&m| self.minute.matches(m)) {
+                        let fire = date.and_hms_opt(hour, minute, 0)?.and_utc();
+                        return (fire >= earliest).then_some(fire);
+                    }
+                }
+            }
+            date = date.pred_opt()?;
+        }
+        None
+    }
+}
+
+/// Longest maintenance window (minutes): one week.
+const MAX_WINDOW_MINUTES: f64 = 7.0 * 24.0 * 60.0;
+
+/// A recurring maintenance window: `duration` minutes from every time `schedule` fires.
+#[derive(Debug, Clone)]
+pub struct MaintenanceWindow {
+    pub schedule: CronSchedule,
+    pub duration: Duration,
+    pub reason: String,
+}
+
+/// An ad-hoc mute set through the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManualMute {
+    pub until: DateTime<Utc>,
+    pub reason: String,
+}
+
+/// Current mute state, as reported by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MuteStatus {
+    pub muted: bool,
+    pub reason: Option<String>,
+    pub manual: Option<ManualMute>,
+}
+
+/// Scheduled maintenance windows plus an ad-hoc mute; triggers inside either are
+/// recorded but not announced.
+#[derive(Debug, Clone, Default)]
+pub struct Maintenance {
+    windows: Vec<MaintenanceWindow>,
+    /// Offset of local time from UTC (hours) for the schedules
+    utc_offset: f64,
+    manual: Option<ManualMute>,
+}
+
+impl Maintenance {
+    /// Build from the `[maintenance]` settings; invalid schedules are skipped with a warning.
+    pub fn from_settings(settings: &MaintenanceSettings) -> Self {
+        if !settings.enabled {
+            return Self::default();
+        }
+        let windows = settings
+            .windows
+            .iter()
+            .filter_map(|w: &MaintenanceWindowSettings| match CronSchedule::parse(&w.cron) {
+                Ok(schedule) => {
+                    let Some(duration) = Some(w.duration)
+                        .filter(|d| d.is_finite() && *d > 0.0 && *d <= MAX_WINDOW_MINUTES)
+                        .and_then(|d| Duration::try_seconds((d * 60.0) as i64))
+                    else {
+                        warn!("Ignoring maintenance window '{}': duration {} min is not in (0, {}]", w.cron, w.duration, MAX_WINDOW_MINUTES);
+                        return None;
+                    };
+                    info!("Maintenance window '{}' for {} min: {}", w.cron, w.duration, w.reason);
+                    Some(MaintenanceWindow { schedule, duration, reason: w.reason.clone() })
+                }
+                Err(e) => {
+                    warn!("Ignoring maintenance window '{}': {}", w.cron, e);
+                    None
+                }
+            })
+            .collect();
+        Self { windows, utc_offset: settings.utc_offset, manual: None }
+    }
+
+    /// Mute everything until `until`.
+    pub fn mute_until(&mut self, until: DateTime<Utc>, reason: String) {
+        info!("Alerts muted until {} ({})", until, reason);
+        self.manual = Some(ManualMute { until, reason });
+    }
+
+    pub fn unmute(&mut self) {
+        if self.manual.take().is_some() {
+            info!("Manual mute cleared");
+        }
+    }
+
+    /// Why alerts are muted at `time`, or None when they are not.
+    pub fn muted_at(&self, time: DateTime<Utc>) -> Option<String> {
+        if let Some(manual) = self.manual.as_ref().filter(|m| time < m.until) {
+            return Some(format!("muted until {}: {}", manual.until.format("%Y-%m-%d %H:%M UTC"), manual.reason));
+        }
+        let local = time + Duration::milliseconds((self.utc_offset * 3_600_000.0) as i64);
+        let local = local.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(local);
+        self.windows.iter().find_map(|w| {
+            // Started within the last `duration`, at minute resolution
+            let minutes = w.duration.num_minutes().max(0);
+            if minutes == 0 {
+                return None;
+            }
+            w.schedule
+                .last_fire(local, local - Duration::minutes(minutes - 1))
+                .map(|_| format!("maintenance window: {}", w.reason))
+        })
+    }
+
+    pub fn status(&self, time: DateTime<Utc>) -> MuteStatus {
+        let reason = self.muted_at(time);
+        MuteStatus {
+            muted: reason.is_some(),
+            reason,
+            manual: self.manual.clone().filter(|m| time < m.until),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cron_fields() {
+        let weekdays_8am = CronSchedule::parse("0 8 * * 1-5").unwrap();
+        // 2026-01-05 is a Monday
+        assert!(weekdays_8am.matches(at("2026-01-05T08:00:30Z")));
+        assert!(!weekdays_8am.matches(at("2026-01-04T08:00:00Z")));
+        assert!(!weekdays_8am.matches(at("2026-01-05T08:01:00Z")));
+
+        let quarter = CronSchedule::parse("*/15 9-17/2 1,15 * *").unwrap();
+        assert!(quarter.matches(at("2026-03-15T11:45:00Z")));
+        assert!(!quarter.matches(at("2026-03-15T12:45:00Z")));
+        // Latest firing at or before a time, within the look-back
+        assert_eq!(quarter.last_fire(at("2026-03-15T12:10:00Z"), at("2026-03-14T00:00:00Z")), Some(at("2026-03-15T11:45:00Z")));
+        assert_eq!(quarter.last_fire(at("2026-03-16T08:00:00Z"), at("2026-03-01T00:00:00Z")), Some(at("2026-03-15T17:45:00Z")));
+        assert_eq!(quarter.last_fire(at("2026-03-15T12:10:00Z"), at("2026-03-15T12:00:00Z")), None);
+        assert_eq!(weekdays_8am.last_fire(at("2026-01-05T08:00:00Z"), at("2026-01-05T08:00:00Z")), Some(at("2026-01-05T08:00:00Z")));
+        assert!(CronSchedule::parse("0 25 * * *").is_err());
+        assert!(CronSchedule::parse("0 8 * *").is_err());
+    }
+
+    #[test]
+    fn test_scheduled_and_manual_mute() {
+        let settings = MaintenanceSettings {
+            enabled: true,
+            utc_offset: 9.0,
+            windows: vec![MaintenanceWindowSettings {
+                cron: "0 8 * * 1-5".to_string(),
+                duration: 600.0,
+                reason: "construction".to_string(),
+            }],
+        };
+        let mut m = Maintenance::from_settings(&settings);
+        // 08:00-18:00 JST on a Monday is 23:00-09:00 UTC
+        assert!(m.muted_at(at("2026-01-05T02:30:00Z")).is_some());
+        assert!(m.muted_at(at("2026-01-05T09:30:00Z")).is_none());
+        // The window covers its first minute up to, not including, the end
+        assert!(m.muted_at(at("2026-01-04T23:00:00Z")).is_some());
+        assert!(m.muted_at(at("2026-01-05T08:59:59Z")).is_some());
+        assert!(m.muted_at(at("2026-01-05T09:00:00Z")).is_none());
+
+        let now = at("2026-01-05T12:00:00Z");
+        m.mute_until(now + Duration::hours(2), "station service".to_string());
+        assert!(m.status(now + Duration::minutes(30)).muted);
+        assert!(m.muted_at(now + Duration::hours(3)).is_none());
+        m.unmute();
+        assert!(!m.status(now).muted);
+
+        let endless = MaintenanceWindowSettings { duration: 1e20, ..settings.windows[0].clone() };
+        let m = Maintenance::from_settings(&MaintenanceSettings { windows: vec![endless], ..settings });
+        assert!(m.muted_at(at("2026-01-05T02:30:00Z")).is_none());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::event::{Event, EventState};
+use crate::forward::ForwardManager;
+use crate::hue::HueIntegration;
+use crate::intensity::IntensityScale;
+use crate::settings::{routes_trigger, AlertSoundSettings};
+use crate::sound::AudioController;
+use crate::trigger::AlertEventType;
+use crate::web::alerts::{format_intensity_message, send_reset_email, send_trigger_email};
+use crate::web::sns::{NotificationEvent, SNSManager};
+use crate::web::stream::WebState;
+
+/// Intensity reported for an event without an intensity or PGA measurement.
+const NO_INTENSITY: f64 = -2.0;
+
+/// True when a notifier's `triggers` list routes any of the event's triggers to it.
+fn routes_event(triggers: &[String], event: &Event) -> bool {
+    event.triggers.iter().any(|t| routes_trigger(triggers, t))
+}
+
+/// Sends every notification (email, SNS providers, forwarded alarms, Hue, audio)
+/// from event updates: once when an event is declared and once, with its
+/// aggregated metrics, when it closes. Muted events notify nothing.
+pub struct EventNotifier {
+    web_state: WebState,
+    sns: Option<Arc<SNSManager>>,
+    hue: Option<HueIntegration>,
+    audio: Option<AudioController>,
+    sound: AlertSoundSettings,
+    forward: Option<Arc<ForwardManager>>,
+    scale: IntensityScale,
+    /// Snapshot capture of each open event, attached to its closing notifications
+    snapshots: HashMap<Uuid, JoinHandle<Option<PathBuf>>>,
+}
+
+impl EventNotifier {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        web_state: WebState,
+        sns: Option<Arc<SNSManager>>,
+        hue: Option<HueIntegration>,
+        audio: Option<AudioController>,
+        sound: AlertSoundSettings,
+        forward: Option<Arc<ForwardManager>>,
+        scale: IntensityScale,
+    ) -> Self {
+        Self { web_state, sns, hue, audio, sound, forward, scale, snapshots: HashMap::new() }
+    }
+
+    /// Attach the snapshot being captured for an event to its closing email and SNS messages.
+    pub fn add_snapshot(&mut self, event_id: Uuid, capture: JoinHandle<Option<PathBuf>>) {
+        self.snapshots.insert(event_id, capture);
+    }
+
+    /// Notify an event update; `station_id` is the station (or network) that raised it.
+    pub fn dispatch(&mut self, event: &Event, station_id: &str) {
+        let snapshot = if event.state == EventState::Closed { self.snapshots.remove(&event.id) } else { None };
+        if event.muted {
+            return;
+        }
+        match event.state {
+            EventState::Declared => self.declared(event, station_id),
+            // Joins and partial resets only refine the aggregates the closing notification carries
+            EventState::Updated => {}
+            EventState::Closed => self.closed(event, station_id, snapshot),
+        }
+    }
+
+    /// React to an early-warning estimate (intensity on the configured scale) for an open event.
+    pub fn early(&self, event: &Event, estimated: f64) {
+        if event.muted {
+            return;
+        }
+        if let Some(hue) = self.hue.clone().filter(|h| event.triggers.iter().any(|t| h.routes_trigger(t))) {
+            tokio::spawn(async move {
+                hue.early_alert(estimated).await;
+            });
+        }
+        if let Some(audio) = self.audio.as_ref().filter(|_| routes_event(&self.sound.triggers, event)) {
+            let class = self.scale.class(estimated);
+            if let Some(file_path) = self.sound.intensity_files.get(&class).filter(|f| !f.is_empty()) {
+                audio.queue_file(file_path);
+            }
+        }
+    }
+
+    fn declared(&self, event: &Event, station_id: &str) {
+        let settings = self.web_state.history.lock().unwrap().get_settings();
+        if settings.email_enabled && routes_event(&settings.triggers, event) {
+            let (channels, start) = (event.channels.join(", "), event.start);
+            tokio::spawn(async move {
+                if let Err(e) = send_trigger_email(&settings, &channels, start) { warn!("Failed to send trigger email: {}", e); }
+            });
+        }
+        if let Some(sns) = self.sns.clone() {
+            let notification = self.notification(event, AlertEventType::Trigger, station_id);
+            tokio::spawn(async move { sns.notify_trigger(&notification).await; });
+        }
+        if let (Some(fwd), Some(channel)) = (&self.forward, event.channels.first()) {
+            fwd.forward_alarm(&format!("ALARM {} {}", channel, event.start.to_rfc3339()));
+        }
+        if let Some(hue) = self.hue.clone().filter(|h| event.triggers.iter().any(|t| h.routes_trigger(t))) {
+            tokio::spawn(async move {
+                hue.trigger_alert().await;
+            });
+        }
+        if let Some(audio) = self.audio.as_ref().filter(|_| routes_event(&self.sound.triggers, event)) {
+            audio.queue_file(&self.sound.trigger_file);
+        }
+    }
+
+    fn closed(&self, event: &Event, station_id: &str, snapshot: Option<JoinHandle<Option<PathBuf>>>) {
+        let max_intensity = event.max_intensity.unwrap_or(NO_INTENSITY);
+        let class = self.scale.class(max_intensity);
+        let end = event.end.unwrap_or_else(Utc::now);
+        if let (Some(fwd), Some(channel)) = (&self.forward, event.channels.first()) {
+            fwd.forward_alarm(&format!("RESET {} {}", channel, end.to_rfc3339()));
+        }
+        if let Some(hue) = self.hue.clone().filter(|h| event.triggers.iter().any(|t| h.routes_trigger(t))) {
+            tokio::spawn(async move {
+                hue.reset_alert(max_intensity).await;
+            });
+        }
+        if let Some(audio) = self.audio.as_ref().filter(|_| routes_event(&self.sound.triggers, event)) {
+            let file_path = self.sound.intensity_files.get(&class).unwrap_or(&self.sound.default_reset_file);
+            if file_path.is_empty() {
+                warn!("No audio file found for intensity {} and no default configured", class);
+            } else {
+                audio.queue_file(file_path);
+            }
+        }
+
+        // Email and SNS carry the snapshot, which may still be capturing
+        let settings = self.web_state.history.lock().unwrap().get_settings();
+        let email = settings.email_enabled && routes_event(&settings.triggers, event);
+        let sns = self.sns.clone();
+        if !email && sns.is_none() {
+            return;
+        }
+        let notification = self.notification(event, AlertEventType::Reset, station_id);
+        let message = format_intensity_message(self.scale, &class);
+        let (start, max_ratio) = (event.start, event.max_ratio);
+        tokio::spawn(async move {
+            let snapshot_path = match snapshot {
+                Some(capture) => capture.await.ok().flatten(),
+                None => None,
+            };
+            if email {
+                let url = snapshot_path.as_ref().and_then(|p| p.file_name()).map(|f| format!("http://localhost:8080/images/alerts/{}", f.to_string_lossy()));
+                if let Err(e) = send_reset_email(&settings, &notification.channel, start, end, max_ratio, url.as_deref(), &message) { warn!("Failed to send reset email: {}", e); }
+            }
+            if let Some(sns) = sns {
+                sns.notify_reset(&NotificationEvent { snapshot_path, ..notification }).await;
+            }
+        });
+    }
+
+    /// The event's aggregates, with the estimates recorded on its alerts.
+    fn notification(&self, event: &Event, event_type: AlertEventType, station_id: &str) -> NotificationEvent {
+        let history = self.web_state.history.lock().unwrap();
+        let recorded: Vec<_> = event.alerts.iter().filter_map(|&id| history.get_event(id)).collect();
+        let timestamp = if event_type == AlertEventType::Reset { event.end.unwrap_or(event.start) } else { event.start };
+        NotificationEvent {
+            event_type,
+            timestamp,
+            station_id: station_id.to_string(),
+            channel: event.channels.join(", "),
+            triggers: event.triggers.clone(),
+            max_ratio: event.max_ratio,
+            max_intensity: event.max_intensity.unwrap_or(NO_INTENSITY),
+            snapshot_path: None,
+            pick: event.first_pick().cloned(),
+            distance: recorded.iter().find_map(|a| a.distance.clone()),
+            magnitude: recorded.iter().find_map(|a| a.magnitude.clone()),
+            ground_motion: recorded.iter().filter_map(|a| a.ground_motion.clone()).max_by(|a, b| a.pga.total_cmp(&b.pga)),
+            early_warning: recorded.iter().find_map(|a| a.early_warning.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventManager;
+    use crate::settings::EventSettings;
+
+    #[test]
+    fn test_routes_any_contributing_trigger() {
+        let mut em = EventManager::new(EventSettings { enabled: true, window: 30.0 });
+        let t0 = Utc::now();
+        em.trigger(Uuid::new_v4(), "EHZ", "local", t0, 4.0, None, false);
+        let event = em.trigger(Uuid::new_v4(), "ENZ", "strong", t0, 6.0, None, false);
+
+        assert!(routes_event(&[], &event));
+        assert!(routes_event(&["strong".to_string()], &event));
+        assert!(!routes_event(&["regional".to_string()], &event));
+    }
+}
@@ -16,6 +16,32 @@ pub struct ChannelResponse {
     pub stage_gain: f64,
     /// Overall instrument sensitivity (counts per physical unit) — for fallback
     pub sensitivity: f64,
+    /// Ground-motion units the instrument responds to (e.g. "M/S", "M/S**2")
+    pub input_units: String,
+}
+
+impl ChannelResponse {
+    /// Sensitivity-only response (no poles/zeros), flat across all frequencies.
+    pub fn from_sensitivity(sensitivity: f64, input_units: &str) -> Self {
+        Self {
+            zeros: vec![],
+            poles: vec![],
+            normalization_factor: 1.0,
+            stage_gain: sensitivity,
+            sensitivity,
+            input_units: input_units.to_string(),
+        }
+    }
+}
+
+/// Infer a channel's input units from its SEED code when StationXML omits them.
+/// Instrument code 'N' (accelerometer) and 'G' (gravimeter) respond to acceleration;
+/// everything else (e.g. 'H' high-gain seismometer, Raspberry Shake geophones) to velocity.
+pub fn infer_input_units(channel: &str) -> &'static str {
+    match channel.chars().nth(1) {
+        Some('N') | Some('G') => "M/S**2",
+        _ => "M/S",
+    }
 }
 
 /// Fetch full instrument response (poles/zeros) from FDSN for frequency-domain deconvolution.
@@ -96,6 +122,10 @@ fn parse_response_xml(xml: &str, sta: &str) -> HashMap<String, ChannelResponse>
             continue;
         }
 
+        let input_units = ch_block.find("<InstrumentSensitivity>")
+            .and_then(|idx| extract_xml_value(&ch_block[idx..], "<InputUnits>", "<Name>"))
+            .unwrap_or_else(|| infer_input_units(&code).to_string());
+
         // Find the PolesZeros stage (Stage 1 typically)
         if let Some(pz_idx) = ch_block.find("<PolesZeros") {
             let pz_block = &ch_block[pz_idx..];
@@ -125,8 +155,8 @@ fn parse_response_xml(xml: &str, sta: &str) -> HashMap<String, ChannelResponse>
             let poles = parse_complex_values(pz_block, "Pole");
 
             info!(
-                "Found response for {}.{}: {} zeros, {} poles, A0={}, gain={}, sensitivity={}",
-                sta, code, zeros.len(), poles.len(), a0, stage_gain, sensitivity
+                "Found response for {}.{}: {} zeros, {} poles, A0={}, gain={}, sensitivity={}, input units={}",
+                sta, code, zeros.len(), poles.len(), a0, stage_gain, sensitivity, input_units
             );
 
             responses.insert(code.to_string(), ChannelResponse {
@@ -135,17 +165,12 @@ fn parse_response_xml(xml: &str, sta: &str) -> HashMap<String, ChannelResponse>
                 normalization_factor: a0,
                 stage_gain,
                 sensitivity,
+                input_units,
             });
         } else {
             // No PolesZeros stage — store sensitivity-only response
             info!("No PolesZeros stage for {}.{}, using sensitivity-only: {}", sta, code, sensitivity);
-            responses.insert(code.to_string(), ChannelResponse {
-                zeros: vec![],
-                poles: vec![],
-                normalization_factor: 1.0,
-                stage_gain: sensitivity,
-                sensitivity,
-            });
+            responses.insert(code.to_string(), ChannelResponse::from_sensitivity(sensitivity, &input_units));
         }
     }
 
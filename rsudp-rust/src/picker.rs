@@ -0,0 +1,124 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Seconds of filtered data, ending at the trigger, searched for the onset.
+pub const PICK_WINDOW_SECONDS: f64 = 20.0;
+
+/// An automatic phase arrival.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhasePick {
+    /// Phase name ("P" or "S")
+    pub phase: String,
+    /// Channel id (NET.STA.LOC.CHA) the phase was picked on
+    #[serde(default)]
+    pub channel: String,
+    pub time: DateTime<Utc>,
+    /// One-sigma timing uncertainty (s)
+    pub uncertainty: f64,
+}
+
+impl std::fmt::Display for PhasePick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ±{:.2} s", self.phase, self.time.format("%Y-%m-%dT%H:%M:%S%.2fZ"), self.uncertainty)
+    }
+}
+
+/// Akaike Information Criterion (Maeda 1985) computed directly from the samples:
+/// `AIC(k) = k·ln(var(x[..k])) + (n-k-1)·ln(var(x[k..]))` for `k` in `2..n-2`.
+/// The minimum separates the noise and signal segments. Returns `(k, AIC(k))`.
+pub fn aic_curve(data: &[f64]) -> Vec<(usize, f64)> {
+    let n = data.len();
+    if n < 8 {
+        return Vec::new();
+    }
+
+    // Prefix sums for O(n) windowed variances
+    let mut sum = vec![0.0_f64; n + 1];
+    let mut sum_sq = vec![0.0_f64; n + 1];
+    for (i, &x) in data.iter().enumerate() {
+        sum[i + 1] = sum[i] + x;
+        sum_sq[i + 1] = sum_sq[i] + x * x;
+    }
+    let variance = |a: usize, b: usize| {
+        let len = (b - a) as f64;
+        let mean = (sum[b] - sum[a]) / len;
+        ((sum_sq[b] - sum_sq[a]) / len - mean * mean).max(f64::MIN_POSITIVE)
+    };
+
+    (2..n - 2)
+        .map(|k| (k, k as f64 * variance(0, k).ln() + (n - k - 1) as f64 * variance(k, n).ln()))
+        .collect()
+}
+
+/// Onset index at the minimum of an AIC curve, with a one-sigma uncertainty in
+/// samples taken from the spread of the relative likelihoods `exp(-(AIC(k) - AIC_min) / 2)`.
+pub fn onset_from_aic(aic: &[(usize, f64)]) -> Option<(usize, f64)> {
+    let &(onset, aic_min) = aic.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+    let (mut weight, mut spread) = (0.0_f64, 0.0_f64);
+    for &(k, value) in aic {
+        let w = (-(value - aic_min) / 2.0).exp();
+        weight += w;
+        spread += w * (k as f64 - onset as f64).powi(2);
+    }
+    Some((onset, (spread / weight).sqrt().max(1.0)))
+}
+
+/// AIC onset of `data`: index and one-sigma uncertainty in samples.
+pub fn aic_onset(data: &[f64]) -> Option<(usize, f64)> {
+    onset_from_aic(&aic_curve(data))
+}
+
+/// Pick a phase on `data` (filtered, at `sample_rate`) whose last sample is at `end_time`.
+pub fn pick_phase(phase: &str, channel: &str, data: &[f64], sample_rate: f64, end_time: DateTime<Utc>) -> Option<PhasePick> {
+    let (onset, sigma) = aic_onset(data)?;
+    let before_end = (data.len() - 1 - onset) as f64 / sample_rate;
+    Some(PhasePick {
+        phase: phase.to_string(),
+        channel: channel.to_string(),
+        time: end_time - Duration::microseconds((before_end * 1_000_000.0) as i64),
+        uncertainty: sigma / sample_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Pseudo-random noise (deterministic LCG) with a 5 Hz arrival at `onset`.
+    fn arrival(n: usize, onset: usize, snr: f64) -> Vec<f64> {
+        let mut seed = 12345_u64;
+        (0..n)
+            .map(|i| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let noise = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+                let signal = if i >= onset { snr * (2.0 * PI * 5.0 * (i - onset) as f64 / 100.0).sin() } else { 0.0 };
+                noise + signal
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aic_finds_onset() {
+        let (onset, sigma) = aic_onset(&arrival(1000, 620, 10.0)).unwrap();
+        assert!((onset as i64 - 620).abs() <= 3, "onset={}", onset);
+        assert!(sigma < 5.0, "sigma={}", sigma);
+    }
+
+    #[test]
+    fn test_uncertainty_grows_with_noise() {
+        let (_, sharp) = aic_onset(&arrival(1000, 620, 20.0)).unwrap();
+        let (_, weak) = aic_onset(&arrival(1000, 620, 0.3)).unwrap();
+        assert!(weak > sharp, "weak={} sharp={}", weak, sharp);
+    }
+
+    #[test]
+    fn test_pick_time() {
+        let end = Utc::now();
+        let pick = pick_phase("P", "AM.R0001.00.EHZ", &arrival(1000, 600, 10.0), 100.0, end).unwrap();
+        // Onset is 399 samples (3.99 s) before the last sample
+        let offset = (end - pick.time).num_milliseconds();
+        assert!((offset - 3990).abs() <= 30, "offset={} ms", offset);
+        assert_eq!(pick.phase, "P");
+    }
+}
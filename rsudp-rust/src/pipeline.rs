@@ -3,42 +3,71 @@ use tracing::{info, warn};
 use std::collections::HashMap;
 use crate::parser::{parse_any};
 use crate::trigger::{TriggerManager, TriggerConfig, AlertEventType};
-use crate::intensity::{IntensityManager, IntensityConfig};
+use crate::intensity::{GroundMotionPeaks, IntensityManager, IntensityConfig, RealtimeIntensity};
 use crate::web::stream::{WebState, ChannelBuffer};
 use crate::web::alerts::AlertEvent as WebAlertEvent;
 use uuid::Uuid;
 use std::time::{Duration, Instant};
 use chrono::Utc;
 
-use crate::web::sns::{SNSManager, NotificationEvent};
+use crate::web::sns::SNSManager;
 use crate::hue::HueIntegration;
 use crate::sound::AudioController;
-use crate::settings::{AlertSoundSettings, CaptureSettings};
+use crate::settings::{routes_trigger, AdaptiveSettings, AlertSoundSettings, CaptureSettings};
 use crate::forward::ForwardManager;
 use crate::pubsub::publisher::SegmentData;
 use crate::rsam::RsamManager;
+use crate::coincidence::{CoincidenceManager, NETWORK_ID};
+use crate::conditioning::{Conditioned, SignalConditioner};
+use crate::distance::DistanceEstimator;
+use crate::eew::EarlyWarningEstimator;
+use crate::event::EventState;
+use crate::notifier::EventNotifier;
+use crate::ground_motion::GroundMotionTracker;
 use std::sync::Arc;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn run_pipeline(
     mut receiver: mpsc::Receiver<Vec<u8>>,
-    trigger_config: TriggerConfig,
+    trigger_configs: Vec<TriggerConfig>,
+    adaptive: Option<AdaptiveSettings>,
     intensity_config: Option<IntensityConfig>,
     web_state: WebState,
-    _sensitivity_map: HashMap<String, f64>,
+    sensitivity_map: HashMap<String, f64>,
     sns_manager: Option<Arc<SNSManager>>,
     hue_integration: Option<HueIntegration>,
     audio_controller: Option<AudioController>,
     alert_sound_settings: AlertSoundSettings,
     forward_manager: Option<Arc<ForwardManager>>,
     mut rsam_manager: Option<RsamManager>,
+    mut coincidence: Option<CoincidenceManager>,
+    mut conditioner: Option<SignalConditioner>,
+    mut distance: Option<DistanceEstimator>,
+    mut ground_motion: Option<GroundMotionTracker>,
+    mut early_warning: Option<EarlyWarningEstimator>,
+    mut realtime: Option<RealtimeIntensity>,
     publisher_tx: Option<mpsc::Sender<SegmentData>>,
     capture_settings: CaptureSettings,
 ) {
     info!("Pipeline started");
-    let mut tm = TriggerManager::new(trigger_config);
+    let responses = web_state.response_map.read().unwrap().clone();
+    let mut triggers: Vec<TriggerManager> = trigger_configs.into_iter()
+        .map(|config| {
+            let adapt = adaptive.clone().filter(|a| routes_trigger(&a.triggers, &config.name));
+            let tm = TriggerManager::new(config).with_characteristic_output().with_responses(responses.clone());
+            match adapt {
+                Some(settings) => tm.with_adaptive(settings),
+                None => tm,
+            }
+        })
+        .collect();
+    // Revision of the live trigger definitions last applied
+    let mut trigger_revision = web_state.triggers.read().unwrap().revision;
     let mut im = intensity_config.map(IntensityManager::new);
-    let mut active_alerts: HashMap<String, Uuid> = HashMap::new();
+    let scale = *web_state.intensity_scale.read().unwrap();
+    let mut notifier = EventNotifier::new(web_state.clone(), sns_manager, hue_integration, audio_controller, alert_sound_settings, forward_manager.clone(), scale);
+    // Keyed by (trigger name, channel id): each trigger definition alerts independently
+    let mut active_alerts: HashMap<(String, String), Uuid> = HashMap::new();
     let max_buffer_samples = (100.0 * 300.0) as usize; 
 
     // --- LOGGING STATE ---
@@ -47,10 +76,6 @@ pub async fn run_pipeline(
     let mut max_intensity_window: f64 = -9.9;
 
     while let Some(data) = receiver.recv().await {
-        // Clone for use in this iteration
-        let hue_iter = hue_integration.clone();
-        let audio_iter = audio_controller.clone();
-        
         let segments = match parse_any(&data) {
             Ok(s) => s,
             Err(e) => {
@@ -59,6 +84,19 @@ pub async fn run_pipeline(
             }
         };
 
+        // --- LIVE TRIGGER CHANGES ---
+        {
+            let registry = web_state.triggers.read().unwrap();
+            if registry.revision != trigger_revision {
+                trigger_revision = registry.revision;
+                for config in &registry.configs {
+                    if let Some(tm) = triggers.iter_mut().find(|tm| tm.config().name == config.name) {
+                        tm.reconfigure(config.clone());
+                    }
+                }
+            }
+        }
+
         // --- FORWARD DATA ---
         if let Some(fwd) = &forward_manager {
             for seg in &segments {
@@ -83,176 +121,259 @@ pub async fn run_pipeline(
             }
 
             let id = format!("{}.{}.{}.{}", segment.network, segment.station, segment.location, segment.channel);
-            let sensitivity = 1.0; 
+            // Counts per physical unit; 0.0 (unknown) keeps deconvolving triggers on counts
+            let sensitivity = sensitivity_map.get(&segment.channel).copied().unwrap_or(0.0);
             
-            // --- TRIGGER ---
-            for (i, &sample) in segment.samples.iter().enumerate() {
-                let sample_ts = segment.starttime + chrono::Duration::nanoseconds((i as f64 * 1_000_000_000.0 / segment.sampling_rate) as i64);
-                
-                if let Some(alert) = tm.add_sample(&id, sample, sample_ts, sensitivity) {
-                    match alert.event_type {
-                        AlertEventType::Trigger => {
-                            let alert_id = Uuid::new_v4();
-                            active_alerts.insert(id.clone(), alert_id);
-                            info!("Triggered: {}. Acquiring max_ints lock...", id);
-                            {
-                                let mut max_ints = web_state.alert_max_intensities.lock().unwrap();
-                                max_ints.insert(alert_id, -2.0);
-                            }
-                            info!("{}", alert);
-                            
-                            web_state.broadcast_alert_start(alert_id, segment.channel.clone(), alert.timestamp).await;
-                            let (settings, trigger_time) = {
-                                let mut history = web_state.history.lock().unwrap();
-                                history.add_event(WebAlertEvent {
-                                    id: alert_id, channel: segment.channel.clone(), trigger_time: alert.timestamp, reset_time: None, max_ratio: alert.ratio, snapshot_path: None, message: None,
-                                });
-                                (history.get_settings(), alert.timestamp)
-                            };
-                            let ch = segment.channel.clone();
-                            let t_settings_trig = settings.clone();
-                            tokio::spawn(async move {
-                                if let Err(e) = crate::web::alerts::send_trigger_email(&t_settings_trig, &ch, trigger_time) { warn!("Failed to send trigger email: {}", e); }
-                            });
-                            if let Some(sns) = sns_manager.clone() {
-                                let event = NotificationEvent {
-                                    event_type: AlertEventType::Trigger, timestamp: alert.timestamp, station_id: format!("{}.{}", segment.network, segment.station), channel: segment.channel.clone(), max_ratio: alert.ratio, max_intensity: 0.0, snapshot_path: None,
-                                };
-                                tokio::spawn(async move { sns.notify_trigger(&event).await; });
-                            }
+            // --- CONDITIONING ---
+            // The triggers see the despiked, step-corrected samples, a few samples late
+            let conditioned = match conditioner.as_mut() {
+                Some(sc) => sc.process_segment(&id, segment.starttime, segment.sampling_rate, &segment.samples),
+                None => Conditioned { start: segment.starttime, samples: segment.samples.clone(), glitches: Vec::new() },
+            };
+            // Artefacts are kept in the alert history as suppressed entries, for auditing
+            for glitch in &conditioned.glitches {
+                web_state.history.lock().unwrap().add_event(WebAlertEvent {
+                    id: Uuid::new_v4(), channel: segment.channel.clone(), trigger_name: "conditioning".to_string(), trigger_time: glitch.time, reset_time: Some(glitch.time), max_ratio: 0.0, snapshot_path: None, message: Some(format!("Suppressed: {}", glitch)), pick: None, distance: None, magnitude: None, ground_motion: None, early_warning: None, clipped: Vec::new(), suppressed: Some(glitch.kind.to_string()), muted: false,
+                });
+            }
 
-                            // Forward ALARM message
-                            if let Some(fwd) = &forward_manager {
-                                fwd.forward_alarm(&format!("ALARM {} {}", segment.channel, alert.timestamp.to_rfc3339()));
-                            }
+            // --- REAL-TIME INTENSITY ---
+            let mut realtime_alerts = Vec::new();
+            if let Some(rt) = realtime.as_mut() {
+                rt.add_segment(&id, segment.starttime, segment.sampling_rate, &segment.samples);
+                for sample in rt.drain_samples() {
+                    web_state.broadcast_realtime_intensity(sample).await;
+                }
+                realtime_alerts = rt.drain_alerts();
+            }
 
-                            // Hue Alert Trigger
-                            if let Some(hue) = &hue_iter {
-                                let hue_clone = hue.clone();
-                                tokio::spawn(async move {
-                                    hue_clone.trigger_alert().await;
-                                });
-                            }
+            // --- TRIGGER ---
+            for tm in triggers.iter_mut() {
+                tm.set_sample_rate(&id, segment.sampling_rate);
+            }
+            let mut alerts = Vec::new();
+            for (i, &sample) in conditioned.samples.iter().enumerate() {
+                let sample_ts = conditioned.start + chrono::Duration::nanoseconds((i as f64 * 1_000_000_000.0 / segment.sampling_rate) as i64);
+                for alert in triggers.iter_mut().filter_map(|tm| tm.add_sample(&id, sample, sample_ts, sensitivity)) {
+                    let alert = match coincidence.as_mut() {
+                        Some(cm) => cm.process(alert),
+                        None => Some(alert),
+                    };
+                    alerts.extend(alert);
+                }
+            }
+            // Real-time intensity alerts are station-level and skip coincidence
+            alerts.append(&mut realtime_alerts);
 
-                            // Audio Alert Trigger
-                            if let Some(audio) = &audio_iter {
-                                audio.queue_file(&alert_sound_settings.trigger_file);
-                            }
-                            
-                            let plot_settings = web_state.settings.read().unwrap().clone();
-                            let delay = Duration::from_secs_f64(plot_settings.window_seconds * plot_settings.save_pct);
-                            let shared_state = web_state.clone();
-                            let alert_ch = segment.channel.clone();
-                            let alert_sta = segment.station.clone();
-                            let t_settings_reset = settings.clone();
-                            let sns_for_reset = sns_manager.clone();
-                            let cap_settings = capture_settings.clone();
+            for alert in alerts {
+                // With coincidence enabled every TRIGGER/RESET here is the network-level alert
+                let (alert_key, alert_channel, alert_station_id) = if realtime.as_ref().is_some_and(|rt| rt.name() == alert.trigger_name) {
+                    let station = format!("{}.{}", segment.network, segment.station);
+                    ((alert.trigger_name.clone(), station.clone()), alert.channel.clone(), station)
+                } else if coincidence.is_some() {
+                    ((alert.trigger_name.clone(), NETWORK_ID.to_string()), alert.channel.clone(), NETWORK_ID.to_string())
+                } else {
+                    ((alert.trigger_name.clone(), id.clone()), segment.channel.clone(), format!("{}.{}", segment.network, segment.station))
+                };
+                match alert.event_type {
+                    AlertEventType::Trigger => {
+                        let alert_id = Uuid::new_v4();
+                        active_alerts.insert(alert_key, alert_id);
+                        if let (Some(de), Some(pick)) = (distance.as_mut(), alert.pick.clone()) {
+                            de.watch(alert_id, pick);
+                        }
+                        if let (Some(eew), Some(pick)) = (early_warning.as_mut(), alert.pick.clone()) {
+                            eew.watch(alert_id, &alert.trigger_name, pick);
+                        }
+                        if let Some(gm) = ground_motion.as_mut() {
+                            gm.start(alert_id, &format!("{}.{}", segment.network, segment.station));
+                        }
+                        info!("Triggered: {} [{}]. Acquiring max_ints lock...", id, alert.trigger_name);
+                        {
+                            let mut max_ints = web_state.alert_max_intensities.lock().unwrap();
+                            max_ints.insert(alert_id, -2.0);
+                        }
+                        info!("{}", alert);
+                        let mute_reason = web_state.maintenance.read().unwrap().muted_at(alert.timestamp);
+                        let event = web_state.events.lock().unwrap().trigger(alert_id, &alert_channel, &alert.trigger_name, alert.timestamp, alert.ratio, alert.pick.clone(), mute_reason.is_some());
+                        // Alerts joining an event share its mute state
+                        let muted = event.muted;
+                        let declared = event.state == EventState::Declared;
+                        if let Some(reason) = mute_reason.filter(|_| declared) {
+                            info!("Event {} muted ({}); notifications skipped", event.id, reason);
+                        }
 
-                            tokio::spawn(async move {
-                                tokio::time::sleep(delay).await;
-                                let max_int = {
-                                    let max_ints = shared_state.alert_max_intensities.lock().unwrap();
-                                    max_ints.get(&alert_id).copied().unwrap_or(-2.0)
-                                };
-                                let shindo_class = crate::intensity::get_shindo_class(max_int);
-                                let intensity_message = crate::web::alerts::format_shindo_message(&shindo_class);
-                                let pre_trigger_duration = plot_settings.window_seconds * (1.0 - plot_settings.save_pct);
-                                let start_time = trigger_time - chrono::Duration::milliseconds((pre_trigger_duration * 1000.0) as i64);
-                                let end_time = start_time + chrono::Duration::milliseconds((plot_settings.window_seconds * 1000.0) as i64);
-                                let channels: Vec<String> = {
-                                    let buffers = shared_state.waveform_buffers.lock().unwrap();
-                                    buffers.keys().cloned().collect()
-                                };
-                                let out_dir = plot_settings.output_dir.clone();
-                                let snapshot_path: Option<std::path::PathBuf> = if cap_settings.enabled {
-                                    crate::web::alerts::capture_screenshot(
-                                        &cap_settings.service_url,
-                                        cap_settings.timeout_seconds,
-                                        &alert_sta,
-                                        &channels,
-                                        start_time,
-                                        end_time,
-                                        &shindo_class,
-                                        max_int,
-                                        &cap_settings.backend_url,
-                                        &out_dir,
-                                    ).await
-                                } else {
-                                    None
-                                };
-                                if let Some(ref p) = snapshot_path {
-                                    let mut history = shared_state.history.lock().unwrap();
-                                    if let Some(fname) = p.file_name() {
-                                        history.set_snapshot_path(alert_id, fname.to_string_lossy().to_string());
-                                    }
-                                }
-                                if let Err(e) = crate::web::alerts::send_reset_email(&t_settings_reset, &alert_ch, trigger_time, Utc::now(), max_int, snapshot_path.as_ref().and_then(|p| p.file_name()).map(|f| format!("http://localhost:8080/images/alerts/{}", f.to_string_lossy())).as_deref(), &intensity_message) { warn!("Failed to send reset email: {}", e); }
-                                if let Some(sns) = sns_for_reset.clone() {
-                                    let event = NotificationEvent {
-                                        event_type: AlertEventType::Reset, timestamp: Utc::now(), station_id: alert_sta.clone(), channel: alert_ch.clone(), max_ratio: alert.max_ratio, max_intensity: max_int, snapshot_path: snapshot_path.clone(),
-                                    };
-                                    tokio::spawn(async move { sns.notify_reset(&event).await; });
-                                }
+                        web_state.broadcast_alert_start(alert_id, alert_channel.clone(), alert.trigger_name.clone(), alert.timestamp, alert.pick.clone()).await;
+                        web_state.history.lock().unwrap().add_event(WebAlertEvent {
+                            id: alert_id, channel: alert_channel.clone(), trigger_name: alert.trigger_name.clone(), trigger_time: alert.timestamp, reset_time: None, max_ratio: alert.ratio, snapshot_path: None, message: None, pick: alert.pick.clone(), distance: None, magnitude: None, ground_motion: None, early_warning: None, clipped: Vec::new(), suppressed: None, muted,
+                        });
+                        let trigger_time = alert.timestamp;
+                        info!("{}", event);
+                        let event_id = event.id;
+                        notifier.dispatch(&event, &alert_station_id);
+                        web_state.broadcast_event(event).await;
+
+                        let plot_settings = web_state.settings.read().unwrap().clone();
+                        let delay = Duration::from_secs_f64(plot_settings.window_seconds * plot_settings.save_pct);
+                        let shared_state = web_state.clone();
+                        let alert_ch = alert_channel.clone();
+                        let alert_sta = segment.station.clone();
+                        let cap_settings = capture_settings.clone();
 
-                                {
-                                    let mut history = shared_state.history.lock().unwrap();
-                                    history.reset_event(alert_id, Utc::now(), max_int, intensity_message.clone());
+                        let capture = tokio::spawn(async move {
+                            tokio::time::sleep(delay).await;
+                            let max_int = {
+                                let max_ints = shared_state.alert_max_intensities.lock().unwrap();
+                                max_ints.get(&alert_id).copied().unwrap_or(-2.0)
+                            };
+                            let shindo_class = scale.class(max_int);
+                            let intensity_message = crate::web::alerts::format_intensity_message(scale, &shindo_class);
+                            let pre_trigger_duration = plot_settings.window_seconds * (1.0 - plot_settings.save_pct);
+                            let start_time = trigger_time - chrono::Duration::milliseconds((pre_trigger_duration * 1000.0) as i64);
+                            let end_time = start_time + chrono::Duration::milliseconds((plot_settings.window_seconds * 1000.0) as i64);
+                            let channels: Vec<String> = {
+                                let buffers = shared_state.waveform_buffers.lock().unwrap();
+                                buffers.keys().cloned().collect()
+                            };
+                            let out_dir = plot_settings.output_dir.clone();
+                            // One snapshot per event, taken for the alert that declared it
+                            let snapshot_path: Option<std::path::PathBuf> = if cap_settings.enabled && declared {
+                                crate::web::alerts::capture_screenshot(
+                                    &cap_settings.service_url,
+                                    cap_settings.timeout_seconds,
+                                    &alert_sta,
+                                    &channels,
+                                    start_time,
+                                    end_time,
+                                    &shindo_class,
+                                    max_int,
+                                    &cap_settings.backend_url,
+                                    &out_dir,
+                                ).await
+                            } else {
+                                None
+                            };
+                            if let Some(ref p) = snapshot_path {
+                                let mut history = shared_state.history.lock().unwrap();
+                                if let Some(fname) = p.file_name() {
+                                    history.set_snapshot_path(alert_id, fname.to_string_lossy().to_string());
                                 }
-                                shared_state.broadcast_alert_end(alert_id, alert_ch, Utc::now(), max_int, intensity_message).await;
+                            }
+                            {
+                                let mut history = shared_state.history.lock().unwrap();
+                                history.reset_event(alert_id, Utc::now(), max_int, intensity_message.clone());
+                            }
+                            shared_state.broadcast_alert_end(alert_id, alert_ch, Utc::now(), max_int, intensity_message).await;
 
-                                // Delayed cleanup: remove max_int entry after RESET has had time to read it
-                                let cleanup_state = shared_state.clone();
-                                tokio::spawn(async move {
-                                    tokio::time::sleep(Duration::from_secs(300)).await;
-                                    cleanup_state.alert_max_intensities.lock().unwrap().remove(&alert_id);
-                                });
+                            // Delayed cleanup: remove max_int entry after RESET has had time to read it
+                            let cleanup_state = shared_state.clone();
+                            tokio::spawn(async move {
+                                tokio::time::sleep(Duration::from_secs(300)).await;
+                                cleanup_state.alert_max_intensities.lock().unwrap().remove(&alert_id);
                             });
-                        },
-                        AlertEventType::Reset => {
-                            if let Some(alert_id) = active_alerts.remove(&id) {
-                                let max_int = {
-                                    let max_ints = web_state.alert_max_intensities.lock().unwrap();
-                                    // Read but don't remove — snapshot task will clean up
-                                    max_ints.get(&alert_id).copied().unwrap_or(-9.9)
-                                };
-                                let shindo = crate::intensity::get_shindo_class(max_int);
-                                info!("{} | Max Intensity: {:.2} (JMA: {})", alert, max_int, shindo);
-
-                                // Forward RESET message
-                                if let Some(fwd) = &forward_manager {
-                                    fwd.forward_alarm(&format!("RESET {} {}", alert.channel, alert.timestamp.to_rfc3339()));
+                            snapshot_path
+                        });
+                        if declared {
+                            notifier.add_snapshot(event_id, capture);
+                        }
+                    },
+                    AlertEventType::Reset => {
+                        if let Some(alert_id) = active_alerts.remove(&alert_key) {
+                            let peaks = ground_motion.as_mut().and_then(|gm| gm.finish(alert_id));
+                            let max_int = {
+                                let mut max_ints = web_state.alert_max_intensities.lock().unwrap();
+                                // Without an intensity stream, fall back to the event's peaks
+                                if let (None, Some(gm)) = (im.as_ref(), peaks.as_ref()) {
+                                    let motion = GroundMotionPeaks { jma: None, pga: Some(gm.pga * 100.0), pgv: Some(gm.pgv * 100.0) };
+                                    max_ints.insert(alert_id, scale.intensity(&motion));
                                 }
-
-                                // Hue Alert Reset
-                                if let Some(hue) = &hue_iter {
-                                    let hue_clone = hue.clone();
-                                    tokio::spawn(async move {
-                                        hue_clone.reset_alert(max_int).await;
-                                    });
+                                // Read but don't remove — snapshot task will clean up
+                                max_ints.get(&alert_id).copied().unwrap_or(-9.9)
+                            };
+                            let pga = peaks.as_ref().map(|gm| gm.pga * 100.0);
+                            if let Some(gm) = peaks.as_ref() {
+                                web_state.events.lock().unwrap().add_ground_motion(alert_id, gm);
+                            }
+                            if let Some(gm) = peaks {
+                                info!("Alert {}: {}", alert_id, gm);
+                                web_state.history.lock().unwrap().set_ground_motion(alert_id, gm);
+                            }
+                            if let Some(sc) = conditioner.as_ref() {
+                                let mut history = web_state.history.lock().unwrap();
+                                let clipped = history.get_event(alert_id).map(|a| sc.clipped_since(a.trigger_time)).unwrap_or_default();
+                                if !clipped.is_empty() {
+                                    warn!("Alert {}: clipped on {}", alert_id, clipped.join(", "));
+                                    history.set_clipped(alert_id, clipped);
                                 }
+                            }
+                            info!("{} | Max Intensity: {:.2} ({}: {})", alert, max_int, scale.name(), scale.class(max_int));
 
-                                // Audio Alert Reset
-                                if let Some(audio) = &audio_iter {
-                                    let file_path = alert_sound_settings.intensity_files
-                                        .get(&shindo.to_string())
-                                        .cloned()
-                                        .unwrap_or(alert_sound_settings.default_reset_file.clone());
-
-                                    if file_path.is_empty() {
-                                        warn!("No audio file found for intensity {} and no default configured", shindo);
-                                    } else {
-                                        audio.queue_file(&file_path);
-                                    }
-                                }
+                            let intensity = (im.is_some() || pga.is_some()).then_some(max_int);
+                            let event = web_state.events.lock().unwrap().reset(alert_id, alert.timestamp, alert.max_ratio, intensity, pga);
+                            let Some(mut event) = event else { continue };
+                            if event.state == EventState::Closed {
+                                event.catalog = web_state.catalog.lock().unwrap().match_detection(event.detection_time());
+                                web_state.events.lock().unwrap().set_catalog(event.id, event.catalog.clone());
                             }
-                        },
-                        AlertEventType::Status => {
-                            max_ratio_window = max_ratio_window.max(alert.ratio);
+                            info!("{}", event);
+                            notifier.dispatch(&event, &alert_station_id);
+                            web_state.broadcast_event(event).await;
                         }
+                    },
+                    AlertEventType::Status => {
+                        max_ratio_window = max_ratio_window.max(alert.ratio);
+                    }
+                    // Early events come from the estimator below, never from the triggers
+                    AlertEventType::Early => {}
+                }
+            }
+
+            // --- EFFECTIVE THRESHOLDS ---
+            {
+                let mut thresholds = web_state.thresholds.write().unwrap();
+                for th in triggers.iter().filter_map(|tm| tm.effective_threshold(&id)) {
+                    thresholds.insert(format!("{}:{}", th.trigger_name, th.channel), th);
+                }
+            }
+
+            // --- S PICK / DISTANCE ---
+            if let Some(de) = distance.as_mut() {
+                de.add_segment(&id, segment.starttime, segment.sampling_rate, &segment.samples);
+                for (alert_id, estimate) in de.poll() {
+                    let mut history = web_state.history.lock().unwrap();
+                    if let Some(distance) = estimate.distance {
+                        info!("Alert {}: distance {}", alert_id, distance);
+                        history.set_distance(alert_id, distance);
+                    }
+                    if let Some(magnitude) = estimate.magnitude {
+                        info!("Alert {}: {}", alert_id, magnitude);
+                        history.set_magnitude(alert_id, magnitude);
                     }
                 }
             }
 
+            // --- EARLY WARNING ---
+            if let Some(eew) = early_warning.as_mut() {
+                eew.add_segment(&id, segment.starttime, segment.sampling_rate, &segment.samples);
+                for (alert_id, alert) in eew.poll() {
+                    let Some(ew) = alert.early_warning.clone() else { continue };
+                    info!("{}", alert);
+                    web_state.history.lock().unwrap().set_early_warning(alert_id, ew.clone());
+                    web_state.broadcast_alert(alert.clone()).await;
+                    // Only react for the alert that declared the event, while it is still
+                    // running; the reset supersedes the estimate
+                    let event = web_state.events.lock().unwrap().event_of(alert_id).filter(|e| e.id == alert_id).cloned();
+                    let Some(event) = event.filter(|_| active_alerts.values().any(|&id| id == alert_id)) else { continue };
+                    let estimated = scale.intensity(&GroundMotionPeaks { jma: Some(ew.intensity), pga: None, pgv: Some(ew.pgv * 100.0) });
+                    notifier.early(&event, estimated);
+                }
+            }
+
+            // --- GROUND MOTION ---
+            if let Some(gm) = ground_motion.as_mut() {
+                gm.add_segment(&id, segment.starttime, segment.sampling_rate, &segment.samples);
+            }
+
             // --- PUBSUB PUBLISHER ---
             if let Some(ref pub_tx) = publisher_tx {
                 let seg_data = SegmentData {
@@ -265,14 +386,18 @@ pub async fn run_pipeline(
             }
 
             web_state.broadcast_waveform(segment.channel.clone(), segment.starttime, segment.samples.clone()).await;
+            for tm in triggers.iter_mut() {
+                for cf in tm.drain_characteristic() {
+                    web_state.broadcast_characteristic(cf).await;
+                }
+            }
 
             // --- INTENSITY ---
             if let Some(im) = im.as_mut() {
-                let is_target = im.config().channels.iter().any(|target| id.contains(target));
-                if is_target {
+                if let Some(component) = im.config().channel_of(&id).map(str::to_string) {
                     let mut map = HashMap::new();
-                    let short_name = if id.contains("ENE") { "ENE" } else if id.contains("ENN") { "ENN" } else { "ENZ" };
-                    map.insert(short_name.to_string(), segment.samples.clone());
+                    map.insert(component, segment.samples.clone());
+                    im.set_sample_rate(segment.sampling_rate);
                     im.add_samples(map, segment.starttime);
                     for res in im.get_results() {
                         max_intensity_window = max_intensity_window.max(res.intensity);
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+/// Damping ratio of the JMA long-period ground motion class and of design spectra.
+pub const DAMPING: f64 = 0.05;
+
+/// Relative displacement and velocity of a damped single-degree-of-freedom oscillator
+/// driven by ground acceleration `acc` (`x'' + 2hωx' + ω²x = -a`), starting at rest.
+/// Exact for piecewise-linear input (Nigam & Jennings 1969), so it holds at any rate.
+pub fn sdof_response(acc: &[f64], sample_rate: f64, period: f64, damping: f64) -> (Vec<f64>, Vec<f64>) {
+    let dt = 1.0 / sample_rate;
+    let (w, h) = (2.0 * std::f64::consts::PI / period, damping);
+    let r = (1.0 - h * h).sqrt();
+    let wd = w * r;
+    let e = (-h * w * dt).exp();
+    let (s, c) = (wd * dt).sin_cos();
+    let (w2, w3) = (w * w, w * w * w);
+
+    let a11 = e * (h / r * s + c);
+    let a12 = e * s / wd;
+    let a21 = -w / r * e * s;
+    let a22 = e * (c - h / r * s);
+    let k1 = (2.0 * h * h - 1.0) / (w2 * dt);
+    let k2 = 2.0 * h / (w3 * dt);
+    let b11 = e * ((k1 + h / w) * s / wd + (k2 + 1.0 / w2) * c) - k2;
+    let b12 = -e * (k1 * s / wd + k2 * c) - 1.0 / w2 + k2;
+    let b21 = e * ((k1 + h / w) * (c - h / r * s) - (k2 + 1.0 / w2) * (wd * s + h * w * c)) + 1.0 / (w2 * dt);
+    let b22 = -e * (k1 * (c - h / r * s) - k2 * (wd * s + h * w * c)) - 1.0 / (w2 * dt);
+
+    let mut disp = Vec::with_capacity(acc.len());
+    let mut vel = Vec::with_capacity(acc.len());
+    let (mut x, mut v) = (0.0, 0.0);
+    for (i, &a) in acc.iter().enumerate() {
+        disp.push(x);
+        vel.push(v);
+        let next = acc.get(i + 1).copied().unwrap_or(a);
+        (x, v) = (a11 * x + a12 * v + b11 * a + b12 * next, a21 * x + a22 * v + b21 * a + b22 * next);
+    }
+    (disp, vel)
+}
+
+/// Pseudo-spectral acceleration `ω²·max|x|` (units of `acc`) at each period, 5% damping.
+pub fn pseudo_spectral_acceleration(acc: &[f64], sample_rate: f64, periods: &[f64]) -> Vec<f64> {
+    periods
+        .iter()
+        .map(|&period| {
+            let w = 2.0 * std::f64::consts::PI / period;
+            let (disp, _) = sdof_response(acc, sample_rate, period, DAMPING);
+            w * w * disp.iter().fold(0.0_f64, |m, x| m.max(x.abs()))
+        })
+        .collect()
+}
+
+/// Pseudo-spectral acceleration of one channel (m/s²), one value per period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelSpectrum {
+    pub channel: String,
+    pub psa: Vec<f64>,
+}
+
+/// 5%-damped acceleration response spectrum of an event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseSpectrum {
+    pub damping: f64,
+    /// Oscillator periods (s)
+    pub periods: Vec<f64>,
+    pub components: Vec<ChannelSpectrum>,
+    /// Larger horizontal PSA per period (m/s²); all components when there are no horizontals
+    pub max_horizontal: Vec<f64>,
+}
+
+impl ResponseSpectrum {
+    /// Spectrum of acceleration records (channel id, sample rate, m/s²).
+    pub fn from_channels(channels: &[(String, f64, &[f64])], periods: &[f64]) -> Option<Self> {
+        if channels.is_empty() || periods.is_empty() {
+            return None;
+        }
+        let components: Vec<ChannelSpectrum> = channels
+            .iter()
+            .map(|(channel, fs, acc)| ChannelSpectrum { channel: channel.clone(), psa: pseudo_spectral_acceleration(acc, *fs, periods) })
+            .collect();
+        let is_horizontal = |c: &ChannelSpectrum| matches!(c.channel.chars().last(), Some('N' | 'E' | '1' | '2'));
+        let horizontal: Vec<&ChannelSpectrum> = match components.iter().filter(|c| is_horizontal(c)).collect::<Vec<_>>() {
+            h if h.is_empty() => components.iter().collect(),
+            h => h,
+        };
+        let max_horizontal = (0..periods.len()).map(|i| horizontal.iter().map(|c| c.psa[i]).fold(0.0, f64::max)).collect();
+        Some(Self { damping: DAMPING, periods: periods.to_vec(), components, max_horizontal })
+    }
+
+    /// Largest value of the spectrum (m/s²).
+    pub fn peak(&self) -> f64 {
+        self.max_horizontal.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+/// JMA long-period ground motion class (長周期地震動階級) of an event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LongPeriodMotion {
+    /// Class 0–4
+    pub class: u8,
+    /// Largest absolute velocity response of the horizontal vector, 5% damping (cm/s)
+    pub sva: f64,
+    /// Period of that maximum (s)
+    pub period: f64,
+}
+
+impl LongPeriodMotion {
+    /// Class from the peak absolute velocity response (cm/s).
+    pub fn class_of(sva: f64) -> u8 {
+        match sva {
+            s if s >= 100.0 => 4,
+            s if s >= 50.0 => 3,
+            s if s >= 15.0 => 2,
+            s if s >= 5.0 => 1,
+            _ => 0,
+        }
+    }
+
+    /// Sva over 1.6–7.8 s in 0.2 s steps from aligned horizontal components, each given as
+    /// ground acceleration (m/s²) and ground velocity (m/s) at `sample_rate`.
+    pub fn from_horizontals(horizontals: &[(&[f64], &[f64])], sample_rate: f64) -> Option<Self> {
+        let len = horizontals.iter().map(|(a, v)| a.len().min(v.len())).min()?;
+        if len == 0 {
+            return None;
+        }
+        let mut best: Option<Self> = None;
+        for step in 0..=31 {
+            let period = 1.6 + 0.2 * step as f64;
+            // Absolute velocity: oscillator relative to the ground plus the ground itself
+            let absolute: Vec<Vec<f64>> = horizontals
+                .iter()
+                .map(|(acc, ground)| {
+                    let (_, rel) = sdof_response(&acc[..len], sample_rate, period, DAMPING);
+                    rel.iter().zip(ground.iter()).map(|(r, g)| r + g).collect()
+                })
+                .collect();
+            let peak = (0..len)
+                .map(|i| absolute.iter().map(|s| s[i] * s[i]).sum::<f64>().sqrt())
+                .fold(0.0, f64::max);
+            let sva = peak * 100.0;
+            if best.as_ref().is_none_or(|b| sva > b.sva) {
+                best = Some(Self { class: Self::class_of(sva), sva, period });
+            }
+        }
+        best
+    }
+}
+
+impl std::fmt::Display for LongPeriodMotion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "long-period class {} (Sva {:.1} cm/s at {:.1} s)", self.class, self.sva, self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_sdof_static_and_resonant_response() {
+        let fs = 100.0;
+        let period = 2.0;
+        let w = 2.0 * PI / period;
+
+        // Constant acceleration: the oscillator settles at -a/ω²
+        let (disp, _) = sdof_response(&vec![1.0; 6000], fs, period, DAMPING);
+        assert!((disp.last().unwrap() + 1.0 / (w * w)).abs() < 1e-3, "{}", disp.last().unwrap());
+
+        // Resonance: steady-state amplitude a/(2hω²)
+        let acc: Vec<f64> = (0..20000).map(|i| (w * i as f64 / fs).sin()).collect();
+        let (disp, vel) = sdof_response(&acc, fs, period, DAMPING);
+        let peak = disp[15000..].iter().fold(0.0_f64, |m, x| m.max(x.abs()));
+        assert!((peak * 2.0 * DAMPING * w * w - 1.0).abs() < 0.01, "{}", peak);
+        let peak_v = vel[15000..].iter().fold(0.0_f64, |m, x| m.max(x.abs()));
+        assert!((peak_v / (peak * w) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pseudo_spectral_acceleration() {
+        let fs = 100.0;
+        let acc: Vec<f64> = (0..3000).map(|i| (2.0 * PI * i as f64 / fs).sin()).collect();
+        // A very stiff oscillator follows the ground: PSA → PGA
+        let stiff = ResponseSpectrum::from_channels(&[("AM.R0001.00.ENE".to_string(), fs, &acc)], &[0.02, 1.0]).unwrap();
+        assert!((stiff.components[0].psa[0] - 1.0).abs() < 0.05, "{:?}", stiff);
+        assert_eq!(stiff.max_horizontal, stiff.components[0].psa);
+        assert!(stiff.peak() >= stiff.max_horizontal[1]);
+    }
+
+    #[test]
+    fn test_long_period_class() {
+        // 10 cycles of 4 s ground motion, 20 cm/s peak ground velocity on one component
+        let fs = 50.0;
+        let w = 2.0 * PI / 4.0;
+        let n = (40.0 * fs) as usize;
+        let vel: Vec<f64> = (0..n).map(|i| 0.2 * (w * i as f64 / fs).sin()).collect();
+        let acc: Vec<f64> = (0..n).map(|i| 0.2 * w * (w * i as f64 / fs).cos()).collect();
+        let zero = vec![0.0; n];
+        let lp = LongPeriodMotion::from_horizontals(&[(&acc, &vel), (&zero, &zero)], fs).unwrap();
+        assert!((lp.period - 4.0).abs() < 0.21, "{}", lp);
+        // Resonant amplification lifts the class above the ground velocity alone
+        assert!(lp.sva > 50.0 && lp.class >= 3, "{}", lp);
+        assert_eq!(LongPeriodMotion::class_of(4.9), 0);
+        assert_eq!(LongPeriodMotion::class_of(15.0), 2);
+    }
+}
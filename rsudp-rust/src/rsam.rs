@@ -5,7 +5,9 @@ use std::time::{Duration, Instant};
 
 use tracing::{info, warn};
 
+use crate::filter::{OutputUnits, StreamingDeconvolver};
 use crate::forward::should_forward_channel;
+use crate::parser::stationxml::{infer_input_units, ChannelResponse};
 use crate::parser::TraceSegment;
 use crate::settings::RsamSettings;
 
@@ -88,6 +90,9 @@ pub struct RsamManager {
     matched_channel: String,
     sensitivity: Option<f64>,
     sensitivity_map: HashMap<String, f64>,
+    response_map: HashMap<String, ChannelResponse>,
+    /// Spectral deconvolver, used when a unit conversion or full response is needed
+    deconvolver: Option<StreamingDeconvolver>,
     warm: bool,
 }
 
@@ -134,10 +139,18 @@ impl RsamManager {
             matched_channel: String::new(),
             sensitivity: None,
             sensitivity_map,
+            response_map: HashMap::new(),
+            deconvolver: None,
             warm: false,
         })
     }
 
+    /// Use full instrument responses (poles/zeros) for deconvolution.
+    pub fn with_responses(mut self, response_map: HashMap<String, ChannelResponse>) -> Self {
+        self.response_map = response_map;
+        self
+    }
+
     pub fn process_segment(&mut self, segment: &TraceSegment) {
         // Channel suffix matching
         if !should_forward_channel(
@@ -153,41 +166,45 @@ impl RsamManager {
             self.matched_channel = segment.channel.clone();
             self.sensitivity = self.sensitivity_map.get(&segment.channel).copied();
 
-            // CHAN mode: resolve units based on channel prefix
-            if self.settings.units.to_uppercase() == "CHAN" {
-                let ch_upper = segment.channel.to_uppercase();
-                if ch_upper.starts_with("EH") {
-                    // Geophone → VEL
-                } else if ch_upper.starts_with("EN") {
-                    // Accelerometer → ACC
+            // Integrate/differentiate in the frequency domain when the requested
+            // units differ from the sensor's native units (CHAN keeps them).
+            if self.settings.deconvolve {
+                let units = OutputUnits::parse(&self.settings.units);
+                let response = self.response_map.get(&segment.channel).cloned().or_else(|| {
+                    self.sensitivity
+                        .filter(|&s| s > 0.0)
+                        .map(|s| ChannelResponse::from_sensitivity(s, infer_input_units(&segment.channel)))
+                });
+                if let Some(response) = response {
+                    if !response.poles.is_empty() || units.derivative_order(&response.input_units) != 0 {
+                        let fs = segment.sampling_rate;
+                        let pre_filt = [0.1, 0.6, 0.95 * fs, fs];
+                        self.deconvolver = Some(
+                            StreamingDeconvolver::new(response, fs, pre_filt, 4.5).with_units(units),
+                        );
+                    }
                 }
-                // For CHAN mode, we just use sample/sensitivity regardless
             }
 
             self.warm = true;
         }
 
         // Accumulate absolute amplitude samples (with optional deconvolution)
-        let deconvolve = self.settings.deconvolve;
-        let units_upper = self.settings.units.to_uppercase();
-        let sensitivity = self.sensitivity;
-
-        for &sample in &segment.samples {
-            let converted = if deconvolve {
-                if let Some(sens) = sensitivity {
-                    let base = sample / sens;
-                    if units_upper == "GRAV" {
-                        base / 9.81
-                    } else {
-                        base
-                    }
-                } else {
-                    sample // Fallback to raw counts
-                }
-            } else {
-                sample // Raw counts
-            };
-            self.buffer.push(converted.abs());
+        if let Some(deconvolver) = self.deconvolver.as_mut() {
+            let converted = deconvolver.process(&segment.samples);
+            // Match rsudp: demean the deconvolved signal before taking amplitudes
+            let mean = converted.iter().sum::<f64>() / converted.len().max(1) as f64;
+            self.buffer.extend(converted.iter().map(|s| (s - mean).abs()));
+        } else {
+            let sensitivity = if self.settings.deconvolve { self.sensitivity } else { None };
+            let scale = OutputUnits::parse(&self.settings.units).scale();
+            for &sample in &segment.samples {
+                let converted = match sensitivity {
+                    Some(sens) => sample / sens * scale,
+                    None => sample, // Raw counts
+                };
+                self.buffer.push(converted.abs());
+            }
         }
 
         // Check if interval has elapsed
@@ -1,4 +1,7 @@
+use crate::detector::DetectorKind;
+use crate::filter::OutputUnits;
 use crate::hue::config::HueConfig;
+use crate::trigger::TriggerConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use config::{Config, ConfigError, Environment, File};
@@ -45,6 +48,30 @@ pub struct Settings {
     pub pubsub: PubsubSettings,
     #[serde(alias = "CAPTURE")]
     pub capture: CaptureSettings,
+    #[serde(alias = "COINCIDENCE")]
+    pub coincidence: CoincidenceSettings,
+    #[serde(alias = "DISTANCE")]
+    pub distance: DistanceSettings,
+    #[serde(alias = "MAGNITUDE")]
+    pub magnitude: MagnitudeSettings,
+    #[serde(alias = "GROUND_MOTION")]
+    pub ground_motion: GroundMotionSettings,
+    #[serde(alias = "EEW")]
+    pub eew: EewSettings,
+    #[serde(alias = "CONDITIONING")]
+    pub conditioning: ConditioningSettings,
+    #[serde(alias = "ADAPTIVE")]
+    pub adaptive: AdaptiveSettings,
+    #[serde(alias = "MAINTENANCE")]
+    pub maintenance: MaintenanceSettings,
+    #[serde(alias = "EVENT")]
+    pub event: EventSettings,
+    #[serde(alias = "CATALOG")]
+    pub catalog: CatalogSettings,
+    #[serde(alias = "INTENSITY")]
+    pub intensity: IntensitySettings,
+    #[serde(alias = "REALTIME_INTENSITY")]
+    pub realtime_intensity: RealtimeIntensitySettings,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -157,6 +184,11 @@ pub struct AlertSettings {
     pub highpass: f64,
     #[serde(alias = "LOWPASS")]
     pub lowpass: f64,
+    #[serde(alias = "CORNERS")]
+    pub corners: usize,
+    /// Detection algorithm: "recursive" (rsudp), "classic", "delayed", "zdetect" or "amplitude"
+    #[serde(alias = "DETECTOR")]
+    pub detector: String,
     #[serde(alias = "DECONVOLVE")]
     pub deconvolve: bool,
     #[serde(alias = "UNITS")]
@@ -167,6 +199,131 @@ pub struct AlertSettings {
     pub on_plot_end_line_color: String,
     #[serde(alias = "ON_PLOT_START_LINE_COLOR")]
     pub on_plot_start_line_color: String,
+    /// Named trigger definitions. When empty, the single trigger described by the
+    /// fields above is used under the name "default".
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<NamedTrigger>,
+}
+
+/// One `[[alert.triggers]]` entry, e.g. a sensitive EHZ trigger for local events
+/// alongside a strong-motion ENZ trigger. Unset fields inherit the `[alert]` values.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct NamedTrigger {
+    #[serde(alias = "NAME")]
+    pub name: String,
+    #[serde(alias = "CHANNEL")]
+    pub channel: Option<String>,
+    #[serde(alias = "STA")]
+    pub sta: Option<f64>,
+    #[serde(alias = "LTA")]
+    pub lta: Option<f64>,
+    #[serde(alias = "DURATION")]
+    pub duration: Option<f64>,
+    #[serde(alias = "THRESHOLD")]
+    pub threshold: Option<f64>,
+    #[serde(alias = "RESET")]
+    pub reset: Option<f64>,
+    #[serde(alias = "HIGHPASS")]
+    pub highpass: Option<f64>,
+    #[serde(alias = "LOWPASS")]
+    pub lowpass: Option<f64>,
+    #[serde(alias = "CORNERS")]
+    pub corners: Option<usize>,
+    #[serde(alias = "DETECTOR")]
+    pub detector: Option<String>,
+    #[serde(alias = "DECONVOLVE")]
+    pub deconvolve: Option<bool>,
+    #[serde(alias = "UNITS")]
+    pub units: Option<String>,
+}
+
+/// One independent STA/LTA trigger with every field resolved.
+#[derive(Debug, Clone)]
+pub struct TriggerDefinition {
+    pub name: String,
+    pub channel: String,
+    pub sta: f64,
+    pub lta: f64,
+    pub duration: f64,
+    pub threshold: f64,
+    pub reset: f64,
+    pub highpass: f64,
+    pub lowpass: f64,
+    pub corners: usize,
+    pub detector: String,
+    /// Trigger on physical `units` (DISP/VEL/ACC/GRAV/CHAN) instead of counts
+    pub deconvolve: bool,
+    pub units: String,
+}
+
+impl AlertSettings {
+    /// The configured trigger definitions, falling back to the legacy single trigger.
+    pub fn trigger_definitions(&self) -> Vec<TriggerDefinition> {
+        if !self.triggers.is_empty() {
+            return self
+                .triggers
+                .iter()
+                .map(|t| TriggerDefinition {
+                    name: t.name.clone(),
+                    channel: t.channel.clone().unwrap_or_else(|| self.channel.clone()),
+                    sta: t.sta.unwrap_or(self.sta),
+                    lta: t.lta.unwrap_or(self.lta),
+                    duration: t.duration.unwrap_or(self.duration),
+                    threshold: t.threshold.unwrap_or(self.threshold),
+                    reset: t.reset.unwrap_or(self.reset),
+                    highpass: t.highpass.unwrap_or(self.highpass),
+                    lowpass: t.lowpass.unwrap_or(self.lowpass),
+                    corners: t.corners.unwrap_or(self.corners),
+                    detector: t.detector.clone().unwrap_or_else(|| self.detector.clone()),
+                    deconvolve: t.deconvolve.unwrap_or(self.deconvolve),
+                    units: t.units.clone().unwrap_or_else(|| self.units.clone()),
+                })
+                .collect();
+        }
+        vec![TriggerDefinition {
+            name: "default".to_string(),
+            channel: self.channel.clone(),
+            sta: self.sta,
+            lta: self.lta,
+            duration: self.duration,
+            threshold: self.threshold,
+            reset: self.reset,
+            highpass: self.highpass,
+            lowpass: self.lowpass,
+            corners: self.corners,
+            detector: self.detector.clone(),
+            deconvolve: self.deconvolve,
+            units: self.units.clone(),
+        }]
+    }
+}
+
+impl TriggerDefinition {
+    /// The trigger parameters, or an error naming an unknown detector or units.
+    pub fn to_config(&self) -> Result<TriggerConfig, String> {
+        Ok(TriggerConfig {
+            name: self.name.clone(),
+            sta_sec: self.sta,
+            lta_sec: self.lta,
+            threshold: self.threshold,
+            reset_threshold: self.reset,
+            highpass: self.highpass,
+            lowpass: self.lowpass,
+            corners: self.corners,
+            target_channel: self.channel.clone(),
+            duration: self.duration,
+            detector: DetectorKind::try_parse(&self.detector).ok_or_else(|| format!("unknown detector '{}'", self.detector))?,
+            deconvolve: self.deconvolve,
+            units: OutputUnits::try_parse(&self.units).ok_or_else(|| format!("unknown units '{}'", self.units))?,
+        })
+    }
+}
+
+/// True when a notifier's `triggers` list routes alerts from `trigger_name` to it.
+/// An empty list accepts every trigger.
+pub fn routes_trigger(triggers: &[String], trigger_name: &str) -> bool {
+    triggers.is_empty() || triggers.iter().any(|t| t == trigger_name)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -180,6 +337,9 @@ pub struct AlertSoundSettings {
     pub default_reset_file: String,
     #[serde(alias = "INTENSITY_FILES")]
     pub intensity_files: std::collections::BTreeMap<String, String>,
+    /// Trigger names routed to this notifier (empty = all)
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
 }
 
 impl Default for AlertSoundSettings {
@@ -192,6 +352,7 @@ impl Default for AlertSoundSettings {
             trigger_file: "".to_string(),
             default_reset_file: "".to_string(),
             intensity_files,
+            triggers: Vec::new(),
         }
     }
 }
@@ -266,6 +427,9 @@ pub struct GoogleChatSettings {
     pub aws_access_key_id: Option<String>,
     #[serde(alias = "AWS_SECRET_ACCESS_KEY")]
     pub aws_secret_access_key: Option<String>,
+    /// Trigger names routed to this notifier (empty = all)
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -281,6 +445,9 @@ pub struct DiscordSettings {
     pub send_images: bool,
     #[serde(alias = "EXTRA_TEXT")]
     pub extra_text: String,
+    /// Trigger names routed to this notifier (empty = all)
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -298,6 +465,9 @@ pub struct SnsSettings {
     pub aws_region: String,
     #[serde(alias = "EXTRA_TEXT")]
     pub extra_text: String,
+    /// Trigger names routed to this notifier (empty = all)
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -325,6 +495,9 @@ pub struct LineSettings {
     pub aws_access_key_id: Option<String>,
     #[serde(alias = "AWS_SECRET_ACCESS_KEY")]
     pub aws_secret_access_key: Option<String>,
+    /// Trigger names routed to this notifier (empty = all)
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -365,6 +538,289 @@ pub struct RsamSettings {
     pub units: String,
 }
 
+/// Network coincidence trigger: declare an event only when enough stations (or
+/// channels) trigger within `window` seconds of each other.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct CoincidenceSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Trigger name carried by the network-level alert
+    #[serde(alias = "NAME")]
+    pub name: String,
+    /// Minimum summed weight of triggered stations/channels (ObsPy `thr_coincidence_sum`)
+    #[serde(alias = "MIN_SUM")]
+    pub min_sum: f64,
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    /// "station" counts each NET.STA once; "channel" counts every NET.STA.LOC.CHA
+    #[serde(alias = "GROUP_BY")]
+    pub group_by: String,
+    /// Per station or channel weights (NET.STA.LOC.CHA, NET.STA or STA keys); default 1.0
+    #[serde(alias = "WEIGHTS", default)]
+    pub weights: std::collections::BTreeMap<String, f64>,
+}
+
+/// S-phase picking on the horizontals after a P pick, and the S-P distance estimate.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct DistanceSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Horizontal channels searched for the S phase (suffix match)
+    #[serde(alias = "CHANNELS", default)]
+    pub channels: Vec<String>,
+    /// P and S velocities of the homogeneous velocity model (km/s)
+    #[serde(alias = "VP")]
+    pub vp: f64,
+    #[serde(alias = "VS")]
+    pub vs: f64,
+    /// Seconds after the P pick searched for the S phase
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    #[serde(alias = "HIGHPASS")]
+    pub highpass: f64,
+    #[serde(alias = "LOWPASS")]
+    pub lowpass: f64,
+}
+
+/// Local magnitude from simulated Wood-Anderson amplitudes on the horizontals.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct MagnitudeSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// "hutton_boore", or "regional" for `ML = log10(A) + a·log10(R) + b·R + c`
+    #[serde(alias = "CORRECTION")]
+    pub correction: String,
+    #[serde(alias = "A")]
+    pub a: f64,
+    #[serde(alias = "B")]
+    pub b: f64,
+    #[serde(alias = "C")]
+    pub c: f64,
+    /// Fixed hypocentral distance (km); 0 uses the S-P distance
+    #[serde(alias = "DISTANCE_KM")]
+    pub distance_km: f64,
+}
+
+/// Peak ground acceleration, velocity and displacement between trigger and reset.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct GroundMotionSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Channels measured (suffix match); components of one instrument form the vector sum
+    #[serde(alias = "CHANNELS", default)]
+    pub channels: Vec<String>,
+    /// Seconds of data before the trigger included in the record
+    #[serde(alias = "PRE_TRIGGER")]
+    pub pre_trigger: f64,
+    /// High-pass corner of the restitution pre-filter (Hz)
+    #[serde(alias = "HIGHPASS")]
+    pub highpass: f64,
+    /// Periods of the 5%-damped response spectrum of accelerometer channels (s); empty = none
+    #[serde(alias = "PERIODS", default)]
+    pub periods: Vec<f64>,
+}
+
+/// On-site early warning (τc, Pd) from the first seconds after a P pick.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct EewSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Vertical channels, in order of preference (suffix match)
+    #[serde(alias = "CHANNELS", default)]
+    pub channels: Vec<String>,
+    /// Seconds of P wave analysed
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    /// High-pass corner applied to displacement and velocity (Hz)
+    #[serde(alias = "HIGHPASS")]
+    pub highpass: f64,
+}
+
+/// Components used for the JMA seismic intensity.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct IntensitySettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Vertical channel (suffix match)
+    #[serde(alias = "VERTICAL")]
+    pub vertical: String,
+    /// The two horizontal channels (N/E or 1/2), or none for a vertical-only station
+    #[serde(alias = "HORIZONTALS", default)]
+    pub horizontals: Vec<String>,
+    /// Azimuths of the horizontal channels (degrees clockwise from north)
+    #[serde(alias = "AZIMUTHS", default)]
+    pub azimuths: Vec<f64>,
+    /// "auto" (from the response or the SEED instrument code), "acceleration" or "velocity"
+    #[serde(alias = "INPUT")]
+    pub input: String,
+    /// Reporting scale: "jma", "mmi" (Worden 2012), "cwa" (Taiwan 2020) or "csis" (China)
+    #[serde(alias = "SCALE")]
+    pub scale: String,
+}
+
+/// Continuous real-time JMA intensity on the `[intensity]` components, optionally
+/// used as a trigger source.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct RealtimeIntensitySettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Raise alerts from the real-time intensity
+    #[serde(alias = "TRIGGER")]
+    pub trigger: bool,
+    /// Trigger name used for alerts, events and sound/Hue routing
+    #[serde(alias = "NAME")]
+    pub name: String,
+    /// Intensity that raises an alert
+    #[serde(alias = "THRESHOLD")]
+    pub threshold: f64,
+    /// Intensity below which the alert resets
+    #[serde(alias = "RESET")]
+    pub reset: f64,
+    /// Lookback for the 0.3 s exceedance level (s)
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    /// WebSocket updates per second
+    #[serde(alias = "RATE")]
+    pub rate: f64,
+}
+
+/// Reference earthquake catalog used to label detections and score the triggers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct CatalogSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// QuakeML or CSV file, checked for changes every minute
+    #[serde(alias = "PATH")]
+    pub path: String,
+    /// Station coordinates (degrees)
+    #[serde(alias = "LATITUDE")]
+    pub latitude: f64,
+    #[serde(alias = "LONGITUDE")]
+    pub longitude: f64,
+    /// P velocity of the homogeneous model for theoretical arrivals (km/s)
+    #[serde(alias = "VP")]
+    pub vp: f64,
+    /// Largest difference between detection and predicted P arrival (s)
+    #[serde(alias = "TOLERANCE")]
+    pub tolerance: f64,
+    /// Catalog events below this magnitude are ignored
+    #[serde(alias = "MIN_MAGNITUDE")]
+    pub min_magnitude: f64,
+    /// Catalog events farther than this are ignored (km, 0 = no limit)
+    #[serde(alias = "MAX_DISTANCE")]
+    pub max_distance: f64,
+}
+
+/// Association of triggers from several channels and trigger definitions into events.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct EventSettings {
+    /// When disabled every alert is its own event
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// A trigger joins an open event whose latest trigger is at most this old (s)
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+}
+
+/// Recurring maintenance windows during which alerts are recorded but not announced.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct MaintenanceSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Offset of local time from UTC (hours) for the schedules
+    #[serde(alias = "UTC_OFFSET")]
+    pub utc_offset: f64,
+    #[serde(alias = "WINDOWS", default)]
+    pub windows: Vec<MaintenanceWindowSettings>,
+}
+
+/// A window of `duration` minutes starting whenever the cron expression fires.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase", default)]
+pub struct MaintenanceWindowSettings {
+    /// minute hour day-of-month month day-of-week, e.g. "0 8 * * 1-5"
+    #[serde(alias = "CRON")]
+    pub cron: String,
+    #[serde(alias = "DURATION")]
+    pub duration: f64,
+    #[serde(alias = "REASON")]
+    pub reason: String,
+}
+
+/// Trigger and reset thresholds scaled with the background noise.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct AdaptiveSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// "rms" (rolling level over `window`) or "hourly" (`percentile` of the levels of
+    /// `window` blocks learned for each hour of day)
+    #[serde(alias = "MODE")]
+    pub mode: String,
+    /// Percentile of an hour's block levels used as its noise in "hourly" mode
+    #[serde(alias = "PERCENTILE")]
+    pub percentile: f64,
+    /// Seconds of quiet data in the rolling noise level
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    /// Time constant of the long-term and hourly noise levels (days)
+    #[serde(alias = "LEARNING_DAYS")]
+    pub learning_days: f64,
+    /// Noise RMS (trigger units) at which the configured thresholds apply; 0 uses the long-term level
+    #[serde(alias = "REFERENCE_NOISE")]
+    pub reference_noise: f64,
+    /// Bounds on the factor applied to `threshold` and `reset`
+    #[serde(alias = "MIN_SCALE")]
+    pub min_scale: f64,
+    #[serde(alias = "MAX_SCALE")]
+    pub max_scale: f64,
+    /// Offset of local time from UTC (hours), for the hour-of-day levels
+    #[serde(alias = "UTC_OFFSET")]
+    pub utc_offset: f64,
+    /// Trigger names to adapt; empty adapts every trigger
+    #[serde(alias = "TRIGGERS", default)]
+    pub triggers: Vec<String>,
+}
+
+/// Spike and DC-step removal ahead of the triggers, and clipping detection.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub struct ConditioningSettings {
+    #[serde(alias = "ENABLED")]
+    pub enabled: bool,
+    /// Seconds of preceding data used for the median/MAD baseline
+    #[serde(alias = "WINDOW")]
+    pub window: f64,
+    /// Spike threshold in MAD-derived standard deviations
+    #[serde(alias = "SPIKE_THRESHOLD")]
+    pub spike_threshold: f64,
+    /// Widest outlier run (samples) still treated as a spike
+    #[serde(alias = "SPIKE_WIDTH")]
+    pub spike_width: usize,
+    /// Absolute count level regarded as clipped; 0 disables clipping detection
+    #[serde(alias = "CLIP_LEVEL")]
+    pub clip_level: f64,
+    /// Consecutive clipped samples needed to report clipping
+    #[serde(alias = "CLIP_SAMPLES")]
+    pub clip_samples: usize,
+    /// Level shift, in standard deviations, removed as a DC step; 0 disables step removal
+    #[serde(alias = "STEP_THRESHOLD")]
+    pub step_threshold: f64,
+    /// Samples the new level must hold steady for a shift to count as a DC step
+    #[serde(alias = "STEP_SAMPLES")]
+    pub step_samples: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub struct PubsubSettings {
@@ -494,11 +950,14 @@ impl Default for AlertSettings {
             reset: 0.5,
             highpass: 0.1,
             lowpass: 2.0,
+            corners: 4,
+            detector: "recursive".to_string(),
             deconvolve: false,
             units: "VEL".to_string(),
             on_plot: false,
             on_plot_end_line_color: "#D72638".to_string(),
             on_plot_start_line_color: "#4C8BF5".to_string(),
+            triggers: Vec::new(),
         }
     }
 }
@@ -553,6 +1012,7 @@ impl Default for GoogleChatSettings {
             s3_upload_timeout_seconds: 3,
             aws_access_key_id: None,
             aws_secret_access_key: None,
+            triggers: Vec::new(),
         }
     }
 }
@@ -565,6 +1025,7 @@ impl Default for DiscordSettings {
             use_embed: true,
             send_images: true,
             extra_text: "".to_string(),
+            triggers: Vec::new(),
         }
     }
 }
@@ -578,6 +1039,7 @@ impl Default for SnsSettings {
             aws_secret_access_key: None,
             aws_region: "n/a".to_string(),
             extra_text: "".to_string(),
+            triggers: Vec::new(),
         }
     }
 }
@@ -596,6 +1058,7 @@ impl Default for LineSettings {
             s3_upload_timeout_seconds: 3,
             aws_access_key_id: None,
             aws_secret_access_key: None,
+            triggers: Vec::new(),
         }
     }
 }
@@ -628,6 +1091,191 @@ impl Default for RsamSettings {
     }
 }
 
+impl Default for CoincidenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: "coincidence".to_string(),
+            min_sum: 2.0,
+            window: 10.0,
+            group_by: "station".to_string(),
+            weights: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for DistanceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channels: vec!["EHN".to_string(), "EHE".to_string(), "ENN".to_string(), "ENE".to_string()],
+            vp: 6.0,
+            vs: 3.5,
+            window: 40.0,
+            highpass: 1.0,
+            lowpass: 10.0,
+        }
+    }
+}
+
+impl MagnitudeSettings {
+    /// Check the distance correction, which must be one `local_magnitude` knows.
+    pub fn validate(&self) -> Result<(), String> {
+        if !["hutton_boore", "regional"].iter().any(|c| self.correction.eq_ignore_ascii_case(c)) {
+            return Err(format!("unknown correction '{}' (expected \"hutton_boore\" or \"regional\")", self.correction));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MagnitudeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            correction: "hutton_boore".to_string(),
+            a: 1.11,
+            b: 0.00189,
+            c: 0.591,
+            distance_km: 0.0,
+        }
+    }
+}
+
+impl Default for IntensitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            vertical: "ENZ".to_string(),
+            horizontals: vec!["ENN".to_string(), "ENE".to_string()],
+            azimuths: vec![0.0, 90.0],
+            input: "auto".to_string(),
+            scale: "jma".to_string(),
+        }
+    }
+}
+
+impl Default for RealtimeIntensitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trigger: true,
+            name: "realtime_intensity".to_string(),
+            threshold: 2.5,
+            reset: 1.5,
+            window: 60.0,
+            rate: 10.0,
+        }
+    }
+}
+
+impl Default for CatalogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            latitude: 0.0,
+            longitude: 0.0,
+            vp: 6.0,
+            tolerance: 10.0,
+            min_magnitude: 0.0,
+            max_distance: 0.0,
+        }
+    }
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self { enabled: true, window: 30.0 }
+    }
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self { enabled: true, utc_offset: 0.0, windows: Vec::new() }
+    }
+}
+
+impl Default for MaintenanceWindowSettings {
+    fn default() -> Self {
+        Self { cron: String::new(), duration: 60.0, reason: "maintenance".to_string() }
+    }
+}
+
+impl AdaptiveSettings {
+    /// Check the mode, percentile and scale bounds, which are applied to every packet.
+    pub fn validate(&self) -> Result<(), String> {
+        if !["rms", "hourly"].iter().any(|m| self.mode.eq_ignore_ascii_case(m)) {
+            return Err(format!("unknown mode '{}' (expected \"rms\" or \"hourly\")", self.mode));
+        }
+        if !(0.0..=100.0).contains(&self.percentile) {
+            return Err(format!("percentile must be between 0 and 100 (got {})", self.percentile));
+        }
+        if !self.min_scale.is_finite() || !self.max_scale.is_finite() {
+            return Err(format!("min_scale ({}) and max_scale ({}) must be finite", self.min_scale, self.max_scale));
+        }
+        if self.min_scale <= 0.0 || self.min_scale > self.max_scale {
+            return Err(format!("expected 0 < min_scale <= max_scale, got {} and {}", self.min_scale, self.max_scale));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AdaptiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: "rms".to_string(),
+            percentile: 50.0,
+            window: 600.0,
+            learning_days: 7.0,
+            reference_noise: 0.0,
+            min_scale: 0.8,
+            max_scale: 3.0,
+            utc_offset: 0.0,
+            triggers: Vec::new(),
+        }
+    }
+}
+
+impl Default for ConditioningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 1.0,
+            spike_threshold: 10.0,
+            spike_width: 3,
+            // Raspberry Shake digitizers are 24-bit
+            clip_level: 8_388_607.0,
+            clip_samples: 3,
+            step_threshold: 10.0,
+            step_samples: 10,
+        }
+    }
+}
+
+impl Default for EewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channels: vec!["ENZ".to_string(), "EHZ".to_string()],
+            window: 3.0,
+            highpass: 0.075,
+        }
+    }
+}
+
+impl Default for GroundMotionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channels: ["ENZ", "ENN", "ENE", "EHZ", "EHN", "EHE"].iter().map(|c| c.to_string()).collect(),
+            pre_trigger: 10.0,
+            highpass: 0.1,
+            periods: vec![0.1, 0.2, 0.3, 0.5, 0.7, 1.0, 1.5, 2.0, 3.0, 5.0],
+        }
+    }
+}
+
 impl Settings {
     pub fn new(config_path: Option<PathBuf>) -> Result<Self, ConfigError> {
         let mut builder = Config::builder();
@@ -669,7 +1317,7 @@ impl Settings {
         // T009: Detect unknown fields
         if let Ok(table) = config.clone().try_deserialize::<serde_json::Value>() {
             if let Some(map) = table.as_object() {
-                let known_sections = ["settings", "printdata", "write", "plot", "forward", "alert", "alertsound", "custom", "tweets", "telegram", "googlechat", "discord", "sns", "line", "bluesky", "rsam", "hue", "pubsub", "capture"];
+                let known_sections = ["settings", "printdata", "write", "plot", "forward", "alert", "alertsound", "custom", "tweets", "telegram", "googlechat", "discord", "sns", "line", "bluesky", "rsam", "hue", "pubsub", "capture", "coincidence", "distance", "magnitude", "ground_motion", "eew", "conditioning", "adaptive", "maintenance", "event", "catalog", "intensity", "realtime_intensity"];
                 for key in map.keys() {
                     let lower_key = key.to_lowercase();
                     if !known_sections.contains(&lower_key.as_str()) {
@@ -679,7 +1327,25 @@ impl Settings {
             }
         }
 
-        config.try_deserialize()
+        let settings: Settings = config.try_deserialize()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Reject values that would fail at run time.
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.adaptive.validate().map_err(|e| ConfigError::Message(format!("[adaptive] {}", e)))?;
+        self.magnitude.validate().map_err(|e| ConfigError::Message(format!("[magnitude] {}", e)))?;
+        let triggers = self.alert.trigger_definitions();
+        for (i, t) in triggers.iter().enumerate() {
+            if triggers[..i].iter().any(|other| other.name == t.name) {
+                return Err(ConfigError::Message(format!("[alert] duplicate trigger name '{}'", t.name)));
+            }
+            t.to_config()
+                .and_then(|config| config.validate())
+                .map_err(|e| ConfigError::Message(format!("[alert] trigger '{}': {}", t.name, e)))?;
+        }
+        Ok(())
     }
 
     pub fn dump(&self, format: &str) -> Result<String, Box<dyn std::error::Error>> {
@@ -718,6 +1384,70 @@ mod tests {
         assert_eq!(settings.plot.duration, 90);
     }
 
+    #[test]
+    fn test_invalid_adaptive_bounds_are_rejected() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[adaptive]\nmin_scale = 3.0\nmax_scale = 0.8\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+
+        let err = Settings::new(Some(config_path)).unwrap_err();
+        assert!(err.to_string().contains("[adaptive]"), "{}", err);
+        assert!(AdaptiveSettings { max_scale: f64::NAN, ..Default::default() }.validate().is_err());
+        assert!(AdaptiveSettings { mode: "percentile".to_string(), ..Default::default() }.validate().is_err());
+        assert!(AdaptiveSettings { mode: "Hourly".to_string(), percentile: 90.0, ..Default::default() }.validate().is_ok());
+        assert!(AdaptiveSettings { percentile: 101.0, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_unknown_magnitude_correction_is_rejected() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[magnitude]\ncorrection = \"hutton-boore\"\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+
+        let err = Settings::new(Some(config_path)).unwrap_err();
+        assert!(err.to_string().contains("[magnitude] unknown correction 'hutton-boore'"), "{}", err);
+        assert!(MagnitudeSettings { correction: "Regional".to_string(), ..Default::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_unknown_detector_is_rejected() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[[alert.triggers]]\nname = \"local\"\ndetector = \"clasic\"\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+
+        let err = Settings::new(Some(config_path)).unwrap_err();
+        assert!(err.to_string().contains("trigger 'local': unknown detector 'clasic'"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_units_are_rejected() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[alert]\nunits = \"VELO\"\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+
+        let err = Settings::new(Some(config_path)).unwrap_err();
+        assert!(err.to_string().contains("trigger 'default': unknown units 'VELO'"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_triggers_are_rejected() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[[alert.triggers]]\nname = \"local\"\nsta = 40.0\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+        let err = Settings::new(Some(config_path.clone())).unwrap_err();
+        assert!(err.to_string().contains("trigger 'local': lta"), "{}", err);
+
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(file, "[[alert.triggers]]\nname = \"local\"\n\n[[alert.triggers]]\nname = \"local\"\nchannel = \"ENZ\"\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]").unwrap();
+        let err = Settings::new(Some(config_path)).unwrap_err();
+        assert!(err.to_string().contains("duplicate trigger name 'local'"), "{}", err);
+    }
+
     #[test]
     fn test_load_yaml() {
         let dir = tempdir().unwrap();
@@ -730,6 +1460,47 @@ mod tests {
         assert_eq!(settings.settings.station, "YAML1");
     }
 
+    #[test]
+    fn test_load_trigger_definitions() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("settings.toml");
+        let mut file = StdFile::create(&config_path).unwrap();
+        writeln!(
+            file,
+            "[alert]\nsta = 4.0\ndetector = \"classic\"\n\n\
+             [[alert.triggers]]\nname = \"local\"\nchannel = \"EHZ\"\nthreshold = 1.7\n\n\
+             [[alert.triggers]]\nname = \"strong\"\nchannel = \"ENZ\"\nhighpass = 0.5\nlowpass = 10.0\ndetector = \"amplitude\"\n\n\
+             [discord]\ntriggers = [\"strong\"]\n\n[hue]\ntarget_ids = []\n\n[alertsound.intensity_files]"
+        )
+        .unwrap();
+
+        let settings = Settings::new(Some(config_path)).unwrap();
+        let triggers = settings.alert.trigger_definitions();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].name, "local");
+        assert_eq!(triggers[0].threshold, 1.7);
+        // Unset fields inherit the [alert] values
+        assert_eq!(triggers[0].sta, 4.0);
+        assert_eq!(triggers[1].sta, 4.0);
+        assert_eq!(triggers[0].lta, 30.0);
+        assert_eq!(triggers[1].channel, "ENZ");
+        assert_eq!(triggers[1].lowpass, 10.0);
+        assert_eq!(triggers[1].detector, "amplitude");
+        assert_eq!(triggers[0].detector, "classic");
+
+        assert!(!routes_trigger(&settings.discord.triggers, "local"));
+        assert!(routes_trigger(&settings.discord.triggers, "strong"));
+        assert!(routes_trigger(&settings.line.triggers, "local"));
+    }
+
+    #[test]
+    fn test_legacy_single_trigger() {
+        let triggers = Settings::default().alert.trigger_definitions();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].name, "default");
+        assert_eq!(triggers[0].channel, "HZ");
+    }
+
     #[test]
     fn test_dump_toml() {
         let settings = Settings::default();
@@ -1,13 +1,23 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::adaptive::{EffectiveThreshold, NoiseTracker};
+use crate::detector::{CharacteristicFunction, Detector, DetectorKind};
+use crate::eew::EarlyWarning;
+use crate::filter::{butter_bandpass_sos, deconvolve_response_to, OutputUnits};
+use crate::parser::stationxml::{infer_input_units, ChannelResponse};
+use crate::picker::{pick_phase, PhasePick, PICK_WINDOW_SECONDS};
+use crate::settings::AdaptiveSettings;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum AlertEventType {
     Trigger,
     Reset,
     Status,
+    /// Early warning estimate from the first seconds of P, before the reset
+    Early,
 }
 
 #[derive(Debug, Clone)]
@@ -30,23 +40,22 @@ impl Biquad {
     }
 }
 
-pub fn butter_bandpass_sos(_order: usize, _low_freq: f64, _high_freq: f64, _fs: f64) -> Vec<Biquad> {
-    vec![
-        Biquad { b0: 1.091166705330671136e-05, b1: 2.182333410661342271e-05, b2: 1.091166705330671136e-05, a1: -1.799856289596911019e+00, a2: 8.118007230490338344e-01, s1:0.0, s2:0.0 },
-        Biquad { b0: 1.000000000000000000e+00, b1: 2.000000000000000000e+00, b2: 1.000000000000000000e+00, a1: -1.902151139520082967e+00, a2: 9.168966689551941718e-01, s1:0.0, s2:0.0 },
-        Biquad { b0: 1.000000000000000000e+00, b1: -2.000000000000000000e+00, b2: 1.000000000000000000e+00, a1: -1.987573235639373159e+00, a2: 9.876201932101479342e-01, s1:0.0, s2:0.0 },
-        Biquad { b0: 1.000000000000000000e+00, b1: -2.000000000000000000e+00, b2: 1.000000000000000000e+00, a1: -1.995513812922410590e+00, a2: 9.955541951492401509e-01, s1:0.0, s2:0.0 },
-    ]
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertEvent {
     pub timestamp: DateTime<Utc>,
     pub channel: String,
+    /// Name of the trigger definition that produced this event
+    pub trigger_name: String,
     pub event_type: AlertEventType,
     pub ratio: f64,
     pub max_ratio: f64,
     pub message: String,
+    /// Refined P arrival (AIC), set on TRIGGER events
+    #[serde(default)]
+    pub pick: Option<PhasePick>,
+    /// τc/Pd estimate, set on EARLY events
+    #[serde(default)]
+    pub early_warning: Option<EarlyWarning>,
 }
 
 impl std::fmt::Display for AlertEvent {
@@ -55,41 +64,131 @@ impl std::fmt::Display for AlertEvent {
             AlertEventType::Trigger => "ALARM",
             AlertEventType::Reset => "RESET",
             AlertEventType::Status => "STATUS",
+            AlertEventType::Early => "EARLY",
         };
         f.write_str("[")?;
         f.write_str(&self.timestamp.to_rfc3339())?;
         f.write_str("] ")?;
         f.write_str(&self.channel)?;
-        f.write_str(": ")?;
+        f.write_str(" [")?;
+        f.write_str(&self.trigger_name)?;
+        f.write_str("]: ")?;
         f.write_str(type_str)?;
         
+        if let Some(ew) = &self.early_warning {
+            return write!(f, " ({})", ew);
+        }
         if self.event_type == AlertEventType::Reset {
              // Use format macro directly with correct syntax
              write!(f, " (end ratio: {:.4}, max ratio: {:.4})", self.ratio, self.max_ratio)
         } else {
-             write!(f, " (ratio: {:.4})", self.ratio)
+             write!(f, " (ratio: {:.4})", self.ratio)?;
+             match &self.pick {
+                 Some(pick) => write!(f, " [{}]", pick),
+                 None => Ok(()),
+             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TriggerConfig {
+    /// Trigger definition name, carried on every emitted AlertEvent
+    pub name: String,
     pub sta_sec: f64,
     pub lta_sec: f64,
     pub threshold: f64,
     pub reset_threshold: f64,
     pub highpass: f64,
     pub lowpass: f64,
+    /// Butterworth bandpass order (ObsPy `corners`)
+    pub corners: usize,
     pub target_channel: String,
     pub duration: f64,
+    /// Detection algorithm; `threshold`/`reset_threshold` apply to its characteristic function
+    pub detector: DetectorKind,
+    /// Convert the trigger input to physical `units` before filtering, so thresholds
+    /// can be given in m/s, m/s² etc. rather than counts
+    pub deconvolve: bool,
+    pub units: OutputUnits,
+}
+
+impl TriggerConfig {
+    /// Check that the parameters describe a usable trigger.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if self.target_channel.trim().is_empty() {
+            return Err("channel must not be empty".to_string());
+        }
+        let values = [self.sta_sec, self.lta_sec, self.threshold, self.reset_threshold, self.highpass, self.lowpass, self.duration];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err("parameters must be finite numbers".to_string());
+        }
+        if self.sta_sec <= 0.0 {
+            return Err(format!("sta must be positive (got {})", self.sta_sec));
+        }
+        if self.lta_sec <= self.sta_sec {
+            return Err(format!("lta ({}) must be longer than sta ({})", self.lta_sec, self.sta_sec));
+        }
+        if self.threshold <= 0.0 {
+            return Err(format!("threshold must be positive (got {})", self.threshold));
+        }
+        if self.reset_threshold < 0.0 || self.reset_threshold >= self.threshold {
+            return Err(format!("reset ({}) must be between 0 and the threshold ({})", self.reset_threshold, self.threshold));
+        }
+        if self.highpass < 0.0 || self.lowpass <= self.highpass {
+            return Err(format!("band {}-{} Hz is invalid", self.highpass, self.lowpass));
+        }
+        if !(1..=10).contains(&self.corners) {
+            return Err(format!("corners must be between 1 and 10 (got {})", self.corners));
+        }
+        if self.duration < 0.0 {
+            return Err(format!("duration must not be negative (got {})", self.duration));
+        }
+        Ok(())
+    }
+}
+
+/// Trigger definitions shared with the web API; `revision` increases with every
+/// change so the pipeline knows when to apply them.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerRegistry {
+    pub revision: u64,
+    pub configs: Vec<TriggerConfig>,
 }
 
+/// Nominal Raspberry Shake sample rate, used until a channel reports its own.
+const DEFAULT_SAMPLE_RATE: f64 = 100.0;
+/// rsudp evaluates STA/LTA once per UDP packet (25 samples at 100 SPS).
+const PACKET_SECONDS: f64 = 0.25;
+/// Water level used for response removal, as in rsudp.
+const WATER_LEVEL_DB: f64 = 4.5;
+
 pub struct TriggerManager {
     config: TriggerConfig,
     states: HashMap<String, StaLtaState>,
+    sample_rates: HashMap<String, f64>,
+    /// Instrument responses keyed by channel code, used when `config.deconvolve` is set
+    responses: HashMap<String, ChannelResponse>,
+    /// Characteristic-function segments awaiting `drain_characteristic`, if enabled
+    cf_output: Option<Vec<CharacteristicFunction>>,
+    /// Scale the thresholds with the background noise of each channel
+    adaptive: Option<AdaptiveSettings>,
 }
 
 struct StaLtaState {
+    sample_rate: f64,
+    /// Bandpass sections designed for this channel's sample rate (state zeroed)
+    sos: Vec<Biquad>,
+    /// Response removed from each window before filtering (None: raw counts)
+    response: Option<ChannelResponse>,
+    detector: Box<dyn Detector>,
+    /// Background noise for adaptive thresholds
+    noise: Option<NoiseTracker>,
+    packet_len: usize,
+    win_size: usize,
     triggered: bool,
     max_ratio: f64,
     last_timestamp: Option<DateTime<Utc>>,
@@ -101,29 +200,190 @@ struct StaLtaState {
 
 impl TriggerManager {
     pub fn new(config: TriggerConfig) -> Self {
-        info!("TriggerManager initialized (Windowed STA/LTA Mode with Status).");
-        Self { config, states: HashMap::new() }
+        info!(
+            "TriggerManager '{}' initialized ({:?} detector with Status, channel {}, bandpass {}-{} Hz, {} corners).",
+            config.name, config.detector, config.target_channel, config.highpass, config.lowpass, config.corners
+        );
+        Self { config, states: HashMap::new(), sample_rates: HashMap::new(), responses: HashMap::new(), cf_output: None, adaptive: None }
     }
 
-    pub fn add_sample(&mut self, id: &str, sample: f64, timestamp: DateTime<Utc>, _sensitivity: f64) -> Option<AlertEvent> {
-        if !id.contains(&self.config.target_channel) { return None; }
+    /// Instrument responses (keyed by channel code) for deconvolving the trigger input.
+    /// Channels without one fall back to the sensitivity passed to `add_sample`.
+    pub fn with_responses(mut self, responses: HashMap<String, ChannelResponse>) -> Self {
+        self.responses = responses;
+        self
+    }
+
+    /// Keep the newest characteristic-function values of every evaluation so they
+    /// can be plotted; collect them with `drain_characteristic`.
+    pub fn with_characteristic_output(mut self) -> Self {
+        self.cf_output = Some(Vec::new());
+        self
+    }
 
-        let clean_id = id.rsplit('.').next().unwrap_or(id).trim_matches('\'').trim().to_string();
+    /// Scale the trigger and reset thresholds with the tracked background noise.
+    pub fn with_adaptive(mut self, settings: AdaptiveSettings) -> Self {
+        info!(
+            "Trigger '{}': adaptive thresholds ({} noise, scale {}-{})",
+            self.config.name, settings.mode, settings.min_scale, settings.max_scale
+        );
+        self.adaptive = Some(settings);
+        self
+    }
+
+    pub fn config(&self) -> &TriggerConfig {
+        &self.config
+    }
+
+    /// Apply new parameters without a restart. Each channel keeps its raw window,
+    /// trigger state and noise history, so the STA/LTA needs no new warm-up; only
+    /// toggling deconvolution starts the channels afresh.
+    pub fn reconfigure(&mut self, config: TriggerConfig) {
+        if config == self.config {
+            return;
+        }
+        info!(
+            "TriggerManager '{}' reconfigured: sta={} lta={} threshold={} reset={} band {}-{} Hz, {} corners, channel {}",
+            config.name, config.sta_sec, config.lta_sec, config.threshold, config.reset_threshold,
+            config.highpass, config.lowpass, config.corners, config.target_channel
+        );
+        let restart = config.deconvolve != self.config.deconvolve;
+        self.config = config;
+        if restart {
+            self.states.clear();
+            return;
+        }
+        let target = self.config.target_channel.clone();
+        self.states.retain(|id, _| id.contains(&target));
+        for state in self.states.values_mut() {
+            let mut rebuilt = Self::new_state(&self.config, state.sample_rate, state.response.take(), state.noise.take());
+            rebuilt.raw_buffer = std::mem::take(&mut state.raw_buffer);
+            while rebuilt.raw_buffer.len() > rebuilt.win_size {
+                rebuilt.raw_buffer.pop_front();
+            }
+            rebuilt.sample_count = state.sample_count;
+            rebuilt.triggered = state.triggered;
+            rebuilt.max_ratio = state.max_ratio;
+            rebuilt.last_timestamp = state.last_timestamp;
+            rebuilt.exceed_start = state.exceed_start;
+            rebuilt.is_exceeding = state.is_exceeding;
+            *state = rebuilt;
+        }
+    }
 
-        let highpass = self.config.highpass;
-        let lowpass = self.config.lowpass;
-        let nlta = (self.config.lta_sec * 100.0) as usize;
-        let nsta = (self.config.sta_sec * 100.0) as usize;
+    /// Thresholds currently applied on a channel, once it has been seen.
+    pub fn effective_threshold(&self, id: &str) -> Option<EffectiveThreshold> {
+        let clean_id = Self::clean_id(id);
+        let state = self.states.get(&clean_id)?;
+        let time = state.last_timestamp?;
+        let scale = state.noise.as_ref().map_or(1.0, |n| n.scale(time));
+        Some(EffectiveThreshold {
+            trigger_name: self.config.name.clone(),
+            channel: clean_id,
+            threshold: self.config.threshold * scale,
+            reset: self.config.reset_threshold * scale,
+            noise: state.noise.as_ref().and_then(|n| n.noise(time)),
+            reference: state.noise.as_ref().and_then(|n| n.reference()),
+            scale,
+        })
+    }
+
+    /// Characteristic-function segments produced since the last call.
+    pub fn drain_characteristic(&mut self) -> Vec<CharacteristicFunction> {
+        self.cf_output.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Record the actual sample rate of a channel. The filter and window lengths are
+    /// redesigned (and the channel's buffer restarted) when the rate changes.
+    pub fn set_sample_rate(&mut self, id: &str, sample_rate: f64) {
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return;
+        }
+        let clean_id = Self::clean_id(id);
+        if self.states.get(&clean_id).is_some_and(|s| s.sample_rate != sample_rate) {
+            self.states.remove(&clean_id);
+        }
+        self.sample_rates.insert(clean_id, sample_rate);
+    }
+
+    /// State key for a channel. The full NET.STA.LOC.CHA id is kept so that
+    /// several stations feeding one pipeline are tracked independently.
+    fn clean_id(id: &str) -> String {
+        id.trim_matches('\'').trim().to_string()
+    }
+
+    /// Response to remove for a channel, or None when the trigger runs on counts.
+    fn resolve_response(&self, id: &str, sensitivity: f64) -> Option<ChannelResponse> {
+        if !self.config.deconvolve {
+            return None;
+        }
+        let channel = id.rsplit('.').next().unwrap_or(id);
+        let response = self.responses.get(channel).cloned().or_else(|| {
+            (sensitivity > 0.0).then(|| ChannelResponse::from_sensitivity(sensitivity, infer_input_units(channel)))
+        });
+        if response.is_none() {
+            warn!("Trigger '{}': no response or sensitivity for {}; triggering on counts", self.config.name, id);
+        }
+        response
+    }
+
+    fn new_state(config: &TriggerConfig, sample_rate: f64, response: Option<ChannelResponse>, noise: Option<NoiseTracker>) -> StaLtaState {
+        let nyquist = sample_rate / 2.0;
+        let mut lowpass = config.lowpass;
+        if lowpass >= nyquist {
+            lowpass = 0.95 * nyquist;
+            warn!(
+                "Trigger lowpass {} Hz is at or above Nyquist for {} SPS; using {} Hz",
+                config.lowpass, sample_rate, lowpass
+            );
+        }
+        let sos = butter_bandpass_sos(config.corners.max(1), config.highpass, lowpass, sample_rate);
+
+        let nlta = (config.lta_sec * sample_rate) as usize;
+        let nsta = (config.sta_sec * sample_rate) as usize;
+        let packet_len = ((PACKET_SECONDS * sample_rate).round() as usize).max(1);
+        let detector = config.detector.build(nsta, nlta);
         // Match Python rsudp window: nlta + one packet (25 samples at 100 SPS).
         // ObsPy's Stream.slice(endtime - lta_sec) yields nlta + ~packet_size samples
         // due to packet-boundary alignment, causing ndat > nlta which triggers
         // ObsPy's recursive_sta_lta to zero the first nlta output elements.
-        let win_size = nlta + 25;
-        let state = self.states.entry(clean_id.clone()).or_insert_with(|| StaLtaState {
+        // Detectors with a longer warm-up (delayed STA/LTA) get a longer window.
+        let win_size = detector.warmup().max(nlta) + packet_len;
+        StaLtaState {
+            sample_rate, sos, response, detector, noise, packet_len, win_size,
             triggered: false, max_ratio: 0.0, last_timestamp: None, exceed_start: None, is_exceeding: false,
             raw_buffer: VecDeque::with_capacity(win_size),
             sample_count: 0,
-        });
+        }
+    }
+
+    /// AIC P pick on the last `PICK_WINDOW_SECONDS` of the trigger window. The window
+    /// is demeaned before filtering so the filter's start-up transient stays small.
+    fn pick_p(id: &str, sos: &[Biquad], window: &[f64], sample_rate: f64, timestamp: DateTime<Utc>) -> Option<PhasePick> {
+        let mean = window.iter().sum::<f64>() / window.len().max(1) as f64;
+        let mut filters = sos.to_vec();
+        let filtered: Vec<f64> = window.iter().map(|&x| {
+            filters.iter_mut().fold(x - mean, |val, section| section.process(val))
+        }).collect();
+        let len = ((PICK_WINDOW_SECONDS * sample_rate) as usize).min(filtered.len());
+        pick_phase("P", id, &filtered[filtered.len() - len..], sample_rate, timestamp)
+    }
+
+    /// Feed one raw sample (counts). `sensitivity` (counts per physical unit) is used
+    /// to convert to physical units when deconvolution is enabled and no full
+    /// response is known for the channel.
+    pub fn add_sample(&mut self, id: &str, sample: f64, timestamp: DateTime<Utc>, sensitivity: f64) -> Option<AlertEvent> {
+        if !id.contains(&self.config.target_channel) { return None; }
+
+        let clean_id = Self::clean_id(id);
+        if !self.states.contains_key(&clean_id) {
+            let sample_rate = self.sample_rates.get(&clean_id).copied().unwrap_or(DEFAULT_SAMPLE_RATE);
+            let response = self.resolve_response(&clean_id, sensitivity);
+            let noise = self.adaptive.clone().map(NoiseTracker::new);
+            self.states.insert(clean_id.clone(), Self::new_state(&self.config, sample_rate, response, noise));
+        }
+        let state = self.states.get_mut(&clean_id)?;
+        let win_size = state.win_size;
 
         // --- GAP DETECTION ---
         if let Some(last_ts) = state.last_timestamp {
@@ -142,41 +402,60 @@ impl TriggerManager {
         }
         state.sample_count += 1;
 
-        // Evaluate only at packet boundaries (every 25 samples), matching Python rsudp.
-        // Python rsudp evaluates once per ~250ms packet at 100 SPS.
-        if !state.sample_count.is_multiple_of(25) || state.raw_buffer.len() < win_size {
+        // Evaluate only at packet boundaries, matching Python rsudp.
+        // Python rsudp evaluates once per ~250ms packet (25 samples at 100 SPS).
+        if !state.sample_count.is_multiple_of(state.packet_len) || state.raw_buffer.len() < win_size {
             return None;
         }
 
-        // Compute ratio: fresh filter + recursive STA/LTA over entire buffer
-        let mut filters = butter_bandpass_sos(4, highpass, lowpass, 100.0);
-        let csta = 1.0 / nsta as f64;
-        let clta = 1.0 / nlta as f64;
-        let mut sta = 0.0_f64;
-        let mut lta = 1e-99_f64;
-        let mut ratio_last = 0.0_f64;
-        let mut ratio_max_tail = 0.0_f64;
-        for (i, &raw) in state.raw_buffer.iter().enumerate() {
-            let mut val = raw;
-            for section in &mut filters {
-                val = section.process(val);
-            }
-            let energy = val * val;
-            sta = csta * energy + (1.0 - csta) * sta;
-            lta = clta * energy + (1.0 - clta) * lta;
-            ratio_last = sta / lta;
-            // Match ObsPy: when ndat > nlta, first nlta values are zeroed.
-            // Track max for the non-zeroed tail (positions >= nlta).
-            if i >= nlta {
-                ratio_max_tail = ratio_max_tail.max(ratio_last);
+        // Optionally remove the instrument response over the whole window first,
+        // as rsudp does (remove_response, demean, then bandpass)
+        let window: Vec<f64> = match state.response.as_ref() {
+            Some(response) => {
+                let raw: Vec<f64> = state.raw_buffer.iter().copied().collect();
+                let fs = state.sample_rate;
+                let pre_filt = [0.1, 0.6, 0.95 * fs, fs];
+                let mut converted = deconvolve_response_to(&raw, response, fs, pre_filt, WATER_LEVEL_DB, self.config.units);
+                let mean = converted.iter().sum::<f64>() / converted.len().max(1) as f64;
+                converted.iter_mut().for_each(|s| *s -= mean);
+                converted
             }
+            None => state.raw_buffer.iter().copied().collect(),
+        };
+
+        // Compute ratio: fresh filter + characteristic function over entire buffer
+        let mut filters = state.sos.clone();
+        let filtered: Vec<f64> = window.iter().map(|&raw| {
+            filters.iter_mut().fold(raw, |val, section| section.process(val))
+        }).collect();
+        let cf = state.detector.characteristic(&filtered);
+        let ratio_last = cf.last().copied().unwrap_or(0.0);
+        // Match ObsPy: the warm-up values are zeroed, so the max covers the tail only
+        let ratio_max_tail = cf[state.detector.warmup().min(cf.len())..].iter().copied().fold(0.0_f64, f64::max);
+
+        if let Some(out) = self.cf_output.as_mut() {
+            let tail = &cf[cf.len().saturating_sub(state.packet_len)..];
+            let span = (tail.len() as f64 - 1.0).max(0.0) / state.sample_rate;
+            out.push(CharacteristicFunction {
+                channel: id.to_string(),
+                trigger_name: self.config.name.clone(),
+                timestamp: timestamp - chrono::Duration::microseconds((span * 1_000_000.0) as i64),
+                sample_rate: state.sample_rate,
+                values: tail.to_vec(),
+            });
         }
 
         // --- TRIGGER LOGIC ---
         // ALARM: use ratio_max_tail (matches Python rsudp stalta.max() after zeroing)
         // RESET: use ratio_last (matches Python rsudp stalta[-1])
-        let threshold = self.config.threshold;
-        let reset_threshold = self.config.reset_threshold;
+        // Adaptive mode scales both thresholds with the background noise and learns
+        // the noise only while the channel is quiet
+        let scale = state.noise.as_ref().map_or(1.0, |n| n.scale(timestamp));
+        let threshold = self.config.threshold * scale;
+        let reset_threshold = self.config.reset_threshold * scale;
+        if let Some(noise) = state.noise.as_mut().filter(|_| !state.triggered && ratio_max_tail <= threshold) {
+            noise.update(&filtered[filtered.len().saturating_sub(state.packet_len)..], state.sample_rate, timestamp);
+        }
 
         if !state.triggered {
             if ratio_max_tail > threshold {
@@ -189,9 +468,10 @@ impl TriggerManager {
                         state.triggered = true;
                         state.max_ratio = ratio_max_tail;
                         state.is_exceeding = false;
+                        let pick = Self::pick_p(id, &state.sos, &window, state.sample_rate, timestamp);
                         return Some(AlertEvent {
-                            timestamp, channel: id.to_string(), event_type: AlertEventType::Trigger,
-                            ratio: ratio_max_tail, max_ratio: ratio_max_tail, message: "ALARM".to_string(),
+                            timestamp, channel: id.to_string(), trigger_name: self.config.name.clone(), event_type: AlertEventType::Trigger,
+                            ratio: ratio_max_tail, max_ratio: ratio_max_tail, message: "ALARM".to_string(), pick, early_warning: None,
                         });
                     }
                 }
@@ -206,8 +486,8 @@ impl TriggerManager {
                 state.max_ratio = 0.0;
 
                 return Some(AlertEvent {
-                    timestamp, channel: id.to_string(), event_type: AlertEventType::Reset,
-                    ratio: ratio_last, max_ratio: mr, message: "RESET".to_string(),
+                    timestamp, channel: id.to_string(), trigger_name: self.config.name.clone(), event_type: AlertEventType::Reset,
+                    ratio: ratio_last, max_ratio: mr, message: "RESET".to_string(), pick: None, early_warning: None,
                 });
             }
         }
@@ -215,8 +495,8 @@ impl TriggerManager {
         // --- PERIODIC STATUS REPORT ---
         if timestamp.timestamp_subsec_millis() < 10 {
              return Some(AlertEvent {
-                timestamp, channel: id.to_string(), event_type: AlertEventType::Status,
-                ratio: ratio_last, max_ratio: state.max_ratio.max(ratio_max_tail), message: "STATUS".to_string(),
+                timestamp, channel: id.to_string(), trigger_name: self.config.name.clone(), event_type: AlertEventType::Status,
+                ratio: ratio_last, max_ratio: state.max_ratio.max(ratio_max_tail), message: "STATUS".to_string(), pick: None, early_warning: None,
             });
         }
 
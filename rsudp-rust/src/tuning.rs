@@ -0,0 +1,191 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::detector::DetectorKind;
+use crate::filter::OutputUnits;
+use crate::parser::TraceSegment;
+use crate::trigger::{AlertEventType, TriggerConfig, TriggerManager};
+
+/// Parameter grid for offline trigger tuning; every combination with `lta > sta`
+/// and `reset < threshold` is evaluated.
+#[derive(Debug, Clone)]
+pub struct TuningGrid {
+    pub channel: String,
+    pub detectors: Vec<DetectorKind>,
+    pub sta: Vec<f64>,
+    pub lta: Vec<f64>,
+    pub threshold: Vec<f64>,
+    pub reset: Vec<f64>,
+    /// (highpass, lowpass) pairs (Hz)
+    pub bands: Vec<(f64, f64)>,
+    pub corners: usize,
+    pub duration: f64,
+}
+
+impl TuningGrid {
+    pub fn configs(&self) -> Vec<TriggerConfig> {
+        let mut configs = Vec::new();
+        for &detector in &self.detectors {
+            for &(highpass, lowpass) in &self.bands {
+                for &sta in &self.sta {
+                    for &lta in self.lta.iter().filter(|&&lta| lta > sta) {
+                        for &threshold in &self.threshold {
+                            for &reset in self.reset.iter().filter(|&&reset| reset < threshold) {
+                                configs.push(TriggerConfig {
+                                    name: format!("{:?} sta={} lta={} on={} off={} band={}-{}", detector, sta, lta, threshold, reset, highpass, lowpass),
+                                    sta_sec: sta,
+                                    lta_sec: lta,
+                                    threshold,
+                                    reset_threshold: reset,
+                                    highpass,
+                                    lowpass,
+                                    corners: self.corners,
+                                    target_channel: self.channel.clone(),
+                                    duration: self.duration,
+                                    detector,
+                                    deconvolve: false,
+                                    units: OutputUnits::Chan,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        configs
+    }
+}
+
+/// Detection performance of one configuration against a labeled event list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TuningScore {
+    /// Triggers within the tolerance of a labeled event
+    pub true_positives: usize,
+    pub false_positives: usize,
+    /// Labeled events with at least one trigger
+    pub detected: usize,
+    pub missed: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Trigger-on times of `config` over `segments` (replayed in time order).
+pub fn run_config(config: &TriggerConfig, segments: &[TraceSegment]) -> Vec<DateTime<Utc>> {
+    let mut tm = TriggerManager::new(config.clone());
+    let mut ordered: Vec<&TraceSegment> = segments.iter().collect();
+    ordered.sort_by_key(|s| s.starttime);
+    let mut triggers = Vec::new();
+    for segment in ordered {
+        let id = segment.nslc();
+        tm.set_sample_rate(&id, segment.sampling_rate);
+        for (i, &sample) in segment.samples.iter().enumerate() {
+            let ts = segment.starttime + Duration::microseconds((i as f64 / segment.sampling_rate * 1e6) as i64);
+            if let Some(alert) = tm.add_sample(&id, sample, ts, 0.0) {
+                if alert.event_type == AlertEventType::Trigger {
+                    triggers.push(alert.timestamp);
+                }
+            }
+        }
+    }
+    triggers
+}
+
+/// Score `triggers` against `labels`: a trigger within `tolerance` seconds of a
+/// labeled event is a true positive.
+pub fn score(triggers: &[DateTime<Utc>], labels: &[DateTime<Utc>], tolerance: f64) -> TuningScore {
+    let tolerance = Duration::milliseconds((tolerance * 1000.0) as i64);
+    let near = |a: DateTime<Utc>, b: DateTime<Utc>| (a - b).abs() <= tolerance;
+    let true_positives = triggers.iter().filter(|&&t| labels.iter().any(|&l| near(t, l))).count();
+    let detected = labels.iter().filter(|&&l| triggers.iter().any(|&t| near(t, l))).count();
+    let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+    let precision = ratio(true_positives, triggers.len());
+    let recall = ratio(detected, labels.len());
+    let f1 = if precision + recall > 0.0 { 2.0 * precision * recall / (precision + recall) } else { 0.0 };
+    TuningScore {
+        true_positives,
+        false_positives: triggers.len() - true_positives,
+        detected,
+        missed: labels.len() - detected,
+        precision,
+        recall,
+        f1,
+    }
+}
+
+/// Event times from a label file: the first field of each line (time column of a
+/// CSV with a header); unparsable lines such as headers and comments are skipped.
+pub fn parse_labels(text: &str) -> Vec<DateTime<Utc>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#')).peekable();
+    let column = lines
+        .peek()
+        .and_then(|header| header.split(',').position(|h| matches!(h.trim().to_lowercase().as_str(), "time" | "origin_time" | "datetime")))
+        .unwrap_or(0);
+    lines
+        .filter_map(|line| line.split(',').nth(column).and_then(crate::catalog::parse_time))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_score_and_labels() {
+        let labels = parse_labels("# events\ntime,mag\n2026-01-05T02:30:00Z,3.1\n2026-01-05 04:00:00,2.0\n");
+        assert_eq!(labels, vec![t("2026-01-05T02:30:00Z"), t("2026-01-05T04:00:00Z")]);
+
+        let triggers = [t("2026-01-05T02:30:04Z"), t("2026-01-05T02:30:20Z"), t("2026-01-05T03:00:00Z")];
+        let s = score(&triggers, &labels, 5.0);
+        assert_eq!((s.true_positives, s.false_positives, s.detected, s.missed), (1, 2, 1, 1));
+        assert!((s.precision - 1.0 / 3.0).abs() < 1e-9);
+        assert!((s.recall - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_runs_trigger_manager() {
+        let grid = TuningGrid {
+            channel: "EHZ".to_string(),
+            detectors: vec![DetectorKind::Recursive],
+            sta: vec![1.0, 20.0],
+            lta: vec![10.0],
+            threshold: vec![3.0, 1000.0],
+            reset: vec![1.0],
+            bands: vec![(0.1, 10.0)],
+            corners: 2,
+            duration: 0.0,
+        };
+        let configs = grid.configs();
+        // sta=20 is not shorter than lta=10
+        assert_eq!(configs.len(), 2);
+
+        // 60 s of low noise, then a 5 Hz burst
+        let sps = 100.0;
+        let samples: Vec<f64> = (0..9000)
+            .map(|i| {
+                let x = i as f64 / sps;
+                let noise = ((i * 7919) % 200) as f64 - 100.0;
+                let burst = if (60.0..70.0).contains(&x) { 5000.0 * (2.0 * std::f64::consts::PI * 5.0 * x).sin() } else { 0.0 };
+                noise + burst
+            })
+            .collect();
+        let segment = TraceSegment {
+            network: "AM".to_string(),
+            station: "R0001".to_string(),
+            location: "00".to_string(),
+            channel: "EHZ".to_string(),
+            starttime: t("2026-01-05T02:29:00Z"),
+            samples,
+            sampling_rate: sps,
+        };
+        let low = run_config(&configs[0], std::slice::from_ref(&segment));
+        let high = run_config(&configs[1], std::slice::from_ref(&segment));
+        assert!(!low.is_empty());
+        assert!(low.iter().any(|&tr| (tr - t("2026-01-05T02:30:00Z")).num_seconds().abs() <= 2), "{:?}", low);
+        assert!(high.is_empty());
+    }
+}
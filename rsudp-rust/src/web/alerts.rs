@@ -4,15 +4,50 @@ use lettre::{Message, SmtpTransport, Transport};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::distance::DistanceEstimate;
+use crate::eew::EarlyWarning;
+use crate::ground_motion::GroundMotion;
+use crate::intensity::IntensityScale;
+use crate::magnitude::LocalMagnitude;
+use crate::picker::PhasePick;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertEvent {
     pub id: Uuid,
     pub channel: String,
+    /// Trigger definition that raised the alert
+    #[serde(default)]
+    pub trigger_name: String,
     pub trigger_time: DateTime<Utc>,
     pub reset_time: Option<DateTime<Utc>>,
     pub max_ratio: f64,
     pub snapshot_path: Option<String>,
     pub message: Option<String>,
+    /// Automatic P arrival
+    #[serde(default)]
+    pub pick: Option<PhasePick>,
+    /// S-P distance and origin time, filled in once the S phase is picked
+    #[serde(default)]
+    pub distance: Option<DistanceEstimate>,
+    /// Local magnitude from the horizontals
+    #[serde(default)]
+    pub magnitude: Option<LocalMagnitude>,
+    /// PGA/PGV/PGD between trigger and reset
+    #[serde(default)]
+    pub ground_motion: Option<GroundMotion>,
+    /// τc/Pd estimate from the first seconds of P
+    #[serde(default)]
+    pub early_warning: Option<EarlyWarning>,
+    /// Channels that clipped while the alert was on; their amplitudes are lower bounds
+    #[serde(default)]
+    pub clipped: Vec<String>,
+    /// Artefact found by the conditioning stage (spike, clipping, DC step); such entries
+    /// record the artefact for auditing and are never notified
+    #[serde(default)]
+    pub suppressed: Option<String>,
+    /// Raised during a maintenance window or manual mute; recorded without notifications
+    #[serde(default)]
+    pub muted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +60,9 @@ pub struct AlertSettings {
     pub smtp_user: String,
     pub smtp_pass: String,
     pub email_recipient: String,
+    /// Trigger names routed to email (empty = all)
+    #[serde(default)]
+    pub triggers: Vec<String>,
     pub save_pct: f64,
 }
 
@@ -39,6 +77,7 @@ impl Default for AlertSettings {
             smtp_user: "".to_string(),
             smtp_pass: "".to_string(),
             email_recipient: "".to_string(),
+            triggers: Vec::new(),
             save_pct: 0.7,
         }
     }
@@ -59,6 +98,15 @@ pub fn format_shindo_message(shindo_class: &str) -> String {
     }
 }
 
+/// Alert message for an intensity class on `scale`.
+pub fn format_intensity_message(scale: IntensityScale, class: &str) -> String {
+    match scale {
+        IntensityScale::Jma => format_shindo_message(class),
+        _ if scale.classes().first() == Some(&class) => "No shaking was detected".to_string(),
+        _ => format!("Shaking of {} intensity {} was detected", scale.name(), class),
+    }
+}
+
 pub fn send_trigger_email(
     settings: &AlertSettings,
     channel: &str,
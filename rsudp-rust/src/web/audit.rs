@@ -0,0 +1,42 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::trigger::TriggerConfig;
+
+/// File (in the output directory) recording every live trigger change, one JSON object per line.
+pub const AUDIT_LOG_FILE: &str = "trigger_audit.jsonl";
+
+/// A trigger parameter change made through the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    pub trigger: String,
+    pub before: TriggerConfig,
+    pub after: TriggerConfig,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Append `entry` to the audit log at `path`, creating it if needed.
+pub fn append(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+
+/// The last `limit` entries of the audit log, oldest first; empty when there is none.
+pub fn read_recent(path: &Path, limit: usize) -> Vec<AuditEntry> {
+    let Ok(file) = std::fs::File::open(path) else { return Vec::new() };
+    let entries: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    entries[entries.len().saturating_sub(limit)..].to_vec()
+}
@@ -1,6 +1,10 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use chrono::{Duration, Utc};
+use crate::distance::DistanceEstimate;
+use crate::eew::EarlyWarning;
+use crate::ground_motion::GroundMotion;
+use crate::magnitude::LocalMagnitude;
 use crate::web::alerts::{AlertEvent, AlertSettings};
 
 #[derive(Default)]
@@ -39,6 +43,40 @@ impl AlertHistoryManager {
         }
     }
 
+    pub fn set_distance(&mut self, id: uuid::Uuid, distance: DistanceEstimate) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == id) {
+            event.distance = Some(distance);
+        }
+    }
+
+    pub fn set_magnitude(&mut self, id: uuid::Uuid, magnitude: LocalMagnitude) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == id) {
+            event.magnitude = Some(magnitude);
+        }
+    }
+
+    pub fn set_ground_motion(&mut self, id: uuid::Uuid, ground_motion: GroundMotion) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == id) {
+            event.ground_motion = Some(ground_motion);
+        }
+    }
+
+    pub fn set_early_warning(&mut self, id: uuid::Uuid, early_warning: EarlyWarning) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == id) {
+            event.early_warning = Some(early_warning);
+        }
+    }
+
+    pub fn set_clipped(&mut self, id: uuid::Uuid, channels: Vec<String>) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == id) {
+            event.clipped = channels;
+        }
+    }
+
+    pub fn get_event(&self, id: uuid::Uuid) -> Option<AlertEvent> {
+        self.events.iter().find(|e| e.id == id).cloned()
+    }
+
     pub fn get_events(&self) -> Vec<AlertEvent> {
         self.events.iter().cloned().collect()
     }
@@ -2,7 +2,9 @@ pub mod routes;
 pub mod stream;
 pub mod test_utils;
 pub mod alerts;
+pub mod audit;
 pub mod history;
+pub mod quakeml;
 pub mod spectrogram;
 pub mod sns;
 
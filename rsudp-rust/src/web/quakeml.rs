@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::event::Event;
+use crate::intensity::IntensityScale;
+use crate::picker::PhasePick;
+use crate::web::alerts::AlertEvent;
+
+/// Resource identifier prefix for everything this station publishes.
+const ID_PREFIX: &str = "smi:rsudp";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn time(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// `<waveformID>` for a NET.STA.LOC.CHA id; bare channel codes get `station`.
+fn waveform_id(id: &str, station: &str) -> String {
+    let parts: Vec<&str> = id.split('.').collect();
+    let (net, sta, loc, cha) = match parts[..] {
+        [net, sta, loc, cha] => (net, sta, loc, cha),
+        [net, sta, cha] => (net, sta, "", cha),
+        _ => ("", station, "", id),
+    };
+    format!(
+        r#"<waveformID networkCode="{}" stationCode="{}" locationCode="{}" channelCode="{}"/>"#,
+        escape(net), escape(sta), escape(loc), escape(cha)
+    )
+}
+
+/// One detected event in QuakeML 1.2, without the document wrapper. `alerts` are the
+/// history entries of the event, which carry the per-alert picks and amplitudes.
+pub fn write_event(event: &Event, alerts: &[AlertEvent], station: &str, scale: IntensityScale) -> String {
+    let id = format!("{}/event/{}", ID_PREFIX, event.id);
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"    <event publicID="{}">"#, id);
+    let _ = writeln!(xml, "      <type>earthquake</type>");
+    let _ = writeln!(xml, "      <typeCertainty>suspected</typeCertainty>");
+    let _ = writeln!(
+        xml,
+        "      <description><text>{} detection on {} ({})</text><type>earthquake name</type></description>",
+        escape(station), escape(&event.channels.join(", ")), escape(&event.triggers.join(", "))
+    );
+
+    let mut comments = vec![format!("Max trigger ratio {:.2}", event.max_ratio)];
+    if let Some(intensity) = event.max_intensity {
+        comments.push(format!("{} intensity {:.1} ({})", scale.name(), intensity, scale.class(intensity)));
+    }
+    if let Some(catalog) = &event.catalog {
+        comments.push(format!("Catalog event {}", catalog));
+    }
+    if let Some(lp) = &event.long_period {
+        comments.push(format!("JMA {}", lp));
+    }
+    if event.muted {
+        comments.push("Recorded during maintenance (notifications muted)".to_string());
+    }
+    for (n, text) in comments.iter().enumerate() {
+        let _ = writeln!(xml, r#"      <comment id="{}/comment/{}"><text>{}</text></comment>"#, id, n, escape(text));
+    }
+
+    // Picks: the P picks of the event and the S picks behind the distance estimates
+    let mut picks: Vec<&PhasePick> = event.picks.iter().collect();
+    picks.extend(alerts.iter().filter_map(|a| a.distance.as_ref().map(|d| &d.s_pick)));
+    for (n, pick) in picks.iter().enumerate() {
+        let _ = writeln!(xml, r#"      <pick publicID="{}/pick/{}">"#, id, n);
+        let _ = writeln!(xml, "        <time><value>{}</value><uncertainty>{}</uncertainty></time>", time(pick.time), pick.uncertainty);
+        let _ = writeln!(xml, "        {}", waveform_id(&pick.channel, station));
+        let _ = writeln!(xml, "        <phaseHint>{}</phaseHint>", escape(&pick.phase));
+        let _ = writeln!(xml, "        <evaluationMode>automatic</evaluationMode>");
+        let _ = writeln!(xml, "      </pick>");
+    }
+
+    // Amplitudes: peak ground motion per component, Wood-Anderson for ML
+    let mut amplitudes = Vec::new();
+    if let Some(gm) = alerts.iter().find_map(|a| a.ground_motion.as_ref()) {
+        for c in &gm.components {
+            amplitudes.push(("PGA", "m/(s*s)", c.pga, c.channel.clone()));
+            amplitudes.push(("PGV", "m/s", c.pgv, c.channel.clone()));
+            amplitudes.push(("PGD", "m", c.pgd, c.channel.clone()));
+        }
+    }
+    let magnitude = alerts.iter().find_map(|a| a.magnitude.as_ref());
+    if let Some(ml) = magnitude {
+        amplitudes.push(("AML", "m", ml.amplitude_mm / 1000.0, String::new()));
+    }
+    for (n, (kind, unit, value, channel)) in amplitudes.iter().enumerate() {
+        let _ = writeln!(xml, r#"      <amplitude publicID="{}/amplitude/{}">"#, id, n);
+        let _ = writeln!(xml, "        <genericAmplitude><value>{:e}</value></genericAmplitude>", value);
+        let _ = writeln!(xml, "        <type>{}</type>", kind);
+        let _ = writeln!(xml, "        <unit>{}</unit>", unit);
+        if !channel.is_empty() {
+            let _ = writeln!(xml, "        {}", waveform_id(channel, station));
+        }
+        let _ = writeln!(xml, "        <evaluationMode>automatic</evaluationMode>");
+        let _ = writeln!(xml, "      </amplitude>");
+    }
+
+    // Magnitudes: local magnitude, then the τc/Pd early estimate
+    let mut magnitudes = Vec::new();
+    if let Some(ml) = magnitude {
+        magnitudes.push(("ML", ml.ml, format!("{}/method/ml/{}", ID_PREFIX, escape(&ml.correction))));
+    }
+    if let Some(ew) = alerts.iter().find_map(|a| a.early_warning.as_ref()) {
+        magnitudes.push(("Mpd", ew.magnitude, format!("{}/method/tauc_pd", ID_PREFIX)));
+    }
+    if !magnitudes.is_empty() {
+        let _ = writeln!(xml, "      <preferredMagnitudeID>{}/magnitude/0</preferredMagnitudeID>", id);
+    }
+    for (n, (kind, value, method)) in magnitudes.iter().enumerate() {
+        let _ = writeln!(xml, r#"      <magnitude publicID="{}/magnitude/{}">"#, id, n);
+        let _ = writeln!(xml, "        <mag><value>{:.2}</value></mag>", value);
+        let _ = writeln!(xml, "        <type>{}</type>", kind);
+        let _ = writeln!(xml, "        <methodID>{}</methodID>", method);
+        let _ = writeln!(xml, "        <stationCount>1</stationCount>");
+        let _ = writeln!(xml, "        <evaluationMode>automatic</evaluationMode>");
+        let _ = writeln!(xml, "      </magnitude>");
+    }
+
+    let _ = writeln!(xml, "      <creationInfo><agencyID>RSUDP</agencyID><creationTime>{}</creationTime></creationInfo>", time(event.start));
+    let _ = writeln!(xml, "    </event>");
+    xml
+}
+
+/// A QuakeML 1.2 document with the given events.
+pub fn write_quakeml(events: &[(Event, Vec<AlertEvent>)], station: &str, scale: IntensityScale) -> String {
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(xml, r#"<q:quakeml xmlns="http://quakeml.org/xmlns/bed/1.2" xmlns:q="http://quakeml.org/xmlns/quakeml/1.2">"#);
+    let _ = writeln!(xml, r#"  <eventParameters publicID="{}/eventParameters/{}">"#, ID_PREFIX, escape(station));
+    for (event, alerts) in events {
+        xml.push_str(&write_event(event, alerts, station, scale));
+    }
+    let _ = writeln!(xml, "  </eventParameters>");
+    let _ = writeln!(xml, "</q:quakeml>");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::parse_quakeml;
+    use crate::event::EventState;
+    use crate::magnitude::LocalMagnitude;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_export_event_with_picks_and_amplitudes() {
+        let t0 = DateTime::parse_from_rfc3339("2026-01-05T02:30:25Z").unwrap().with_timezone(&Utc);
+        let id = Uuid::new_v4();
+        let event = Event {
+            id,
+            state: EventState::Closed,
+            start: t0,
+            end: Some(t0 + chrono::Duration::seconds(30)),
+            alerts: vec![id],
+            channels: vec!["EHZ".to_string()],
+            triggers: vec!["stalta <local>".to_string()],
+            max_ratio: 6.2,
+            max_intensity: Some(2.7),
+            pga: Some(3.1),
+            picks: vec![PhasePick { phase: "P".to_string(), channel: "AM.R0001.00.EHZ".to_string(), time: t0, uncertainty: 0.05 }],
+            muted: false,
+            catalog: None,
+            long_period: None,
+            spectrum: None,
+        };
+        let alert = AlertEvent {
+            id,
+            channel: "EHZ".to_string(),
+            trigger_name: "stalta".to_string(),
+            trigger_time: t0,
+            reset_time: None,
+            max_ratio: 6.2,
+            snapshot_path: None,
+            message: None,
+            pick: None,
+            distance: None,
+            magnitude: Some(LocalMagnitude { ml: 2.4, amplitude_mm: 0.8, distance_km: 40.0, correction: "hutton_boore".to_string() }),
+            ground_motion: None,
+            early_warning: None,
+            clipped: Vec::new(),
+            suppressed: None,
+            muted: false,
+        };
+        let xml = write_quakeml(&[(event, vec![alert])], "R0001", IntensityScale::Jma);
+        assert!(xml.contains(r#"<waveformID networkCode="AM" stationCode="R0001" locationCode="00" channelCode="EHZ"/>"#));
+        assert!(xml.contains("<phaseHint>P</phaseHint>"));
+        assert!(xml.contains("<type>AML</type>"));
+        assert!(xml.contains("stalta &lt;local&gt;"));
+        assert!(xml.contains("JMA intensity 2.7"));
+
+        // No origin is published, so the event carries magnitude information only
+        assert!(parse_quakeml(&xml).is_empty());
+        assert_eq!(xml.matches("<event ").count(), 1);
+        assert!(xml.contains("<mag><value>2.40</value></mag>"));
+    }
+}
@@ -1,7 +1,18 @@
 use crate::web::stream::{PlotSettings, WebState};
 use crate::web::alerts::{AlertEvent, AlertSettings};
-use axum::{Json, Router, extract::{Query, State}, routing::get};
-use axum::http::StatusCode;
+use crate::adaptive::EffectiveThreshold;
+use crate::catalog::CatalogStats;
+use crate::detector::DetectorKind;
+use crate::response_spectrum::ResponseSpectrum;
+use crate::event::Event;
+use crate::filter::OutputUnits;
+use crate::trigger::TriggerConfig;
+use crate::web::audit::{self, AuditEntry, AUDIT_LOG_FILE};
+use crate::maintenance::MuteStatus;
+use crate::parser::stationxml::{ChannelResponse, infer_input_units};
+use axum::{Json, Router, extract::{Path, Query, State}, routing::get};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -15,6 +26,16 @@ pub async fn create_router(state: WebState) -> Router {
         .route("/api/station", get(get_station_name))
         .route("/api/alerts", get(get_alert_history))
         .route("/api/alerts/settings", get(get_alert_settings).put(update_alert_settings))
+        .route("/api/events", get(get_events))
+        .route("/api/events.xml", get(get_events_quakeml))
+        .route("/api/events/:id/quakeml", get(get_event_quakeml))
+        .route("/api/events/:id/spectrum", get(get_event_spectrum))
+        .route("/api/catalog/stats", get(get_catalog_stats))
+        .route("/api/thresholds", get(get_thresholds))
+        .route("/api/triggers", get(get_triggers))
+        .route("/api/triggers/audit", get(get_trigger_audit))
+        .route("/api/triggers/:name", get(get_trigger).put(update_trigger))
+        .route("/api/mute", get(get_mute).post(set_mute).delete(clear_mute))
         .route("/api/capture/data", get(get_capture_data))
         .nest_service("/images/alerts", ServeDir::new("alerts"))
         .route("/ws", get(crate::web::stream::ws_handler))
@@ -27,6 +48,64 @@ async fn get_alert_history(State(state): State<WebState>) -> Json<Vec<AlertEvent
     Json(history.get_events())
 }
 
+async fn get_events(State(state): State<WebState>) -> Json<Vec<Event>> {
+    Json(state.events.lock().unwrap().get_events())
+}
+
+/// Catalog matches and false-alarm/missed counts over the retained events (last 24 h).
+async fn get_catalog_stats(State(state): State<WebState>) -> Json<CatalogStats> {
+    let catalog = state.catalog.lock().unwrap();
+    let mut events = state.events.lock().unwrap();
+    events.label(&catalog);
+    let detections: Vec<DateTime<Utc>> = events.get_events().iter().filter(|e| !e.is_open()).map(|e| e.detection_time()).collect();
+    let end = Utc::now();
+    Json(catalog.statistics(&detections, end - chrono::Duration::hours(24), end))
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+/// Events with the history entries of their alerts, for QuakeML export.
+fn events_with_alerts(state: &WebState, events: Vec<Event>) -> Vec<(Event, Vec<AlertEvent>)> {
+    let history = state.history.lock().unwrap();
+    events
+        .into_iter()
+        .map(|event| {
+            let alerts = event.alerts.iter().filter_map(|&id| history.get_event(id)).collect();
+            (event, alerts)
+        })
+        .collect()
+}
+
+/// 5%-damped pseudo-spectral acceleration of an event, for plotting.
+async fn get_event_spectrum(State(state): State<WebState>, Path(id): Path<uuid::Uuid>) -> Result<Json<ResponseSpectrum>, StatusCode> {
+    let event = state.events.lock().unwrap().get_event(id).ok_or(StatusCode::NOT_FOUND)?;
+    event.spectrum.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// QuakeML 1.2 of the events that started between `start` and `end` (both optional).
+async fn get_events_quakeml(State(state): State<WebState>, Query(params): Query<EventsQuery>) -> impl IntoResponse {
+    let events: Vec<Event> = state.events.lock().unwrap().get_events().into_iter()
+        .filter(|e| params.start.is_none_or(|start| e.start >= start) && params.end.is_none_or(|end| e.start <= end))
+        .collect();
+    let station = state.station_name.read().unwrap().clone();
+    let scale = *state.intensity_scale.read().unwrap();
+    let xml = crate::web::quakeml::write_quakeml(&events_with_alerts(&state, events), &station, scale);
+    ([(header::CONTENT_TYPE, "application/xml")], xml)
+}
+
+async fn get_event_quakeml(State(state): State<WebState>, Path(id): Path<uuid::Uuid>) -> Result<impl IntoResponse, StatusCode> {
+    let event = state.events.lock().unwrap().get_event(id).ok_or(StatusCode::NOT_FOUND)?;
+    let station = state.station_name.read().unwrap().clone();
+    let scale = *state.intensity_scale.read().unwrap();
+    let xml = crate::web::quakeml::write_quakeml(&events_with_alerts(&state, vec![event]), &station, scale);
+    let disposition = format!("attachment; filename=\"event-{}.xml\"", id);
+    Ok(([(header::CONTENT_TYPE, "application/xml".to_string()), (header::CONTENT_DISPOSITION, disposition)], xml))
+}
+
 async fn get_alert_settings(State(state): State<WebState>) -> Json<AlertSettings> {
     let history = state.history.lock().unwrap();
     Json(history.get_settings())
@@ -41,6 +120,135 @@ async fn update_alert_settings(
     Json(new_settings)
 }
 
+async fn get_thresholds(State(state): State<WebState>) -> Json<Vec<EffectiveThreshold>> {
+    let thresholds = state.thresholds.read().unwrap();
+    let mut list: Vec<EffectiveThreshold> = thresholds.values().cloned().collect();
+    list.sort_by(|a, b| (&a.trigger_name, &a.channel).cmp(&(&b.trigger_name, &b.channel)));
+    Json(list)
+}
+
+async fn get_triggers(State(state): State<WebState>) -> Json<Vec<TriggerConfig>> {
+    Json(state.triggers.read().unwrap().configs.clone())
+}
+
+async fn get_trigger(State(state): State<WebState>, Path(name): Path<String>) -> Result<Json<TriggerConfig>, StatusCode> {
+    let registry = state.triggers.read().unwrap();
+    registry.configs.iter().find(|c| c.name == name).cloned().map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Fields of a trigger definition to change; omitted fields keep their value.
+#[derive(Debug, Default, Deserialize)]
+pub struct TriggerUpdate {
+    pub sta: Option<f64>,
+    pub lta: Option<f64>,
+    pub threshold: Option<f64>,
+    pub reset: Option<f64>,
+    pub highpass: Option<f64>,
+    pub lowpass: Option<f64>,
+    pub corners: Option<usize>,
+    pub duration: Option<f64>,
+    pub channel: Option<String>,
+    pub detector: Option<String>,
+    pub deconvolve: Option<bool>,
+    pub units: Option<String>,
+    /// Recorded in the audit log
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl TriggerUpdate {
+    fn apply(&self, config: &TriggerConfig) -> Result<TriggerConfig, String> {
+        let mut updated = config.clone();
+        if let Some(v) = self.sta { updated.sta_sec = v; }
+        if let Some(v) = self.lta { updated.lta_sec = v; }
+        if let Some(v) = self.threshold { updated.threshold = v; }
+        if let Some(v) = self.reset { updated.reset_threshold = v; }
+        if let Some(v) = self.highpass { updated.highpass = v; }
+        if let Some(v) = self.lowpass { updated.lowpass = v; }
+        if let Some(v) = self.corners { updated.corners = v; }
+        if let Some(v) = self.duration { updated.duration = v; }
+        if let Some(v) = &self.channel { updated.target_channel = v.clone(); }
+        if let Some(v) = &self.detector {
+            updated.detector = DetectorKind::try_parse(v).ok_or_else(|| format!("unknown detector '{}'", v))?;
+        }
+        if let Some(v) = self.deconvolve { updated.deconvolve = v; }
+        if let Some(v) = &self.units {
+            updated.units = OutputUnits::try_parse(v).ok_or_else(|| format!("unknown units '{}'", v))?;
+        }
+        Ok(updated)
+    }
+}
+
+/// Validate and apply a trigger change; the pipeline picks it up on its next packet.
+async fn update_trigger(
+    State(state): State<WebState>,
+    Path(name): Path<String>,
+    Json(update): Json<TriggerUpdate>,
+) -> Result<Json<TriggerConfig>, (StatusCode, Json<serde_json::Value>)> {
+    let audit_path = state.settings.read().unwrap().output_dir.join(AUDIT_LOG_FILE);
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": e})));
+    let mut registry = state.triggers.write().unwrap();
+    let Some(index) = registry.configs.iter().position(|c| c.name == name) else {
+        return Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": format!("unknown trigger '{}'", name)}))));
+    };
+    let before = registry.configs[index].clone();
+    let after = update.apply(&before).map_err(bad_request)?;
+    after.validate().map_err(bad_request)?;
+    if after == before {
+        return Ok(Json(after));
+    }
+    // A change that cannot be audited is not applied
+    let entry = AuditEntry { time: Utc::now(), trigger: name, before, after, reason: update.reason };
+    if let Err(e) = audit::append(&audit_path, &entry) {
+        tracing::warn!("Failed to write trigger audit log {}: {}", audit_path.display(), e);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("audit log: {}", e)}))));
+    }
+    registry.configs[index] = entry.after.clone();
+    registry.revision += 1;
+    tracing::info!("Trigger '{}' updated via API: {:?}", entry.trigger, entry.after);
+    Ok(Json(entry.after))
+}
+
+async fn get_trigger_audit(State(state): State<WebState>) -> Json<Vec<AuditEntry>> {
+    let audit_path = state.settings.read().unwrap().output_dir.join(AUDIT_LOG_FILE);
+    Json(audit::read_recent(&audit_path, 100))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MuteRequest {
+    /// Mute for this many minutes from now
+    pub minutes: f64,
+    #[serde(default)]
+    pub reason: String,
+}
+
+async fn get_mute(State(state): State<WebState>) -> Json<MuteStatus> {
+    Json(state.maintenance.read().unwrap().status(Utc::now()))
+}
+
+async fn set_mute(
+    State(state): State<WebState>,
+    Json(req): Json<MuteRequest>,
+) -> Result<Json<MuteStatus>, StatusCode> {
+    if !req.minutes.is_finite() || req.minutes <= 0.0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let now = Utc::now();
+    let until = chrono::Duration::try_seconds((req.minutes * 60.0) as i64)
+        .and_then(|d| now.checked_add_signed(d))
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let reason = if req.reason.is_empty() { "manual mute".to_string() } else { req.reason };
+    let mut maintenance = state.maintenance.write().unwrap();
+    maintenance.mute_until(until, reason);
+    Ok(Json(maintenance.status(now)))
+}
+
+async fn clear_mute(State(state): State<WebState>) -> Json<MuteStatus> {
+    let mut maintenance = state.maintenance.write().unwrap();
+    maintenance.unmute();
+    Json(maintenance.status(Utc::now()))
+}
+
 async fn get_station_name(State(state): State<WebState>) -> Json<String> {
     let name = state.station_name.read().unwrap();
     Json(name.clone())
@@ -216,44 +424,37 @@ async fn get_capture_data(
     let mut spectrogram_map = HashMap::new();
     let mut sample_rate = 100.0;
     let deconv_water_level = 4.5; // dB, matches rsudp default
+    let out_units = OutputUnits::parse(&units);
 
     for (ch, data) in &raw_data {
         sample_rate = data.sample_rate;
 
         // Apply deconvolution if enabled (same logic as WebSocket stream)
-        let waveform_samples = if deconvolve {
-            if let Some(response) = resp_map.get(ch) {
-                if !response.poles.is_empty() {
-                    let pre_filt = [0.1, 0.6, 0.95 * data.sample_rate, data.sample_rate];
-                    let mut deconv = crate::filter::deconvolve_response(
-                        &data.samples, response, data.sample_rate, pre_filt, deconv_water_level,
-                    );
+        let response = if deconvolve {
+            resp_map.get(ch).cloned().or_else(|| match sens_map.get(ch) {
+                Some(&s) if s > 0.0 => Some(ChannelResponse::from_sensitivity(s, infer_input_units(ch))),
+                _ => None,
+            })
+        } else {
+            None
+        };
+        let waveform_samples = match response {
+            Some(response) => {
+                let spectral = !response.poles.is_empty() || out_units.derivative_order(&response.input_units) != 0;
+                let pre_filt = [0.1, 0.6, 0.95 * data.sample_rate, data.sample_rate];
+                let mut deconv = crate::filter::deconvolve_response_to(
+                    &data.samples, &response, data.sample_rate, pre_filt, deconv_water_level, out_units,
+                );
+                if spectral {
                     // Match rsudp: demean the deconvolved signal
                     let mean = deconv.iter().sum::<f64>() / deconv.len().max(1) as f64;
                     for s in &mut deconv {
                         *s -= mean;
                     }
-                    if units.to_uppercase() == "GRAV" {
-                        for s in &mut deconv {
-                            *s /= 9.81;
-                        }
-                    }
-                    deconv
-                } else {
-                    // Fallback: simple scalar division by sensitivity
-                    let sensitivity = sens_map.get(ch).copied().unwrap_or(1.0);
-                    let extra_divisor = if units.to_uppercase() == "GRAV" { 9.81 } else { 1.0 };
-                    if sensitivity > 0.0 {
-                        data.samples.iter().map(|&s| s / sensitivity / extra_divisor).collect()
-                    } else {
-                        data.samples.clone()
-                    }
                 }
-            } else {
-                data.samples.clone()
+                deconv
             }
-        } else {
-            data.samples.clone()
+            None => data.samples.clone(),
         };
 
         channels_map.insert(
@@ -398,6 +599,57 @@ mod tests {
         start
     }
 
+    #[tokio::test]
+    async fn test_update_trigger_rejects_unknown_names() {
+        let state = WebState::new();
+        let config = TriggerConfig {
+            name: "default".to_string(), sta_sec: 6.0, lta_sec: 30.0, threshold: 1.1, reset_threshold: 0.5,
+            highpass: 0.1, lowpass: 2.0, corners: 4, target_channel: "EHZ".to_string(), duration: 0.0,
+            detector: DetectorKind::Recursive, deconvolve: false, units: OutputUnits::Chan,
+        };
+        state.triggers.write().unwrap().configs.push(config.clone());
+        let app = create_router(state.clone()).await;
+        for body in [r#"{"detector":"clasic"}"#, r#"{"units":"VELOCITY"}"#] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("PUT")
+                        .uri("/api/triggers/default")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", body);
+            let json = response_body_json(response).await;
+            assert!(json["error"].as_str().unwrap().contains("unknown"));
+        }
+        let registry = state.triggers.read().unwrap();
+        assert_eq!(registry.configs[0], config);
+        assert_eq!(registry.revision, 0);
+    }
+
+    #[tokio::test]
+    async fn test_mute_rejects_out_of_range_minutes() {
+        let state = WebState::new();
+        let app = create_router(state.clone()).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/mute")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"minutes":1e20}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(!state.maintenance.read().unwrap().status(Utc::now()).muted);
+    }
+
     #[tokio::test]
     async fn test_capture_data_valid_query() {
         let state = WebState::new();
@@ -426,7 +678,7 @@ mod tests {
         assert!(body["sample_rate"].as_f64().unwrap() > 0.0);
         assert!(body["channels"]["EHZ"].is_object());
         assert!(body["channels"]["EHN"].is_object());
-        assert!(body["channels"]["EHZ"]["samples"].as_array().unwrap().len() > 0);
+        assert!(!body["channels"]["EHZ"]["samples"].as_array().unwrap().is_empty());
         assert!(body["settings"]["filter_waveform"].is_boolean());
         assert!(body["spectrogram"]["EHZ"].is_object());
     }
@@ -24,7 +24,7 @@ impl AwsSnsProvider {
 #[async_trait]
 impl SNSProvider for AwsSnsProvider {
     async fn send_trigger(&self, event: &NotificationEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let message = format!("ALERT: {} triggered at {}", event.channel, event.timestamp);
+        let message = format!("ALERT: {} ({}) triggered at {}{}", event.channel, event.triggers.join(", "), event.timestamp, event.details_suffix());
         self.client.publish()
             .topic_arn(&self.topic_arn)
             .message(message)
@@ -35,8 +35,8 @@ impl SNSProvider for AwsSnsProvider {
 
     async fn send_reset(&self, event: &NotificationEvent, _image_url: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let message = format!(
-            "ALERT RESET: {}\nMax Ratio: {:.2}\nMax Intensity: {:.2}",
-            event.channel, event.max_ratio, event.max_intensity
+            "ALERT RESET: {} ({})\nMax Ratio: {:.2}\nMax Intensity: {:.2}{}",
+            event.channel, event.triggers.join(", "), event.max_ratio, event.max_intensity, event.details_suffix()
         );
         self.client.publish()
             .topic_arn(&self.topic_arn)
@@ -21,7 +21,7 @@ impl DiscordProvider {
 impl SNSProvider for DiscordProvider {
     async fn send_trigger(&self, event: &NotificationEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let client = reqwest::Client::new();
-        let content = format!("🚨 **ALERT TRIGGERED** on channel `{}` at {} 🚨", event.channel, event.timestamp);
+        let content = format!("🚨 **ALERT TRIGGERED** on channel `{}` ({}) at {} 🚨{}", event.channel, event.triggers.join(", "), event.timestamp, event.details_suffix());
         
         let payload = if self.use_embed {
             serde_json::json!({
@@ -47,8 +47,8 @@ impl SNSProvider for DiscordProvider {
     async fn send_reset(&self, event: &NotificationEvent, _image_url: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let client = reqwest::Client::new();
         let content = format!(
-            "✅ **ALERT RESET** on channel `{}`\nMax Ratio: {:.2}\nMax Intensity: {:.2}",
-            event.channel, event.max_ratio, event.max_intensity
+            "✅ **ALERT RESET** on channel `{}` ({})\nMax Ratio: {:.2}\nMax Intensity: {:.2}{}",
+            event.channel, event.triggers.join(", "), event.max_ratio, event.max_intensity, event.details_suffix()
         );
 
         if let Some(path) = &event.snapshot_path {
@@ -16,7 +16,7 @@ impl GChatProvider {
 impl SNSProvider for GChatProvider {
     async fn send_trigger(&self, event: &NotificationEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let client = reqwest::Client::new();
-        let text = format!("🚨 *ALERT TRIGGERED* on channel `{}` at {} 🚨", event.channel, event.timestamp);
+        let text = format!("🚨 *ALERT TRIGGERED* on channel `{}` ({}) at {} 🚨{}", event.channel, event.triggers.join(", "), event.timestamp, event.details_suffix());
         
         client.post(&self.webhook_url)
             .json(&json!({ "text": text }))
@@ -28,8 +28,8 @@ impl SNSProvider for GChatProvider {
 
     async fn send_reset(&self, event: &NotificationEvent, image_url: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let client = reqwest::Client::new();
-        let header = format!("✅ *ALERT RESET* on channel `{}`", event.channel);
-        let content = format!("Max Ratio: {:.2}\nMax Intensity: {:.2}", event.max_ratio, event.max_intensity);
+        let header = format!("✅ *ALERT RESET* on channel `{}` ({})", event.channel, event.triggers.join(", "));
+        let content = format!("Max Ratio: {:.2}\nMax Intensity: {:.2}{}", event.max_ratio, event.max_intensity, event.details_suffix());
 
         let payload = if let Some(url) = image_url {
             json!({
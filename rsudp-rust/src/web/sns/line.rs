@@ -42,7 +42,7 @@ impl LineProvider {
 #[async_trait]
 impl SNSProvider for LineProvider {
     async fn send_trigger(&self, event: &NotificationEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let text = format!("🚨 地震検知 🚨\nチャンネル: {}\n発生時刻: {}", event.channel, event.timestamp);
+        let text = format!("🚨 地震検知 🚨\nチャンネル: {}\nトリガー: {}\n発生時刻: {}{}", event.channel, event.triggers.join(", "), event.timestamp, event.details_suffix());
         self.push_message(json!({
             "type": "text",
             "text": text
@@ -51,8 +51,8 @@ impl SNSProvider for LineProvider {
 
     async fn send_reset(&self, event: &NotificationEvent, image_url: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let text = format!(
-            "✅ 警報解除 ✅\nチャンネル: {}\n最大STA/LTA比: {:.2}\n最大震度: {:.2}",
-            event.channel, event.max_ratio, event.max_intensity
+            "✅ 警報解除 ✅\nチャンネル: {}\nトリガー: {}\n最大STA/LTA比: {:.2}\n最大震度: {:.2}{}",
+            event.channel, event.triggers.join(", "), event.max_ratio, event.max_intensity, event.details_suffix()
         );
         
         // 1. Send text
@@ -1,8 +1,13 @@
 use std::path::PathBuf;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
+use crate::distance::DistanceEstimate;
+use crate::eew::EarlyWarning;
+use crate::ground_motion::GroundMotion;
+use crate::magnitude::LocalMagnitude;
+use crate::picker::PhasePick;
 use crate::trigger::AlertEventType;
-use crate::settings::Settings;
+use crate::settings::{routes_trigger, Settings};
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
@@ -11,9 +16,44 @@ pub struct NotificationEvent {
     pub timestamp: DateTime<Utc>,
     pub station_id: String,
     pub channel: String,
+    /// Trigger definitions that contributed to the event
+    pub triggers: Vec<String>,
     pub max_ratio: f64,
     pub max_intensity: f64,
     pub snapshot_path: Option<PathBuf>,
+    pub pick: Option<PhasePick>,
+    pub distance: Option<DistanceEstimate>,
+    pub magnitude: Option<LocalMagnitude>,
+    pub ground_motion: Option<GroundMotion>,
+    pub early_warning: Option<EarlyWarning>,
+}
+
+impl NotificationEvent {
+    /// Extra lines describing the event (P pick, ...), appended to provider messages.
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(pick) = &self.pick {
+            lines.push(format!("P arrival: {}", pick));
+        }
+        if let Some(early_warning) = &self.early_warning {
+            lines.push(format!("Early warning: {}", early_warning));
+        }
+        if let Some(distance) = &self.distance {
+            lines.push(format!("Distance: {}", distance));
+        }
+        if let Some(magnitude) = &self.magnitude {
+            lines.push(format!("Magnitude: {}", magnitude));
+        }
+        if let Some(ground_motion) = &self.ground_motion {
+            lines.push(format!("Ground motion: {}", ground_motion));
+        }
+        lines
+    }
+
+    /// `details()` joined as a message suffix, empty when there is nothing to add.
+    pub fn details_suffix(&self) -> String {
+        self.details().iter().map(|line| format!("\n{}", line)).collect()
+    }
 }
 
 #[async_trait]
@@ -34,8 +74,14 @@ use self::line::LineProvider;
 use self::gchat::GChatProvider;
 use self::aws_sns::AwsSnsProvider;
 
+/// A provider together with the trigger names routed to it (empty = all).
+struct RoutedProvider {
+    provider: Arc<dyn SNSProvider>,
+    triggers: Vec<String>,
+}
+
 pub struct SNSManager {
-    providers: Vec<Arc<dyn SNSProvider>>,
+    providers: Vec<RoutedProvider>,
     s3_client: Option<Arc<S3Client>>,
 }
 
@@ -57,40 +103,53 @@ impl SNSManager {
 
         // Discord
         if settings.discord.enabled {
-            manager.providers.push(Arc::new(DiscordProvider::new(
+            manager.add_provider(Arc::new(DiscordProvider::new(
                 settings.discord.webhook_url.clone(),
                 settings.discord.use_embed
-            )));
+            )), &settings.discord.triggers);
         }
 
         // LINE
         if settings.line.enabled {
-            manager.providers.push(Arc::new(LineProvider::new(
+            manager.add_provider(Arc::new(LineProvider::new(
                 settings.line.channel_access_token.clone(),
                 settings.line.to_ids.split(',').map(|s| s.trim().to_string()).collect()
-            )));
+            )), &settings.line.triggers);
         }
 
         // Google Chat
         if settings.googlechat.enabled {
-            manager.providers.push(Arc::new(GChatProvider::new(
+            manager.add_provider(Arc::new(GChatProvider::new(
                 settings.googlechat.webhook_url.clone()
-            )));
+            )), &settings.googlechat.triggers);
         }
 
         // Amazon SNS
         if settings.sns.enabled {
-            manager.providers.push(Arc::new(AwsSnsProvider::new(
+            manager.add_provider(Arc::new(AwsSnsProvider::new(
                 settings.sns.topic_arn.clone(),
                 settings.sns.aws_region.clone()
-            ).await));
+            ).await), &settings.sns.triggers);
         }
 
         manager
     }
 
+    fn add_provider(&mut self, provider: Arc<dyn SNSProvider>, triggers: &[String]) {
+        self.providers.push(RoutedProvider { provider, triggers: triggers.to_vec() });
+    }
+
+    /// Providers that any of the event's triggers is routed to.
+    fn providers_for(&self, event: &NotificationEvent) -> impl Iterator<Item = &Arc<dyn SNSProvider>> + '_ {
+        let triggers = event.triggers.clone();
+        self.providers
+            .iter()
+            .filter(move |p| triggers.iter().any(|t| routes_trigger(&p.triggers, t)))
+            .map(|p| &p.provider)
+    }
+
     pub async fn notify_trigger(&self, event: &NotificationEvent) {
-        for provider in &self.providers {
+        for provider in self.providers_for(event) {
             let provider = provider.clone();
             let event = event.clone();
             tokio::spawn(async move {
@@ -102,6 +161,9 @@ impl SNSManager {
     }
 
     pub async fn notify_reset(&self, event: &NotificationEvent) {
+        if self.providers_for(event).next().is_none() {
+            return;
+        }
         let mut image_url = None;
 
         // Upload to S3 if we have a client and a snapshot
@@ -117,7 +179,7 @@ impl SNSManager {
 
         let image_url_shared = image_url.map(Arc::new);
 
-        for provider in &self.providers {
+        for provider in self.providers_for(event) {
             let provider = provider.clone();
             let event = event.clone();
             let img_url = image_url_shared.clone();
@@ -1,7 +1,13 @@
-use crate::filter::{BiquadChain, deconvolve_response};
-use crate::intensity::IntensityResult;
-use crate::parser::stationxml::ChannelResponse;
-use crate::trigger::AlertEvent;
+use crate::adaptive::EffectiveThreshold;
+use crate::catalog::Catalog;
+use crate::detector::CharacteristicFunction;
+use crate::event::{Event, EventManager};
+use crate::filter::{BiquadChain, OutputUnits, deconvolve_response_to};
+use crate::intensity::{IntensityResult, IntensityScale, RealtimeIntensitySample};
+use crate::maintenance::Maintenance;
+use crate::picker::PhasePick;
+use crate::parser::stationxml::{ChannelResponse, infer_input_units};
+use crate::trigger::{AlertEvent, TriggerRegistry};
 use crate::web::history::{AlertHistoryManager, SharedHistory};
 use crate::web::spectrogram::compute_spectrogram;
 use axum::{
@@ -123,10 +129,17 @@ pub enum WsMessage {
     },
     Alert(AlertEvent),
     Intensity(IntensityResult),
+    /// Real-time intensity, several times per second
+    RealtimeIntensity(RealtimeIntensitySample),
+    /// Trigger characteristic function (STA/LTA ratio, Z value, amplitude, ...)
+    Characteristic(CharacteristicFunction),
     AlertStart {
         id: uuid::Uuid,
         channel: String,
+        trigger_name: String,
         timestamp: DateTime<Utc>,
+        /// Automatic P arrival, drawn as a marker on the plot
+        pick: Option<PhasePick>,
     },
     AlertEnd {
         id: uuid::Uuid,
@@ -135,6 +148,8 @@ pub enum WsMessage {
         max_ratio: f64,
         message: String,
     },
+    /// Declared, updated or closed event
+    Event(Event),
     #[serde(skip)]
     Spectrogram {
         channel: String,
@@ -153,6 +168,18 @@ pub struct WebState {
     pub station_name: Arc<RwLock<String>>,
     pub sensitivity_map: Arc<RwLock<HashMap<String, f64>>>,
     pub response_map: Arc<RwLock<HashMap<String, ChannelResponse>>>,
+    /// Thresholds in force per trigger and channel, keyed "trigger:channel"
+    pub thresholds: Arc<RwLock<HashMap<String, EffectiveThreshold>>>,
+    /// Maintenance windows and manual mute
+    pub maintenance: Arc<RwLock<Maintenance>>,
+    /// Open and recent events associated from the alerts
+    pub events: Arc<Mutex<EventManager>>,
+    /// Reference earthquake catalog for labelling events
+    pub catalog: Arc<Mutex<Catalog>>,
+    /// Live trigger definitions, editable through the API
+    pub triggers: Arc<RwLock<TriggerRegistry>>,
+    /// Scale intensities are reported in
+    pub intensity_scale: Arc<RwLock<IntensityScale>>,
 }
 
 impl Default for WebState {
@@ -189,6 +216,12 @@ impl WebState {
             station_name: Arc::new(RwLock::new(String::new())),
             sensitivity_map: Arc::new(RwLock::new(HashMap::new())),
             response_map: Arc::new(RwLock::new(HashMap::new())),
+            thresholds: Arc::new(RwLock::new(HashMap::new())),
+            maintenance: Arc::new(RwLock::new(Maintenance::default())),
+            events: Arc::new(Mutex::new(EventManager::default())),
+            catalog: Arc::new(Mutex::new(Catalog::default())),
+            triggers: Arc::new(RwLock::new(TriggerRegistry::default())),
+            intensity_scale: Arc::new(RwLock::new(IntensityScale::default())),
         }
     }
 
@@ -217,8 +250,20 @@ impl WebState {
         let _ = self.tx.send(WsMessage::Intensity(res));
     }
 
-    pub async fn broadcast_alert_start(&self, id: uuid::Uuid, channel: String, timestamp: DateTime<Utc>) {
-        let _ = self.tx.send(WsMessage::AlertStart { id, channel, timestamp });
+    pub async fn broadcast_realtime_intensity(&self, sample: RealtimeIntensitySample) {
+        let _ = self.tx.send(WsMessage::RealtimeIntensity(sample));
+    }
+
+    pub async fn broadcast_characteristic(&self, cf: CharacteristicFunction) {
+        let _ = self.tx.send(WsMessage::Characteristic(cf));
+    }
+
+    pub async fn broadcast_alert_start(&self, id: uuid::Uuid, channel: String, trigger_name: String, timestamp: DateTime<Utc>, pick: Option<PhasePick>) {
+        let _ = self.tx.send(WsMessage::AlertStart { id, channel, trigger_name, timestamp, pick });
+    }
+
+    pub async fn broadcast_event(&self, event: Event) {
+        let _ = self.tx.send(WsMessage::Event(event));
     }
 
     pub async fn broadcast_alert_end(&self, id: uuid::Uuid, channel: String, timestamp: DateTime<Utc>, max_ratio: f64, message: String) {
@@ -439,38 +484,40 @@ async fn handle_socket(socket: axum::extract::ws::WebSocket, state: WebState) {
     // This matches rsudp/obspy behavior — no high-frequency attenuation.
     let deconv_water_level = 4.5; // dB, matches rsudp default
 
+    let out_units = OutputUnits::parse(&units);
+
+    // Instrument response per channel: poles/zeros when available, otherwise a flat
+    // sensitivity-only response so unit conversion (DISP/ACC) still works.
+    let channel_response = |channel_id: &str| -> Option<ChannelResponse> {
+        if let Some(response) = resp_map.get(channel_id) {
+            return Some(response.clone());
+        }
+        match sens_map.get(channel_id) {
+            Some(&s) if s > 0.0 => Some(ChannelResponse::from_sensitivity(s, infer_input_units(channel_id))),
+            _ => None,
+        }
+    };
+
     // Helper: apply batch deconvolution (for backfill)
     let deconvolve_batch = |samples: &[f64], channel_id: &str, sample_rate: f64| -> Vec<f64> {
         if !deconvolve {
             return samples.to_vec();
         }
-        // Try frequency-domain deconvolution with poles/zeros
-        if let Some(response) = resp_map.get(channel_id) {
-            if !response.poles.is_empty() {
-                let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
-                let mut deconv = deconvolve_response(samples, response, sample_rate, pre_filt, deconv_water_level);
-                // Match rsudp: demean the deconvolved signal before filtering
-                // (rsudp calls stream.detrend('demean') after remove_response)
-                let vel_mean = deconv.iter().sum::<f64>() / deconv.len().max(1) as f64;
-                for s in &mut deconv {
-                    *s -= vel_mean;
-                }
-                // Apply extra divisor for GRAV units
-                if units.to_uppercase() == "GRAV" {
-                    for s in &mut deconv {
-                        *s /= 9.81;
-                    }
-                }
-                return deconv;
+        let Some(response) = channel_response(channel_id) else {
+            return samples.to_vec();
+        };
+        let spectral = !response.poles.is_empty() || out_units.derivative_order(&response.input_units) != 0;
+        let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
+        let mut deconv = deconvolve_response_to(samples, &response, sample_rate, pre_filt, deconv_water_level, out_units);
+        if spectral {
+            // Match rsudp: demean the deconvolved signal before filtering
+            // (rsudp calls stream.detrend('demean') after remove_response)
+            let mean = deconv.iter().sum::<f64>() / deconv.len().max(1) as f64;
+            for s in &mut deconv {
+                *s -= mean;
             }
         }
-        // Fallback: simple scalar division
-        let sensitivity = match sens_map.get(channel_id) {
-            Some(&s) if s > 0.0 => s,
-            _ => return samples.to_vec(),
-        };
-        let extra_divisor = if units.to_uppercase() == "GRAV" { 9.81 } else { 1.0 };
-        samples.iter().map(|&s| s / sensitivity / extra_divisor).collect()
+        deconv
     };
 
     // Per-channel raw sample context buffers for live FFT deconvolution.
@@ -709,71 +756,56 @@ async fn handle_socket(socket: axum::extract::ws::WebSocket, state: WebState) {
                         // Produces two outputs:
                         //   deconv_samples: unfiltered deconvolved (for spectrogram)
                         //   filtered_samples: bandpass-filtered forward-only (for waveform display)
-                        let (deconv_samples, filtered_samples) = if deconvolve {
-                            if let Some(response) = resp_map.get(channel) {
-                                if !response.poles.is_empty() {
-                                    // Add raw samples to context buffer
-                                    let deconv_context = (deconv_context_seconds * sample_rate) as usize;
-                                    let raw_buf = raw_context_bufs.entry(channel.clone()).or_default();
-                                    for &s in samples.iter() {
-                                        raw_buf.push_back(s);
-                                    }
-                                    while raw_buf.len() > deconv_context {
-                                        raw_buf.pop_front();
-                                    }
-
-                                    // Deconvolve the full context buffer
-                                    let buf_vec: Vec<f64> = raw_buf.iter().cloned().collect();
-                                    let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
-                                    let mut all_deconv = deconvolve_response(&buf_vec, response, sample_rate, pre_filt, deconv_water_level);
-
-                                    // Match rsudp: demean the deconvolved signal before filtering
-                                    let vel_mean = all_deconv.iter().sum::<f64>() / all_deconv.len().max(1) as f64;
-                                    for s in &mut all_deconv {
-                                        *s -= vel_mean;
-                                    }
-
-                                    // Extract unfiltered tail for spectrogram
-                                    let new_count = samples.len().min(all_deconv.len());
-                                    let unfilt_tail = all_deconv[all_deconv.len() - new_count..].to_vec();
-
-                                    // Apply forward-only bandpass filter to the full deconvolved window,
-                                    // then extract filtered tail for waveform display.
-                                    // This matches rsudp's actual behavior: obspy's filter() with default
-                                    // zerophase=False uses sosfilt (forward-only), applied to the entire
-                                    // accumulated stream with fresh filter state each update cycle.
-                                    let filt_tail = if filter_enabled && filter_highpass > 0.0 && filter_lowpass > filter_highpass {
-                                        let mut chain = BiquadChain::bandpass(filter_corners, filter_highpass, filter_lowpass, sample_rate);
-                                        let all_filtered = chain.process_vec(&all_deconv);
-                                        let n = new_count.min(all_filtered.len());
-                                        all_filtered[all_filtered.len() - n..].to_vec()
-                                    } else {
-                                        unfilt_tail.clone()
-                                    };
-
-                                    // Apply extra divisor for GRAV units
-                                    let (mut u, mut f) = (unfilt_tail, filt_tail);
-                                    if units.to_uppercase() == "GRAV" {
-                                        for s in &mut u { *s /= 9.81; }
-                                        for s in &mut f { *s /= 9.81; }
-                                    }
-                                    (u, f)
-                                } else {
-                                    // No poles/zeros — use simple scalar division
-                                    let sensitivity = response.sensitivity;
-                                    let extra_divisor = if units.to_uppercase() == "GRAV" { 9.81 } else { 1.0 };
-                                    let v: Vec<f64> = samples.iter().map(|&s| s / sensitivity / extra_divisor).collect();
-                                    (v.clone(), v)
+                        let response = if deconvolve { channel_response(channel) } else { None };
+                        let (deconv_samples, filtered_samples) = match response {
+                            Some(response) if !response.poles.is_empty() || out_units.derivative_order(&response.input_units) != 0 => {
+                                // Add raw samples to context buffer
+                                let deconv_context = (deconv_context_seconds * sample_rate) as usize;
+                                let raw_buf = raw_context_bufs.entry(channel.clone()).or_default();
+                                for &s in samples.iter() {
+                                    raw_buf.push_back(s);
+                                }
+                                while raw_buf.len() > deconv_context {
+                                    raw_buf.pop_front();
+                                }
+
+                                // Deconvolve the full context buffer (including unit conversion)
+                                let buf_vec: Vec<f64> = raw_buf.iter().cloned().collect();
+                                let pre_filt = [0.1, 0.6, 0.95 * sample_rate, sample_rate];
+                                let mut all_deconv = deconvolve_response_to(&buf_vec, &response, sample_rate, pre_filt, deconv_water_level, out_units);
+
+                                // Match rsudp: demean the deconvolved signal before filtering
+                                let vel_mean = all_deconv.iter().sum::<f64>() / all_deconv.len().max(1) as f64;
+                                for s in &mut all_deconv {
+                                    *s -= vel_mean;
                                 }
-                            } else if let Some(&sensitivity) = sens_map.get(channel) {
-                                let extra_divisor = if units.to_uppercase() == "GRAV" { 9.81 } else { 1.0 };
-                                let v: Vec<f64> = samples.iter().map(|&s| s / sensitivity / extra_divisor).collect();
+
+                                // Extract unfiltered tail for spectrogram
+                                let new_count = samples.len().min(all_deconv.len());
+                                let unfilt_tail = all_deconv[all_deconv.len() - new_count..].to_vec();
+
+                                // Apply forward-only bandpass filter to the full deconvolved window,
+                                // then extract filtered tail for waveform display.
+                                // This matches rsudp's actual behavior: obspy's filter() with default
+                                // zerophase=False uses sosfilt (forward-only), applied to the entire
+                                // accumulated stream with fresh filter state each update cycle.
+                                let filt_tail = if filter_enabled && filter_highpass > 0.0 && filter_lowpass > filter_highpass {
+                                    let mut chain = BiquadChain::bandpass(filter_corners, filter_highpass, filter_lowpass, sample_rate);
+                                    let all_filtered = chain.process_vec(&all_deconv);
+                                    let n = new_count.min(all_filtered.len());
+                                    all_filtered[all_filtered.len() - n..].to_vec()
+                                } else {
+                                    unfilt_tail.clone()
+                                };
+
+                                (unfilt_tail, filt_tail)
+                            }
+                            Some(response) => {
+                                // No poles/zeros and no unit conversion — use simple scalar division
+                                let v: Vec<f64> = samples.iter().map(|&s| s / response.sensitivity * out_units.scale()).collect();
                                 (v.clone(), v)
-                            } else {
-                                (samples.to_vec(), samples.to_vec())
                             }
-                        } else {
-                            (samples.to_vec(), samples.to_vec())
+                            None => (samples.to_vec(), samples.to_vec()),
                         };
 
                         let waveform_packet = serialize_waveform_packet(
@@ -832,11 +864,7 @@ async fn handle_socket(socket: axum::extract::ws::WebSocket, state: WebState) {
             // Poll the WebSocket receiver to process control frames (Ping/Close)
             ws_msg = receiver.next() => {
                 match ws_msg {
-                    Some(Ok(Message::Ping(data))) => {
-                        if sender.send(Message::Pong(data)).await.is_err() {
-                            break;
-                        }
-                    }
+                    Some(Ok(Message::Ping(data))) if sender.send(Message::Pong(data.clone())).await.is_err() => break,
                     Some(Ok(Message::Close(_))) | None => {
                         debug!("WebSocket client disconnected");
                         break;
@@ -21,7 +21,7 @@ struct BackgroundProcess {
 
 impl BackgroundProcess {
     fn new(mut cmd: Command, name: &str) -> Self {
-        let child = cmd.spawn().expect(&format!("Failed to spawn {}", name));
+        let child = cmd.spawn().unwrap_or_else(|_| panic!("Failed to spawn {}", name));
         Self {
             child,
             _name: name.to_string(),
@@ -52,7 +52,7 @@ fn test_e2e_alert_triggering() {
     let log_file = fs::File::create(&log_path).expect("Failed to create log file");
     
     let mut cmd = Command::new("cargo");
-    cmd.args(&["run", "--bin", "rsudp-rust", "--"])
+    cmd.args(["run", "--bin", "rsudp-rust", "--"])
        .arg("--udp-port").arg(udp_port.to_string())
        .arg("--web-port").arg(web_port.to_string())
        .arg("--station").arg("R6E01")
@@ -71,7 +71,7 @@ fn test_e2e_alert_triggering() {
     // T006: Spawn streamer (100x speed)
     let mseed_path = "../references/mseed/fdsnws.mseed";
     let mut streamer_cmd = Command::new("cargo");
-    streamer_cmd.args(&["run", "--bin", "streamer", "--"])
+    streamer_cmd.args(["run", "--bin", "streamer", "--"])
         .arg("--file").arg(mseed_path)
         .arg("--addr").arg(format!("127.0.0.1:{}", udp_port))
         .arg("--speed").arg("100.0");
@@ -115,14 +115,12 @@ fn test_e2e_alert_triggering() {
         if alarm_found && !png_found {
             fn find_png(dir: &std::path::Path) -> Option<std::path::PathBuf> {
                 if let Ok(entries) = fs::read_dir(dir) {
-                    for entry in entries {
-                        if let Ok(entry) = entry {
-                            let path = entry.path();
-                            if path.is_dir() {
-                                if let Some(found) = find_png(&path) { return Some(found); }
-                            } else if path.extension().and_then(|s| s.to_str()) == Some("png") {
-                                return Some(path);
-                            }
+                    for entry in entries.flatten() {
+                        let path = entry.path();
+                        if path.is_dir() {
+                            if let Some(found) = find_png(&path) { return Some(found); }
+                        } else if path.extension().and_then(|s| s.to_str()) == Some("png") {
+                            return Some(path);
                         }
                     }
                 }
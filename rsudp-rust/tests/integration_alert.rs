@@ -1,4 +1,6 @@
 use chrono::{Duration, Utc};
+use rsudp_rust::detector::DetectorKind;
+use rsudp_rust::filter::OutputUnits;
 use rsudp_rust::trigger::{AlertEventType, TriggerConfig, TriggerManager};
 use std::f64::consts::PI;
 
@@ -8,14 +10,19 @@ use std::f64::consts::PI;
 #[tokio::test]
 async fn test_sta_lta_trigger() {
     let mut tm = TriggerManager::new(TriggerConfig {
+        name: "default".to_string(),
         sta_sec: 1.0,
         lta_sec: 10.0,
         threshold: 3.0,
         reset_threshold: 1.5,
         highpass: 0.1,
         lowpass: 5.0,
+        corners: 4,
         target_channel: "HZ".to_string(),
         duration: 0.0,
+        detector: DetectorKind::Recursive,
+        deconvolve: false,
+        units: OutputUnits::Chan,
     });
 
     let id = "TEST.EHZ";
@@ -71,3 +78,232 @@ async fn test_sta_lta_trigger() {
     assert_eq!(ev.event_type, AlertEventType::Reset);
     println!("Reset at ratio: {:.4}, max: {:.4}", ev.ratio, ev.max_ratio);
 }
+
+/// Feed a 1 Hz background with a 10 Hz burst and report whether an ALARM fired.
+fn burst_triggers(highpass: f64, lowpass: f64, sps: f64) -> bool {
+    let mut tm = TriggerManager::new(TriggerConfig {
+        name: "default".to_string(),
+        sta_sec: 1.0,
+        lta_sec: 10.0,
+        threshold: 3.0,
+        reset_threshold: 1.5,
+        highpass,
+        lowpass,
+        corners: 4,
+        target_channel: "HZ".to_string(),
+        duration: 0.0,
+        detector: DetectorKind::Recursive,
+        deconvolve: false,
+        units: OutputUnits::Chan,
+    });
+    let id = "TEST.EHZ";
+    tm.set_sample_rate(id, sps);
+
+    let base_ts = Utc::now();
+    let n_background = (12.0 * sps) as usize;
+    let n_burst = (3.0 * sps) as usize;
+    for i in 0..(n_background + n_burst) {
+        let t = i as f64 / sps;
+        let mut val = (2.0 * PI * 1.0 * t).sin();
+        if i >= n_background {
+            // Ramp the burst in over 1 s so its onset carries no broadband step
+            let ramp = ((i - n_background) as f64 / sps).min(1.0);
+            val += 20.0 * ramp * (2.0 * PI * 10.0 * t).sin();
+        }
+        let ts = base_ts + Duration::microseconds((t * 1_000_000.0) as i64);
+        if let Some(ev) = tm.add_sample(id, val, ts, 1.0) {
+            if ev.event_type == AlertEventType::Trigger {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// The configured band must control what the trigger sees: a 10 Hz burst is
+/// invisible through the default 0.1-2 Hz band but triggers a 5-20 Hz band,
+/// at both 100 and 50 SPS.
+#[test]
+fn test_trigger_honors_configured_band_and_sample_rate() {
+    for sps in [100.0, 50.0] {
+        assert!(!burst_triggers(0.1, 2.0, sps), "0.1-2 Hz band triggered on 10 Hz burst at {} SPS", sps);
+        assert!(burst_triggers(5.0, 20.0, sps), "5-20 Hz band missed 10 Hz burst at {} SPS", sps);
+    }
+}
+
+/// Every detector is selectable per trigger, fires on a strong onset, and streams
+/// its characteristic function one packet at a time for plotting.
+#[test]
+fn test_selectable_detectors_and_characteristic_output() {
+    let sps = 100.0;
+    for (detector, threshold) in [
+        (DetectorKind::Recursive, 3.0),
+        (DetectorKind::Classic, 3.0),
+        (DetectorKind::Delayed, 3.0),
+        (DetectorKind::ZDetect, 3.0),
+        (DetectorKind::Amplitude, 20.0),
+    ] {
+        let mut tm = TriggerManager::new(TriggerConfig {
+            name: "cf".to_string(),
+            sta_sec: 1.0,
+            lta_sec: 10.0,
+            threshold,
+            reset_threshold: 1.5,
+            highpass: 0.1,
+            lowpass: 5.0,
+            corners: 4,
+            target_channel: "HZ".to_string(),
+            duration: 0.0,
+            detector,
+            deconvolve: false,
+            units: OutputUnits::Chan,
+        })
+        .with_characteristic_output();
+
+        let id = "TEST.EHZ";
+        let base_ts = Utc::now();
+        let mut triggered = false;
+        let mut cf_values = 0;
+        for i in 0..2500 {
+            let amp = if i >= 2000 { 100.0 } else { 1.0 };
+            let val = amp * (2.0 * PI * i as f64 / sps).sin();
+            let ts = base_ts + Duration::milliseconds(i as i64 * 10);
+            if let Some(ev) = tm.add_sample(id, val, ts, 1.0) {
+                triggered |= ev.event_type == AlertEventType::Trigger;
+            }
+            for cf in tm.drain_characteristic() {
+                assert_eq!(cf.trigger_name, "cf");
+                assert_eq!(cf.values.len(), 25);
+                cf_values += cf.values.len();
+            }
+        }
+        assert!(triggered, "{:?} detector did not trigger", detector);
+        assert!(cf_values > 0, "{:?} detector produced no characteristic function", detector);
+    }
+}
+
+/// Feed `velocity` (m/s, 2 Hz) recorded by a sensor with `sensitivity` counts per m/s
+/// into an amplitude trigger with a threshold of 1e-5 m/s.
+fn physical_amplitude_triggers(velocity: f64, sensitivity: f64) -> bool {
+    let mut tm = TriggerManager::new(TriggerConfig {
+        name: "physical".to_string(),
+        sta_sec: 1.0,
+        lta_sec: 10.0,
+        threshold: 1e-5,
+        reset_threshold: 5e-6,
+        highpass: 0.5,
+        lowpass: 5.0,
+        corners: 4,
+        target_channel: "HZ".to_string(),
+        duration: 0.0,
+        detector: DetectorKind::Amplitude,
+        deconvolve: true,
+        units: OutputUnits::Vel,
+    });
+    let id = "AM.R0001.00.EHZ";
+    let base_ts = Utc::now();
+    (0..2000).any(|i| {
+        let counts = velocity * sensitivity * (2.0 * PI * 2.0 * i as f64 / 100.0).sin();
+        let ts = base_ts + Duration::milliseconds(i as i64 * 10);
+        tm.add_sample(id, counts, ts, sensitivity)
+            .is_some_and(|ev| ev.event_type == AlertEventType::Trigger)
+    })
+}
+
+/// With deconvolution the threshold is in m/s, so one config works for Shakes
+/// with very different gains.
+#[test]
+fn test_trigger_on_physical_units() {
+    for sensitivity in [3.99e8, 4.0e5] {
+        assert!(physical_amplitude_triggers(2e-5, sensitivity), "missed 2e-5 m/s at sensitivity {}", sensitivity);
+        assert!(!physical_amplitude_triggers(5e-6, sensitivity), "triggered on 5e-6 m/s at sensitivity {}", sensitivity);
+    }
+}
+
+/// The ALARM carries an AIC P pick close to the true onset, earlier than the
+/// STA/LTA threshold crossing.
+#[test]
+fn test_trigger_carries_p_pick() {
+    let mut tm = TriggerManager::new(TriggerConfig {
+        name: "default".to_string(),
+        sta_sec: 1.0,
+        lta_sec: 10.0,
+        threshold: 3.0,
+        reset_threshold: 1.5,
+        highpass: 0.5,
+        lowpass: 10.0,
+        corners: 4,
+        target_channel: "HZ".to_string(),
+        duration: 0.0,
+        detector: DetectorKind::Recursive,
+        deconvolve: false,
+        units: OutputUnits::Chan,
+    });
+    let sps = 100.0;
+    let onset = 1500;
+    let base_ts = Utc::now();
+    let onset_ts = base_ts + Duration::milliseconds(onset as i64 * 10);
+    let mut seed = 7_u64;
+    let alarm = (0..3000).find_map(|i| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let noise = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+        let signal = if i >= onset { 30.0 * (2.0 * PI * 3.0 * (i - onset) as f64 / sps).sin() } else { 0.0 };
+        let ts = base_ts + Duration::milliseconds(i as i64 * 10);
+        tm.add_sample("TEST.EHZ", noise + signal, ts, 1.0)
+            .filter(|ev| ev.event_type == AlertEventType::Trigger)
+    });
+
+    let alarm = alarm.expect("Should have triggered");
+    let pick = alarm.pick.expect("ALARM should carry a P pick");
+    assert_eq!(pick.phase, "P");
+    assert!(pick.time <= alarm.timestamp);
+    let error_ms = (pick.time - onset_ts).num_milliseconds().abs();
+    assert!(error_ms <= 100, "pick off by {} ms", error_ms);
+    assert!(pick.uncertainty > 0.0 && pick.uncertainty < 0.5, "uncertainty {}", pick.uncertainty);
+}
+
+/// Changing the parameters of a running trigger keeps its window: a burst right
+/// after the change is detected without waiting for a new LTA warm-up.
+#[test]
+fn test_reconfigure_keeps_warm_state() {
+    let config = TriggerConfig {
+        name: "default".to_string(),
+        sta_sec: 1.0,
+        lta_sec: 10.0,
+        threshold: 1000.0,
+        reset_threshold: 1.5,
+        highpass: 0.1,
+        lowpass: 5.0,
+        corners: 4,
+        target_channel: "HZ".to_string(),
+        duration: 0.0,
+        detector: DetectorKind::Recursive,
+        deconvolve: false,
+        units: OutputUnits::Chan,
+    };
+    let mut tm = TriggerManager::new(config.clone());
+    let sps = 100.0;
+    let base_ts = Utc::now();
+    let sample = |i: usize| {
+        let amplitude = if i >= 1500 { 100.0 } else { 1.0 };
+        amplitude * (2.0 * PI * 1.0 * i as f64 / sps).sin()
+    };
+    let ts = |i: usize| base_ts + Duration::milliseconds(i as i64 * 10);
+    for i in 0..1500 {
+        let ev = tm.add_sample("TEST.EHZ", sample(i), ts(i), 1.0);
+        assert!(ev.is_none_or(|ev| ev.event_type != AlertEventType::Trigger));
+    }
+
+    let invalid = TriggerConfig { lta_sec: 0.5, ..config.clone() };
+    assert!(invalid.validate().is_err());
+    let lowered = TriggerConfig { threshold: 3.0, sta_sec: 0.5, ..config };
+    lowered.validate().expect("valid config");
+    tm.reconfigure(lowered.clone());
+    assert_eq!(tm.config(), &lowered);
+
+    // A fresh manager would need 10 s of data before its first evaluation
+    let alarm = (1500..1700).find_map(|i| {
+        tm.add_sample("TEST.EHZ", sample(i), ts(i), 1.0).filter(|ev| ev.event_type == AlertEventType::Trigger)
+    });
+    assert!(alarm.is_some(), "reconfigured trigger lost its warm-up");
+}
@@ -0,0 +1,41 @@
+use chrono::Utc;
+use rsudp_rust::conditioning::SignalConditioner;
+use rsudp_rust::pipeline::run_pipeline;
+use rsudp_rust::settings::{AlertSoundSettings, CaptureSettings, ConditioningSettings};
+use rsudp_rust::web::WebState;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+/// A spike fed through the pipeline is removed before the triggers and kept in the
+/// alert history as a suppressed entry naming the artefact.
+#[tokio::test]
+async fn test_spike_is_recorded_as_suppressed() {
+    // 10 s of low-level noise at 100 Hz with one large single-sample spike at 6 s
+    let mut samples: Vec<f64> = (0..1000).map(|i| ((i * 7919) % 201) as f64 - 100.0).collect();
+    samples[600] += 50_000.0;
+    // Recent enough to stay within the 24 h the history keeps
+    let t0 = (Utc::now().timestamp() - 60) as f64;
+
+    let (tx, rx) = mpsc::channel(64);
+    let web_state = WebState::new();
+    let conditioner = SignalConditioner::new(ConditioningSettings { enabled: true, ..Default::default() });
+    let pipeline = tokio::spawn(run_pipeline(
+        rx, Vec::new(), None, None, web_state.clone(), HashMap::new(), None, None, None,
+        AlertSoundSettings::default(), None, None, None, Some(conditioner), None, None, None, None, None,
+        CaptureSettings::default(),
+    ));
+    for (k, chunk) in samples.chunks(25).enumerate() {
+        let values: Vec<String> = chunk.iter().map(|v| v.to_string()).collect();
+        let packet = format!("{{'EHZ', {:.2}, {}}}", t0 + k as f64 * 0.25, values.join(", "));
+        tx.send(packet.into_bytes()).await.unwrap();
+    }
+    drop(tx);
+    pipeline.await.unwrap();
+
+    let history = web_state.history.lock().unwrap().get_events();
+    let suppressed: Vec<_> = history.iter().filter(|e| e.suppressed.is_some()).collect();
+    assert_eq!(suppressed.len(), 1, "{:?}", history);
+    assert_eq!(suppressed[0].suppressed.as_deref(), Some("spike"));
+    assert_eq!(suppressed[0].channel, "EHZ");
+    assert_eq!(suppressed[0].trigger_time.timestamp_millis(), (t0 as i64 + 6) * 1000);
+}
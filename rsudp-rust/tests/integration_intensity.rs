@@ -16,6 +16,7 @@ fn test_intensity_with_verified_samples() {
         channels: vec!["ENE".to_string(), "ENN".to_string(), "ENZ".to_string()],
         sample_rate: 100.0,
         sensitivities: vec![1.0 / 384500.0, 1.0 / 384500.0, 1.0 / 384500.0],
+        ..Default::default()
     });
 
     let mut lines = reader.lines();
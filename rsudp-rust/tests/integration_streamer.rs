@@ -9,5 +9,4 @@
 fn test_streamer_integration_placeholder() {
     // Placeholder to satisfy T007 requirements if a separate file is needed.
     // Real logic is in the binary's internal tests.
-    assert!(true);
 }
\ No newline at end of file
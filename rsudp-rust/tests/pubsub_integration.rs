@@ -82,7 +82,7 @@ async fn publish_batch(client: &Client, topic_name: &str, batch: &SeismicBatch)
     attributes.insert("station".to_string(), batch.station.clone());
 
     let msg = PubsubMessage {
-        data: data.into(),
+        data,
         attributes,
         ordering_key: batch.station.clone(),
         ..Default::default()
@@ -35,11 +35,8 @@ async fn recv_with_timeout(socket: &UdpSocket, timeout_ms: u64) -> Option<Vec<u8
 /// Helper: drain all packets from a UDP socket within a timeout.
 async fn recv_all_with_timeout(socket: &UdpSocket, timeout_ms: u64) -> Vec<Vec<u8>> {
     let mut results = Vec::new();
-    loop {
-        match recv_with_timeout(socket, timeout_ms).await {
-            Some(data) => results.push(data),
-            None => break,
-        }
+    while let Some(data) = recv_with_timeout(socket, timeout_ms).await {
+        results.push(data);
     }
     results
 }
@@ -0,0 +1,130 @@
+use rsudp_rust::intensity::filter::JmaFilter;
+use rsudp_rust::parser::mseed::parse_mseed_file;
+use rustfft::{num_complex::Complex, FftPlanner};
+use std::path::Path;
+
+/// Band-limited resampling of a whole record to `to` Hz in the frequency domain.
+fn resample(data: &[f64], from: f64, to: f64) -> Vec<f64> {
+    let n = data.len();
+    let m = (n as f64 * to / from).round() as usize;
+    let mut planner = FftPlanner::new();
+    let mut spectrum: Vec<Complex<f64>> = data.iter().map(|&v| Complex::new(v, 0.0)).collect();
+    planner.plan_fft_forward(n).process(&mut spectrum);
+
+    let mut out = vec![Complex::new(0.0, 0.0); m];
+    let half = n.min(m) / 2;
+    for k in 0..half {
+        out[k] = spectrum[k];
+        if k > 0 {
+            out[m - k] = spectrum[n - k];
+        }
+    }
+    planner.plan_fft_inverse(m).process(&mut out);
+    out.iter().map(|c| c.re / n as f64).collect()
+}
+
+/// Intensity of the recorded fdsnws.mseed earthquake at its native 100 Hz and
+/// resampled to 50, 200 and 250 Hz: the JMA filter must agree at every rate.
+#[test]
+#[ignore = "needs ../references/mseed/fdsnws.mseed; run with --ignored"]
+fn test_recorded_intensity_at_each_sample_rate() {
+    let path = "../references/mseed/fdsnws.mseed";
+    assert!(Path::new(path).exists(), "{} not found", path);
+
+    let mut segments = parse_mseed_file(path).expect("Should parse fdsnws.mseed");
+    segments.sort_by_key(|s| s.starttime);
+    let channels = ["ENZ", "ENN", "ENE"];
+    let records: Vec<Vec<f64>> = channels
+        .iter()
+        .map(|ch| {
+            segments
+                .iter()
+                .filter(|s| s.channel == *ch)
+                .flat_map(|s| s.samples.iter().map(|&c| c * 100.0 / 384500.0))
+                .collect()
+        })
+        .collect();
+    assert!(records.iter().all(|r| !r.is_empty()), "fdsnws.mseed lacks ENZ/ENN/ENE");
+    let sps = segments.iter().find(|s| s.channel == "ENZ").unwrap().sampling_rate;
+
+    // The 60 s around the strongest horizontal shaking
+    let len = records.iter().map(|r| r.len()).min().unwrap();
+    let window = ((60.0 * sps) as usize).min(len);
+    let peak = (0..len).max_by(|&a, &b| {
+        let h = |i: usize| records[1][i].powi(2) + records[2][i].powi(2);
+        h(a).total_cmp(&h(b))
+    }).unwrap();
+    let start = peak.saturating_sub(window / 2).min(len - window);
+    let components: Vec<Vec<f64>> = records.iter().map(|r| r[start..start + window].to_vec()).collect();
+
+    let native = JmaFilter::new(sps).calculate_intensity_components(&components);
+    println!("fdsnws.mseed: {:.1} at {} Hz", native, sps);
+    for rate in [50.0, 200.0, 250.0] {
+        let resampled: Vec<Vec<f64>> = components.iter().map(|c| resample(c, sps, rate)).collect();
+        let intensity = JmaFilter::new(rate).calculate_intensity_components(&resampled);
+        println!("fdsnws.mseed: {:.1} at {} Hz", intensity, rate);
+        assert!((intensity - native).abs() <= 0.1, "{} Hz: {} vs {} at {} Hz", rate, intensity, native, sps);
+    }
+}
+
+/// Steady tones (gal, Hz, rad) that repeat every 2 s, so a 60 s window holds whole periods.
+type Tones = [&'static [(f64, f64, f64)]; 3];
+
+/// Reference intensities of steady multi-tone records: the published JMA weighting
+/// (√(1/f), high-cut in f/10 Hz, low-cut at 0.5 Hz) applied analytically to each tone,
+/// the vector sum evaluated in continuous time, and `a` taken as the level exceeded
+/// 0.3 s in 60 s. Independent of any sampling, so every rate must reproduce them.
+#[test]
+fn test_reference_intensities_at_each_sample_rate() {
+    let references: [(Tones, f64); 3] = [
+        // 4.647
+        ([&[(19.0, 2.0, 0.0)], &[(57.0, 1.0, 0.0), (24.0, 3.0, 0.7)], &[(43.0, 1.0, 1.9), (14.0, 5.0, 0.3)]], 4.6),
+        // 2.735: long-period motion, where the low-cut dominates
+        ([&[(3.0, 7.0, 0.0)], &[(7.0, 0.5, 0.0)], &[(5.0, 0.5, std::f64::consts::FRAC_PI_2)]], 2.7),
+        // 6.155
+        ([&[(150.0, 1.5, 0.4)], &[(290.0, 1.0, 0.0), (115.0, 4.0, 1.1)], &[(240.0, 0.5, 2.0), (75.0, 2.5, 0.6)]], 6.1),
+    ];
+    for (tones, reference) in references {
+        for fs in [50.0, 100.0, 200.0, 250.0] {
+            let components: Vec<Vec<f64>> = tones
+                .iter()
+                .map(|component| {
+                    (0..(60.0 * fs) as usize)
+                        .map(|i| {
+                            let t = i as f64 / fs;
+                            component.iter().map(|&(a, f, phase)| a * (2.0 * std::f64::consts::PI * f * t + phase).sin()).sum()
+                        })
+                        .collect()
+                })
+                .collect();
+            let intensity = JmaFilter::new(fs).calculate_intensity_components(&components);
+            assert!((intensity - reference).abs() < 1e-9, "{} Hz: {} vs reference {}", fs, intensity, reference);
+        }
+    }
+}
+
+/// A synthetic three-component record built at each rate from the same
+/// continuous signal gives the same intensity.
+#[test]
+fn test_synthetic_intensity_at_each_sample_rate() {
+    // 1.2 Hz and 3.5 Hz wave groups (gal) under a 4 s envelope
+    let signal = |t: f64, phase: f64| {
+        let envelope = (-((t - 30.0) / 4.0).powi(2)).exp();
+        envelope * (40.0 * (2.0 * std::f64::consts::PI * 1.2 * t + phase).sin() + 25.0 * (2.0 * std::f64::consts::PI * 3.5 * t + 2.0 * phase).sin())
+    };
+    let intensities: Vec<f64> = [50.0, 100.0, 200.0, 250.0]
+        .iter()
+        .map(|&fs| {
+            let components: Vec<Vec<f64>> = [0.0, 1.0, 2.0]
+                .iter()
+                .map(|&phase| (0..(60.0 * fs) as usize).map(|i| signal(i as f64 / fs, phase)).collect())
+                .collect();
+            JmaFilter::new(fs).calculate_intensity_components(&components)
+        })
+        .collect();
+    println!("synthetic: {:?}", intensities);
+    assert!(intensities[1] > 3.0, "{:?}", intensities);
+    for intensity in &intensities {
+        assert!((intensity - intensities[1]).abs() <= 0.1, "{:?}", intensities);
+    }
+}
@@ -274,14 +274,16 @@ fn test_rsam_deconvolution_grav() {
     let mut settings = make_settings(port);
     settings.deconvolve = true;
     settings.units = "GRAV".to_string();
+    // Accelerometer channel: GRAV is a plain rescale of ACC, no differentiation
+    settings.channel = "ENZ".to_string();
 
     let mut sens_map = HashMap::new();
-    sens_map.insert("EHZ".to_string(), 100.0);
+    sens_map.insert("ENZ".to_string(), 100.0);
 
     let mut mgr = RsamManager::new(&settings, sens_map).unwrap();
 
     // Feed [981.0] → 981/100/9.81 ≈ 1.0
-    let seg = make_segment("EHZ", "TEST", vec![981.0]);
+    let seg = make_segment("ENZ", "TEST", vec![981.0]);
     mgr.process_segment(&seg);
 
     let result = mgr.calculate().unwrap();
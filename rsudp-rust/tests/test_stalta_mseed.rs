@@ -1,4 +1,6 @@
 use chrono::Duration;
+use rsudp_rust::detector::DetectorKind;
+use rsudp_rust::filter::OutputUnits;
 use rsudp_rust::parser::mseed::parse_mseed_file;
 use rsudp_rust::trigger::{AlertEventType, TriggerConfig, TriggerManager};
 use std::path::Path;
@@ -42,17 +44,23 @@ fn test_streaming_stalta_shindo0() {
     );
 
     let mut tm = TriggerManager::new(TriggerConfig {
+        name: "default".to_string(),
         sta_sec: 6.0,
         lta_sec: 30.0,
         threshold: 1.1,
         reset_threshold: 0.5,
         highpass: 0.1,
         lowpass: 2.0,
+        corners: 4,
         target_channel: "HZ".to_string(),
         duration: 0.0,
+        detector: DetectorKind::Recursive,
+        deconvolve: false,
+        units: OutputUnits::Chan,
     });
 
     let nslc = ehz_segments[0].nslc();
+    tm.set_sample_rate(&nslc, sps);
     let mut alarm_events = Vec::new();
     let mut reset_events = Vec::new();
 
@@ -77,7 +85,7 @@ fn test_streaming_stalta_shindo0() {
                     );
                     reset_events.push(ev);
                 }
-                AlertEventType::Status => {}
+                AlertEventType::Status | AlertEventType::Early => {}
             }
         }
     }
@@ -21,7 +21,7 @@ const AlertSettingsPanel: React.FC = () => {
     fetch(`${getBackendOrigin()}/api/alerts/settings`, {
       method: 'PUT',
       headers: { 'Content-Type': 'application/json' },
-      body: JSON.stringify(settings),
+      body: JSON.stringify({ ...settings, triggers: (settings.triggers ?? []).filter(t => t) }),
     })
       .then(() => setIsSaving(false))
       .catch(err => {
@@ -114,6 +114,15 @@ const AlertSettingsPanel: React.FC = () => {
               className="w-full text-xs p-2 rounded border border-gray-600 bg-[#2a2f3d] text-gray-300"
             />
           </div>
+          <div>
+            <label className="block text-[10px] font-black uppercase text-gray-500 mb-1">Triggers (comma separated, empty = all)</label>
+            <input
+              type="text"
+              value={(settings.triggers ?? []).join(', ')}
+              onChange={e => setSettings({...settings, triggers: e.target.value.split(',').map(t => t.trim())})}
+              className="w-full text-xs p-2 rounded border border-gray-600 bg-[#2a2f3d] text-gray-300"
+            />
+          </div>
         </div>
       )}
 
@@ -34,6 +34,124 @@ export interface IntensityResult {
   instrumental_intensity: number;
   intensity_class: string;
   timestamp: string;
+  scale?: 'Jma' | 'Mmi' | 'Cwa' | 'Csis';
+}
+
+export interface RealtimeIntensitySample {
+  timestamp: string;
+  intensity: number; // real-time JMA intensity Ir
+  acceleration: number; // filtered vector acceleration (gal)
+}
+
+export interface PhasePick {
+  phase: string;
+  channel: string;
+  time: string;
+  uncertainty: number; // seconds, one sigma
+}
+
+export interface EarlyWarning {
+  channel: string;
+  tau_c: number;
+  pd: number;
+  magnitude: number;
+  pgv: number;
+  intensity: number;
+  window: number;
+}
+
+export interface EffectiveThreshold {
+  trigger_name: string;
+  channel: string;
+  threshold: number;
+  reset: number;
+  noise: number | null;
+  reference: number | null;
+  scale: number;
+}
+
+export interface SeismicEvent {
+  id: string;
+  state: 'declared' | 'updated' | 'closed';
+  start: string;
+  end: string | null;
+  alerts: string[];
+  channels: string[];
+  triggers: string[];
+  max_ratio: number;
+  max_intensity: number | null;
+  pga: number | null;
+  picks: PhasePick[];
+  muted: boolean;
+  catalog?: CatalogMatch | null;
+  long_period?: LongPeriodMotion | null;
+  spectrum?: ResponseSpectrum | null;
+}
+
+// GET /api/events/:id/spectrum
+export interface ResponseSpectrum {
+  damping: number;
+  periods: number[]; // s
+  components: { channel: string; psa: number[] }[]; // m/s²
+  max_horizontal: number[]; // m/s²
+}
+
+export interface LongPeriodMotion {
+  class: number; // JMA long-period ground motion class 0-4
+  sva: number; // cm/s
+  period: number; // s
+}
+
+export interface CatalogMatch {
+  event: {
+    id: string;
+    origin_time: string;
+    latitude: number;
+    longitude: number;
+    depth: number;
+    magnitude: number | null;
+  };
+  distance: number;
+  predicted_p: string;
+  residual: number;
+}
+
+export interface MuteStatus {
+  muted: boolean;
+  reason: string | null;
+  manual: { until: string; reason: string } | null;
+}
+
+export interface TriggerConfig {
+  name: string;
+  sta_sec: number;
+  lta_sec: number;
+  threshold: number;
+  reset_threshold: number;
+  highpass: number;
+  lowpass: number;
+  corners: number;
+  target_channel: string;
+  duration: number;
+  detector: string; // Recursive, Classic, Delayed, ZDetect, Amplitude
+  deconvolve: boolean;
+  units: string;
+}
+
+export interface TriggerAuditEntry {
+  time: string;
+  trigger: string;
+  before: TriggerConfig;
+  after: TriggerConfig;
+  reason: string;
+}
+
+export interface CharacteristicFunction {
+  channel: string;
+  trigger_name: string;
+  timestamp: string; // time of the first value
+  sample_rate: number;
+  values: number[];
 }
 
 export interface AlertSettings {
@@ -45,6 +163,7 @@ export interface AlertSettings {
   smtp_user: string;
   smtp_pass: string;
   email_recipient: string;
+  triggers?: string[]; // trigger names routed to email (empty = all)
   save_pct: number;
 }
 
@@ -74,8 +193,11 @@ export interface IntensityIndicatorState {
 
 export type WsMessage =
   | { type: 'Waveform', data: WaveformPacket }
-  | { type: 'Alert', data: { timestamp: string, channel: string, message: string } }
+  | { type: 'Alert', data: { timestamp: string, channel: string, message: string, event_type?: string, early_warning?: EarlyWarning | null } }
   | { type: 'Intensity', data: IntensityResult }
-  | { type: 'AlertStart', data: { id: string, channel: string, timestamp: string } }
+  | { type: 'RealtimeIntensity', data: RealtimeIntensitySample }
+  | { type: 'Characteristic', data: CharacteristicFunction }
+  | { type: 'AlertStart', data: { id: string, channel: string, trigger_name: string, timestamp: string, pick: PhasePick | null } }
   | { type: 'AlertEnd', data: { id: string, channel: string, timestamp: string, max_ratio: number, message: string } }
+  | { type: 'Event', data: SeismicEvent }
   | { type: 'BackfillComplete', data: { channels: string[] } };